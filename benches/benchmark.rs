@@ -1,4 +1,4 @@
-use a_fractal_a_day::fractal::{Ising, Boundary};
+use a_fractal_a_day::fractal::{Ising, Boundary, mandelbrot_lanes};
 use criterion::{Criterion, criterion_group, criterion_main};
 
 use num::Complex;
@@ -69,6 +69,26 @@ fn mandelbrot_vec_complex3(zs: &[Complex<f64>], n: usize) -> Vec<Complex<f64>> {
     out
 }
 
+// the whole set, where most pixels are interior points
+fn mandelbrot_grid(n: usize) -> Vec<Complex<f64>> {
+    (0..n*n).map(|k| Complex::new(-2.2 + 3. * (k % n) as f64 / n as f64, -1.5 + 3. * (k / n) as f64 / n as f64))
+            .collect()
+}
+
+// one pixel after the other until it escapes, like `MandelbrotFractal::iterate`
+fn mandelbrot_per_pixel(zs: &[Complex<f64>], n: usize) -> Vec<usize> {
+    zs.iter().map(|&start| {
+            let mut z = start;
+            let mut ctr = 0;
+            while {
+                z = z * z + start;
+                ctr += 1;
+                z.norm_sqr() < 4. && ctr < n
+            } {}
+            ctr
+        }).collect()
+}
+
 fn ising_helical(l: usize, sweeps: usize) {
     let mut ising = Ising::new(l as u32, l as u32, Boundary::Helical, 42);
     for _ in 0..sweeps {
@@ -116,6 +136,19 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("mandelbrot vec complex 3",
         |b| b.iter(|| mandelbrot_vec_complex3(&input, 100))
     );
+
+    let grid = mandelbrot_grid(32);
+    c.bench_function("mandelbrot per pixel",
+        |b| b.iter(|| mandelbrot_per_pixel(&grid, 1000))
+    );
+
+    c.bench_function("mandelbrot lanes",
+        |b| b.iter(|| mandelbrot_lanes(&grid, 1000, 4., false))
+    );
+
+    c.bench_function("mandelbrot lanes interior",
+        |b| b.iter(|| mandelbrot_lanes(&grid, 1000, 4., true))
+    );
 }
 
 criterion_group!(benches, criterion_benchmark);
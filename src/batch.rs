@@ -0,0 +1,304 @@
+//! Render many fractals described by a recipe file.
+//!
+//! A recipe lists jobs, every job renders one image per seed of its seed
+//! range. Finished renders are recorded in a report next to the images,
+//! such that an interrupted run can be resumed by starting it again.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Mutex;
+
+use log::{info, warn};
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::FractalType;
+use crate::error::{FractalError, from_json};
+use crate::histogram::BoundsTypes;
+use crate::fractal::{FractalBuilder, Normalization, Style, Supersampling, Symmetry, Variation, render_wrapper};
+use crate::fractal::plugin;
+
+fn default_output() -> String {
+    "batch".to_string()
+}
+
+fn default_resolution() -> (u32, u32) {
+    (2400, 1350)
+}
+
+fn default_name() -> String {
+    "{type}_{seed}".to_string()
+}
+
+/// a list of render jobs, usually read from a json file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recipe {
+    /// directory for the images, the json files and the report
+    #[serde(default = "default_output")]
+    pub output: String,
+    /// size of the thread pool shared by all jobs, defaults to the number of CPUs
+    #[serde(default)]
+    pub threads: Option<usize>,
+    pub jobs: Vec<Job>,
+}
+
+/// render one image of `fractal_type` for every seed in `seeds`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    #[serde(rename = "type")]
+    pub fractal_type: FractalType,
+    pub seeds: Range<usize>,
+    #[serde(default = "default_resolution")]
+    pub resolution: (u32, u32),
+    /// name of a built-in style or a full definition, see `Style`
+    #[serde(default)]
+    pub style: Option<Style>,
+    #[serde(default)]
+    pub normalization: Option<Normalization>,
+    #[serde(default)]
+    pub variation: Option<Variation>,
+    #[serde(default)]
+    pub symmetry: Option<Symmetry>,
+    #[serde(default)]
+    pub bounds: Option<BoundsTypes>,
+    #[serde(default)]
+    pub supersampling: Supersampling,
+    /// file name without extension, `{type}` and `{seed}` are replaced
+    #[serde(default = "default_name")]
+    pub name: String,
+}
+
+/// name of a fractal type which is safe to use in file names
+fn type_name(fractal_type: &FractalType) -> String {
+    let name = match *fractal_type {
+        FractalType::Plugin(ref name) => name.clone(),
+        FractalType::LoadJson(_) => "LoadJson".to_string(),
+        ref x => x.to_string(),
+    };
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+impl Job {
+    /// the type which is rendered for `seed`, `Random` is resolved
+    fn fractal_type(&self, seed: usize) -> FractalType {
+        match self.fractal_type {
+            FractalType::Random => plugin::random_type(seed),
+            ref x => x.clone(),
+        }
+    }
+
+    fn filename(&self, seed: usize) -> String {
+        self.name.replace("{type}", &type_name(&self.fractal_type(seed)))
+                 .replace("{seed}", &seed.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Verdict {
+    /// the image passed the quality estimates
+    Accepted,
+    /// the fractal looked boring, no image is kept
+    Rejected,
+    /// the render failed with this error
+    Failed(String),
+}
+
+/// outcome of a single seed of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Render {
+    pub job: usize,
+    pub seed: usize,
+    pub name: String,
+    pub verdict: Verdict,
+    pub description: String,
+}
+
+/// all renders of a recipe which are finished
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Report {
+    pub renders: Vec<Render>,
+}
+
+impl Report {
+    fn save(&self, path: &Path) -> Result<(), FractalError> {
+        // write to a temporary file first, such that an interrupted
+        // write does not destroy the progress
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self).expect("the report is always serializable");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// the accepted seeds of a job
+    pub fn accepted(&self, job: usize) -> Vec<usize> {
+        self.seeds(job, |v| *v == Verdict::Accepted)
+    }
+
+    /// the rejected and failed seeds of a job
+    pub fn rejected(&self, job: usize) -> Vec<usize> {
+        self.seeds(job, |v| *v != Verdict::Accepted)
+    }
+
+    fn seeds<F>(&self, job: usize, filter: F) -> Vec<usize>
+        where F: Fn(&Verdict) -> bool
+    {
+        let mut seeds: Vec<usize> = self.renders.iter()
+                                         .filter(|r| r.job == job && filter(&r.verdict))
+                                         .map(|r| r.seed)
+                                         .collect();
+        seeds.sort_unstable();
+        seeds
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut jobs: Vec<usize> = self.renders.iter().map(|r| r.job).collect();
+        jobs.sort_unstable();
+        jobs.dedup();
+        for job in jobs {
+            writeln!(f, "job {}: accepted {:?}, rejected {:?}", job, self.accepted(job), self.rejected(job))?;
+        }
+        for r in &self.renders {
+            if let Verdict::Failed(ref e) = r.verdict {
+                writeln!(f, "{} failed: {}", r.name, e)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Recipe {
+    pub fn from_file(filename: &str) -> Result<Recipe, FractalError> {
+        let json = fs::read_to_string(filename)?;
+        let recipe: Recipe = from_json(&json, "recipe")?;
+        recipe.check_names()?;
+        Ok(recipe)
+    }
+
+    /// every render needs its own file name, otherwise the images of
+    /// different jobs overwrite each other
+    fn check_names(&self) -> Result<(), FractalError> {
+        let mut names: HashMap<String, usize> = HashMap::new();
+        for (n, job) in self.jobs.iter().enumerate() {
+            for seed in job.seeds.clone() {
+                if let Some(other) = names.insert(job.filename(seed), n) {
+                    return Err(FractalError::InvalidArgument(format!(
+                        "jobs {} and {} both render '{}', give them distinct names", other, n, job.filename(seed)
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// render all jobs, skipping the renders already accepted or rejected
+    /// in the report, failed renders are tried again
+    pub fn run(&self) -> Result<Report, FractalError> {
+        self.check_names()?;
+        let output = Path::new(&self.output);
+        fs::create_dir_all(output)?;
+        let report_path = output.join("report.json");
+
+        let report = if report_path.exists() {
+            let json = fs::read_to_string(&report_path)?;
+            from_json::<Report>(&json, "batch report")?
+        } else {
+            Report::default()
+        };
+
+        let done: HashSet<(usize, usize)> = report.renders.iter()
+                                                  .filter(|r| !matches!(r.verdict, Verdict::Failed(_)))
+                                                  .map(|r| (r.job, r.seed))
+                                                  .collect();
+        if !done.is_empty() {
+            info!("resume batch, {} renders are already done", done.len());
+        }
+
+        let tasks: Vec<(usize, usize)> = self.jobs.iter()
+            .enumerate()
+            .flat_map(|(n, job)| job.seeds.clone().map(move |seed| (n, seed)))
+            .filter(|task| !done.contains(task))
+            .collect();
+
+        let mut pool = rayon::ThreadPoolBuilder::new();
+        if let Some(threads) = self.threads {
+            pool = pool.num_threads(threads);
+        }
+        let pool = pool.build().map_err(|e| FractalError::InvalidArgument(e.to_string()))?;
+
+        let report = Mutex::new(report);
+        pool.install(|| {
+            tasks.par_iter().try_for_each(|&(n, seed)| {
+                let render = self.render(n, seed, output);
+                let mut report = report.lock().expect("report is poisoned");
+                // a retried render replaces its failure
+                match report.renders.iter().position(|r| r.job == render.job && r.seed == render.seed) {
+                    Some(idx) => report.renders[idx] = render,
+                    None => report.renders.push(render),
+                }
+                report.save(&report_path)
+            })
+        })?;
+
+        Ok(report.into_inner().expect("report is poisoned"))
+    }
+
+    fn render(&self, n: usize, seed: usize, output: &Path) -> Render {
+        let job = &self.jobs[n];
+        let name = job.filename(seed);
+        let img = output.join(format!("{}.png", name));
+        let img = img.to_string_lossy();
+
+        let result = self.render_job(job, seed, &img);
+
+        let (verdict, description) = match result {
+            Ok((true, description, json)) => {
+                match fs::write(output.join(format!("{}.json", name)), json) {
+                    Ok(()) => (Verdict::Accepted, description),
+                    Err(e) => (Verdict::Failed(FractalError::from(e).to_string()), description),
+                }
+            }
+            Ok((false, description, _)) => {
+                // the image might not exist, if it was rejected before rendering
+                let _ = fs::remove_file(&*img);
+                (Verdict::Rejected, description)
+            }
+            Err(e) => {
+                warn!("{}: {}", name, e);
+                (Verdict::Failed(e.to_string()), String::new())
+            }
+        };
+        info!("{}: {:?}", name, verdict);
+
+        Render {
+            job: n,
+            seed,
+            name,
+            verdict,
+            description,
+        }
+    }
+
+    fn render_job(&self, job: &Job, seed: usize, filename: &str) -> Result<(bool, String, String), FractalError> {
+        let fractal_type = job.fractal_type(seed);
+
+        let mut fractal = FractalBuilder::new()
+                                         .seed(seed)
+                                         .style(&job.style)
+                                         .normalization(&job.normalization)
+                                         .variation(&job.variation)
+                                         .symmetry(&job.symmetry)
+                                         .bounds(&job.bounds)
+                                         .dimensions(&Some(job.resolution))
+                                         .build(&fractal_type)?;
+
+        render_wrapper(&mut fractal, filename, &job.resolution, job.supersampling)
+    }
+}
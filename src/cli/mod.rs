@@ -0,0 +1,207 @@
+//! The command line interface of `a_fractal_a_day`.
+//!
+//! The binary is a thin wrapper around `run`. A crate providing its own
+//! fractals registers them with `fractal::plugin::register` and calls `run`
+//! afterwards, such that they are available by `--plugin` and as random
+//! fractals.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::prelude::*;
+
+use log::{info, error};
+use simplelog::{CombinedLogger, SimpleLogger, WriteLogger, LevelFilter, Config};
+
+use crate::{FractalType, batch, postprocess_image};
+use crate::fractal::{FractalBuilder, render_wrapper, plugin};
+use crate::error::FractalError;
+
+#[cfg(feature = "my_twitter")]
+use my_twitter::twitter as twitter;
+
+mod parse_cl;
+pub use self::parse_cl::{parse_cl, Options};
+
+// only log errors to stdout, but everything to a log file
+fn init_logging(quiet: bool) {
+    let level = if quiet {LevelFilter::Error} else {LevelFilter::Info};
+    log_panics::init();
+    let _ = CombinedLogger::init(
+        vec![
+            SimpleLogger::new(level, Config::default()),
+            WriteLogger::new(level, Config::default(),
+                             fs::OpenOptions::new().append(true)
+                                                   .create(true)
+                                                   .open("fractals.log")
+                                                   .expect("Failed to create log file!")),
+            WriteLogger::new(LevelFilter::Debug, Config::default(),
+                             fs::OpenOptions::new().append(true)
+                                                   .create(true)
+                                                   .open("fractals.debug")
+                                                   .expect("Failed to create log file!"))
+        ]
+    );
+}
+
+
+fn prepare(filename: &str) -> (String, String) {
+    fs::create_dir_all("img").expect("could not create output directory");
+    fs::create_dir_all("json").expect("could not create output directory");
+
+    let img = format!("img/{}.png", filename);
+    let json = format!("json/{}.json", filename);
+
+    (img, json)
+}
+
+fn build_fractal(
+    filename: &str,
+    seed: usize,
+    opt: &Options
+) -> Result<(String, String), FractalError>
+{
+    let mut description;
+    let mut json;
+    let mut ctr = 0;
+    let mut fractal_type: FractalType = opt.fractal_type.clone();
+
+    if let FractalType::Random = fractal_type {
+        fractal_type = plugin::random_type(seed);
+    };
+
+    let dim = (opt.width.unwrap_or(2400), opt.height.unwrap_or(1350));
+
+    // hacky do while loop
+    while {
+        let mut fractal = FractalBuilder::new()
+                                         .seed(seed+ctr)
+                                         .bounds(&opt.bounds)
+                                         .style(&opt.style)
+                                         .normalization(&opt.normalization)
+                                         .trap(&opt.trap)
+                                         .variation(&opt.variation)
+                                         .symmetry(&opt.symmetry)
+                                         .vibrancy(&opt.vibrancy)
+                                         .gamma(&opt.gamma)
+                                         .iterations(&opt.iterations)
+                                         .rules(&opt.rules)
+                                         .angle(&opt.angle)
+                                         .rpn(&opt.rpn)
+                                         .infix(&opt.formula)
+                                         .zoom(&opt.zoom)
+                                         .center(&opt.center)
+                                         .parameter(&opt.parameter)
+                                         .precise_center(&opt.precise_center)
+                                         .exponent(&opt.exponent)
+                                         .basins(&opt.basins)
+                                         .root_finding(&opt.root_finding)
+                                         .sequence(&opt.sequence)
+                                         .qmaprule(&opt.qmaprule)
+                                         .limits(&opt.limits)
+                                         .anti(&opt.anti)
+                                         .dimensions(&Some(dim))
+                                         .params(&opt.params)
+                                         .build(&fractal_type)?;
+
+        let (finished, tmp_description, tmp_json)
+            = render_wrapper(&mut fractal, filename, &dim, opt.supersampling)?;
+
+        description = tmp_description;
+        json = tmp_json;
+        ctr += 1;
+        ! finished
+    } {}
+
+    Ok((description, json))
+}
+
+#[cfg(feature = "my_twitter")]
+fn tweet(filename: &str, description: &str) {
+    use std::thread::sleep;
+    use std::time;
+    twitter::tweet_image(description, filename)
+            .or_else(|_| {
+                    info!("Upload to Twitter failed!");
+                    info!("Try again in 60 seconds.");
+                    sleep(time::Duration::from_secs(60));
+                    twitter::tweet_image(description, filename)
+                }
+            )
+            .or_else(|_| {
+                    info!("Upload to Twitter failed again!");
+                    info!("Try again in 10 minutes.");
+                    sleep(time::Duration::from_secs(600));
+                    twitter::tweet_image(description, filename)
+                }
+            )
+            .expect("Uploading to twitter failed three times! Panic!");
+}
+
+/// Run the command line interface with the arguments `args`, the first
+/// of which is the name of the program.
+///
+/// Plugins have to be registered before, errors are logged and returned.
+pub fn run<I, T>(args: I) -> Result<(), FractalError>
+    where I: IntoIterator<Item=T>,
+          T: Into<OsString> + Clone
+{
+    let timestamp = time::now_utc().to_timespec().sec;
+
+    let opt = parse_cl(args).map_err(|e| {
+        eprintln!("{}", e);
+        e
+    })?;
+    init_logging(opt.quiet);
+    info!("Start!");
+    info!("{}", opt);
+
+    if opt.tweet && cfg!(not(feature = "my_twitter")) {
+        let e = FractalError::InvalidArgument("tweeting needs the feature `binaries`".to_string());
+        error!("{}", e);
+        return Err(e)
+    }
+
+    if let Some(ref filename) = opt.batch {
+        let report = batch::Recipe::from_file(filename)
+                                   .and_then(|recipe| recipe.run())
+                                   .map_err(|e| {
+                                       error!("{}", e);
+                                       e
+                                   })?;
+        print!("{}", report);
+        return Ok(())
+    }
+
+    let seed = opt.seed.unwrap_or(timestamp as usize);
+    let filename = opt.filename.clone().unwrap_or_else(|| timestamp.to_string());
+    let (file_img, file_json) = prepare(&filename);
+
+    info!("start generation with seed {}", seed);
+
+    #[cfg_attr(not(feature = "my_twitter"), allow(unused_variables))]
+    let (description, json) = build_fractal(&file_img, seed, &opt).map_err(|e| {
+        error!("{}", e);
+        e
+    })?;
+    let mut file = fs::File::create(file_json)?;
+    file.write_all(json.as_bytes())?;
+
+    info!("image saved as {}", file_img);
+
+    if opt.optipng {
+        postprocess_image(&file_img);
+    }
+
+    #[cfg(feature = "my_twitter")]
+    if opt.tweet {
+        let for_twitter = format!("{}_for_twitter.png", file_img);
+        crate::postprocess_image_for_twitter(&file_img, &for_twitter);
+        info!("start upload to twitter");
+        tweet(&for_twitter, &description);
+        info!("tweeted");
+        fs::remove_file(&for_twitter).unwrap_or_else(|_| log::warn!("could not delete {}", for_twitter));
+    }
+
+    info!("Success!");
+    Ok(())
+}
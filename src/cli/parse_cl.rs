@@ -1,25 +1,33 @@
-use a_fractal_a_day::histogram::BoundsTypes;
-use clap::{App, Arg, ArgGroup};
+use clap::{App, Arg, ArgGroup, ArgMatches};
 
+use std::ffi::OsString;
 use std::f64::consts::PI;
 use std::fmt;
 use std::fs;
 use std::io::prelude::*;
+use std::str::FromStr;
+
+use crate::histogram::BoundsTypes;
+use crate::error::FractalError;
+use crate::color::RGB;
 
 use crate::FractalType;
-use crate::fractal::{Style, Variation, Symmetry, Lrules};
+use crate::fractal::{Style, Variation, Symmetry, Lrules, Supersampling, Normalization, RootFinding, TrapKind};
+use crate::fractal::plugin::PluginParams;
 
 #[derive(Debug)]
 pub struct Options {
     pub seed: Option<usize>,
     pub filename: Option<String>,
     pub style: Option<Style>,
+    pub normalization: Option<Normalization>,
+    pub trap: Option<TrapKind>,
     pub height: Option<u32>,
     pub width: Option<u32>,
     pub tweet: bool,
     pub quiet: bool,
     pub optipng: bool,
-    pub supersampling: bool,
+    pub supersampling: Supersampling,
     pub bounds: Option<BoundsTypes>,
     pub fractal_type: FractalType,
     pub variation: Option<Variation>,
@@ -30,9 +38,20 @@ pub struct Options {
     pub rules: Option<Lrules>,
     pub angle: Option<f64>,
     pub rpn: Option<String>,
-    pub zoom: Option<u64>,
+    pub formula: Option<String>,
+    pub zoom: Option<f64>,
     pub center: Option<(f64, f64)>,
+    pub parameter: Option<(f64, f64)>,
+    pub precise_center: Option<(String, String)>,
+    pub exponent: Option<f64>,
+    pub basins: Option<RGB>,
+    pub root_finding: Option<RootFinding>,
+    pub sequence: Option<String>,
     pub qmaprule: Option<String>,
+    pub limits: Option<[u64; 3]>,
+    pub anti: Option<bool>,
+    pub params: Option<PluginParams>,
+    pub batch: Option<String>,
 }
 
 impl fmt::Display for Options {
@@ -50,8 +69,37 @@ impl fmt::Display for Options {
     }
 }
 
-// FIXME: return result with an clError, instead of panicing
-pub fn parse_cl() -> Options {
+/// parse the value of argument `name`, if it is present
+fn parse_value<T: FromStr>(matches: &ArgMatches, name: &str, expected: &str) -> Result<Option<T>, FractalError> {
+    matches.value_of(name)
+           .map(|s| s.parse::<T>().map_err(|_|
+               FractalError::InvalidArgument(format!("{} needs to be {}, but is '{}'", name, expected, s))
+           ))
+           .transpose()
+}
+
+/// parse the value of argument `name` as a point `x,y`
+fn parse_point(matches: &ArgMatches, name: &str) -> Result<Option<(f64, f64)>, FractalError> {
+    match matches.value_of(name) {
+        Some(s) => {
+            let invalid = || FractalError::InvalidArgument(format!("{} needs the format `x,y`, but is '{}'", name, s));
+            let c: Vec<&str> = s.split(',').collect();
+            if c.len() != 2 {
+                return Err(invalid())
+            }
+            let x = c[0].parse::<f64>().map_err(|_| invalid())?;
+            let y = c[1].parse::<f64>().map_err(|_| invalid())?;
+            Ok(Some((x, y)))
+        }
+        None => Ok(None)
+    }
+}
+
+/// parse the arguments `args`, the first one is the name of the program
+pub fn parse_cl<I, T>(args: I) -> Result<Options, FractalError>
+    where I: IntoIterator<Item=T>,
+          T: Into<OsString> + Clone
+{
     let matches = App::new(env!("CARGO_PKG_NAME"))
               .version(env!("CARGO_PKG_VERSION"))
               .about(env!("CARGO_PKG_DESCRIPTION"))
@@ -76,7 +124,19 @@ pub fn parse_cl() -> Options {
                     .short("s")
                     .long("style")
                     .takes_value(true)
-                    .help("the name of the style applied to visualize")
+                    .help("the style applied to visualize: `vibrant`, `spooky`, `strong`, `pastell`, `distance`, `trap`, `trap_angle` or a style definition in JSON")
+              )
+              .arg(Arg::with_name("normalization")
+                    .long("normalization")
+                    .takes_value(true)
+                    .help("how iteration counts are mapped to colors: `linear`, `log`, `sqrt`, `equalize` or `percentile:low,high`")
+                    .requires("escape_time")
+              )
+              .arg(Arg::with_name("trap")
+                    .long("trap")
+                    .takes_value(true)
+                    .help("color by the closest approach of orbits to a random `point`, `line`, `cross`, `circle` or `image`, not supported by newton, nova and lyapunov fractals, see the styles `trap` and `trap_angle`")
+                    .requires("escape_time")
               )
               .arg(Arg::with_name("height")
                     .short("y")
@@ -92,7 +152,9 @@ pub fn parse_cl() -> Options {
               )
               .arg(Arg::with_name("supersampling")
                     .long("supersampling")
-                    .help("sample the fractal at 4x the resolution")
+                    .takes_value(true)
+                    .min_values(0)
+                    .help("average multiple samples per pixel: `grid:n`, `jittered:n` or `adaptive:n` (default `grid:2`)")
               )
               .arg(Arg::with_name("quiet")
                     .short("q")
@@ -114,10 +176,60 @@ pub fn parse_cl() -> Options {
                     .help("render a julia fractal")
                     .group("escape_time")
               )
+              .arg(Arg::with_name("quadratic-julia")
+                    .long("quadratic-julia")
+                    .help("render the julia fractal of `z^2 + c`, with a `c` close to the boundary of the Mandelbrot set, see --parameter")
+                    .group("escape_time")
+                    .group("zoomable")
+                    .group("quadratic")
+              )
               .arg(Arg::with_name("mandelbrot")
                     .long("mandelbrot")
                     .help("render a mandelbrot fractal")
                     .group("escape_time")
+                    .group("zoomable")
+              )
+              .arg(Arg::with_name("burning-ship")
+                    .long("burning-ship")
+                    .help("render a burning ship fractal")
+                    .group("escape_time")
+                    .group("zoomable")
+              )
+              .arg(Arg::with_name("tricorn")
+                    .long("tricorn")
+                    .help("render a tricorn (mandelbar) fractal")
+                    .group("escape_time")
+                    .group("zoomable")
+              )
+              .arg(Arg::with_name("multibrot")
+                    .long("multibrot")
+                    .help("render a multibrot fractal, see --exponent")
+                    .group("escape_time")
+                    .group("zoomable")
+              )
+              .arg(Arg::with_name("phoenix")
+                    .long("phoenix")
+                    .help("render a phoenix fractal")
+                    .group("escape_time")
+                    .group("zoomable")
+              )
+              .arg(Arg::with_name("nova")
+                    .long("nova")
+                    .help("render a nova fractal")
+                    .group("escape_time")
+                    .group("zoomable")
+              )
+              .arg(Arg::with_name("lyapunov")
+                    .long("lyapunov")
+                    .help("render a Markus-Lyapunov fractal, see --sequence")
+                    .group("escape_time")
+                    .group("zoomable")
+              )
+              .arg(Arg::with_name("generic-mandelbrot")
+                    .long("generic-mandelbrot")
+                    .help("render the Mandelbrot set of `f(z) + c`, for the function of --formula or --rpn or a random one")
+                    .group("escape_time")
+                    .group("zoomable")
               )
               .arg(Arg::with_name("dragon")
                     .long("dragon")
@@ -161,7 +273,7 @@ pub fn parse_cl() -> Options {
               )
               .arg(Arg::with_name("json")
                     .long("json")
-                    .help("load a fractal from a file (only ifs)")
+                    .help("load a fractal from a json file")
                     .takes_value(true)
                     .group("iterated_function_system")
               )
@@ -245,12 +357,44 @@ pub fn parse_cl() -> Options {
                     .help("render the double pendulum (fractal dimension 2, I guess)")
                     .group("ode")
               )
+              .arg(Arg::with_name("buddhabrot")
+                    .long("buddhabrot")
+                    .help("render a Buddhabrot, a Nebulabrot or their anti variants, see --limits and --anti")
+                    .group("buddha")
+              )
+              .arg(Arg::with_name("julia-iim")
+                    .long("julia-iim")
+                    .help("render the julia set of `z^2 + c` by inverse iteration, see --parameter")
+                    .group("buddha")
+                    .group("quadratic")
+              )
+              .arg(Arg::with_name("batch")
+                    .long("batch")
+                    .takes_value(true)
+                    .help("render all jobs of a json recipe, resumes an interrupted run")
+              )
+              .arg(Arg::with_name("plugin")
+                    .long("plugin")
+                    .takes_value(true)
+                    .help("render a fractal type registered by a plugin")
+                    .group("plugins")
+              )
+              .arg(Arg::with_name("param")
+                    .long("param")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .help("pass a parameter `key=value` to the plugin, can be repeated")
+                    .requires("plugin")
+              )
               .group(ArgGroup::with_name("iterated_function_system")
                   .conflicts_with("escape_time")
                   .conflicts_with("lsystem")
                   .conflicts_with("lattice")
                   .conflicts_with("qmap")
                   .conflicts_with("ode")
+                  .conflicts_with("plugins")
+                  .conflicts_with("buddha")
               )
               .group(ArgGroup::with_name("escape_time")
                   .conflicts_with("iterated_function_system")
@@ -258,6 +402,8 @@ pub fn parse_cl() -> Options {
                   .conflicts_with("lattice")
                   .conflicts_with("qmap")
                   .conflicts_with("ode")
+                  .conflicts_with("plugins")
+                  .conflicts_with("buddha")
               )
               .group(ArgGroup::with_name("lsystem")
                   .conflicts_with("iterated_function_system")
@@ -265,6 +411,8 @@ pub fn parse_cl() -> Options {
                   .conflicts_with("lattice")
                   .conflicts_with("qmap")
                   .conflicts_with("ode")
+                  .conflicts_with("plugins")
+                  .conflicts_with("buddha")
               )
               .group(ArgGroup::with_name("lattice")
                   .conflicts_with("iterated_function_system")
@@ -272,6 +420,8 @@ pub fn parse_cl() -> Options {
                   .conflicts_with("lsystem")
                   .conflicts_with("qmap")
                   .conflicts_with("ode")
+                  .conflicts_with("plugins")
+                  .conflicts_with("buddha")
               )
               .group(ArgGroup::with_name("qmap")
                   .conflicts_with("iterated_function_system")
@@ -279,6 +429,8 @@ pub fn parse_cl() -> Options {
                   .conflicts_with("lsystem")
                   .conflicts_with("lattice")
                   .conflicts_with("ode")
+                  .conflicts_with("plugins")
+                  .conflicts_with("buddha")
               )
               .group(ArgGroup::with_name("ode")
                   .conflicts_with("iterated_function_system")
@@ -286,6 +438,26 @@ pub fn parse_cl() -> Options {
                   .conflicts_with("lsystem")
                   .conflicts_with("lattice")
                   .conflicts_with("qmap")
+                  .conflicts_with("plugins")
+                  .conflicts_with("buddha")
+              )
+              .group(ArgGroup::with_name("plugins")
+                  .conflicts_with("iterated_function_system")
+                  .conflicts_with("escape_time")
+                  .conflicts_with("lsystem")
+                  .conflicts_with("lattice")
+                  .conflicts_with("qmap")
+                  .conflicts_with("ode")
+                  .conflicts_with("buddha")
+              )
+              .group(ArgGroup::with_name("buddha")
+                  .conflicts_with("iterated_function_system")
+                  .conflicts_with("escape_time")
+                  .conflicts_with("lsystem")
+                  .conflicts_with("lattice")
+                  .conflicts_with("qmap")
+                  .conflicts_with("ode")
+                  .conflicts_with("plugins")
               )
               .group(ArgGroup::with_name("symmetry")
                   .conflicts_with("escape_time")
@@ -348,23 +520,74 @@ pub fn parse_cl() -> Options {
               .arg(Arg::with_name("rpn")
                   .long("rpn")
                   .long("reverse-polish-notation")
-                  .help("give a formula to visualize as a newton fractal in reverse polish
+                  .help("give a formula to visualize as a newton, julia or generic mandelbrot fractal in reverse polish
                         notation, must be enclosed in quotations")
                   .takes_value(true)
                   .requires("escape_time")
               )
+              .arg(Arg::with_name("formula")
+                  .long("formula")
+                  .help("give a formula to visualize as a newton, julia or generic mandelbrot fractal in infix notation,
+                        e.g., \"z^3 - 2*sin(z) + (0.5+1i)\", must be enclosed in quotations")
+                  .takes_value(true)
+                  .conflicts_with("rpn")
+                  .requires("escape_time")
+              )
               .arg(Arg::with_name("zoom")
                   .long("zoom")
-                  .help("how deep to zoom into the complex plane for Mandelbrot. The visible square will have a side length of 1/zoom")
+                  .help("how deep to zoom into the complex plane for Mandelbrot like fractals. The visible square will have a side length of 1/zoom, deep zooms like 1e50 are only supported for Mandelbrot")
                   .takes_value(true)
-                  .requires("mandelbrot")
+                  .requires("zoomable")
               )
               .arg(Arg::with_name("center")
                   .long("center")
-                  .help("where to look at on the complex plane for Mandelbrot. Format: `x,y`")
+                  .help("where to look at on the complex plane for Mandelbrot like fractals. Format: `x,y`, all digits are used for deep zooms")
                   .takes_value(true)
                   .allow_hyphen_values(true)
-                  .requires("mandelbrot")
+                  .requires("zoomable")
+              )
+              .arg(Arg::with_name("parameter")
+                  .long("parameter")
+                  .help("the parameter `c` of a quadratic julia set `z^2 + c`. Format: `x,y`")
+                  .takes_value(true)
+                  .allow_hyphen_values(true)
+                  .requires("quadratic")
+              )
+              .arg(Arg::with_name("exponent")
+                  .long("exponent")
+                  .help("the exponent `d` of the multibrot `z^d + c`, a real number larger than 1")
+                  .takes_value(true)
+                  .requires("multibrot")
+              )
+              .arg(Arg::with_name("basins")
+                  .long("basins")
+                  .help("color a newton fractal by the root every point converges to, optionally with the color of points which do not converge, e.g., `#ffffff` (default black)")
+                  .takes_value(true)
+                  .min_values(0)
+                  .requires("newton")
+              )
+              .arg(Arg::with_name("root-finding")
+                  .long("root-finding")
+                  .help("the iteration of a newton fractal: `newton`, `halley`, `schroeder` or `householder:d` with the order `d`")
+                  .takes_value(true)
+                  .requires("newton")
+              )
+              .arg(Arg::with_name("sequence")
+                  .long("sequence")
+                  .help("the sequence of the parameters of a Lyapunov fractal, e.g., `AABAB`")
+                  .takes_value(true)
+                  .requires("lyapunov")
+              )
+              .arg(Arg::with_name("limits")
+                  .long("limits")
+                  .help("the iteration limit of a Buddhabrot `n`, or one per color channel `r,g,b` for a Nebulabrot")
+                  .takes_value(true)
+                  .requires("buddhabrot")
+              )
+              .arg(Arg::with_name("anti")
+                  .long("anti")
+                  .help("render an anti-Buddhabrot of the orbits which do not escape")
+                  .requires("buddhabrot")
               )
               .arg(Arg::with_name("iterations")
                     .short("N")
@@ -406,22 +629,37 @@ pub fn parse_cl() -> Options {
                     .help("specify how the image is cropped")
 
               )
-              .get_matches();
+              .get_matches_from(args);
 
     let tweet = matches.is_present("tweet");
     let quiet = matches.is_present("quiet");
     let optipng = !matches.is_present("no-optipng");
-    let supersampling = matches.is_present("supersampling");
+    let supersampling = if matches.is_present("supersampling") {
+        match matches.value_of("supersampling") {
+            Some(s) => s.parse()?,
+            None => Supersampling::Grid(2),
+        }
+    } else {
+        Supersampling::None
+    };
     let filename = matches.value_of("filename")
                           .and_then(|f| Some(f.to_string()))
                           .or_else(|| None);
     // test if style is valid
     let style = match matches.value_of("style")
     {
-        Some(x) => Some(Style::from_string(x).expect(&format!("Invalid Style {}", x))),
+        Some(x) => Some(x.parse()?),
         None => None
     };
 
+    let normalization = matches.value_of("normalization")
+                               .map(|s| s.parse())
+                               .transpose()?;
+
+    let trap = matches.value_of("trap")
+                      .map(|s| s.parse())
+                      .transpose()?;
+
     let bounds = match matches.value_of("bound") {
         Some("strict") => Some(BoundsTypes::StrictBounds),
         Some("loose") => Some(BoundsTypes::BoundsWithoutOutliers),
@@ -430,62 +668,95 @@ pub fn parse_cl() -> Options {
         _ => unreachable!()
     };
 
-    let seed = matches.value_of("seed")
-                      .and_then(|s| Some(s.parse::<usize>().expect("seed needs to be an integer")))
-                      .or_else(|| None);
+    let seed = parse_value::<usize>(&matches, "seed", "an integer")?;
 
-    let height = matches.value_of("height")
-                        .and_then(|s| Some(s.parse::<u32>().expect("height needs to be an integer")))
-                        .or_else(|| None);
-    let width = matches.value_of("width")
-                       .and_then(|s| Some(s.parse::<u32>().expect("width needs to be an integer")))
-                       .or_else(|| None);
+    let height = parse_value::<u32>(&matches, "height", "an integer")?;
+    let width = parse_value::<u32>(&matches, "width", "an integer")?;
 
-    let vibrancy = matches.value_of("vibrancy")
-                       .and_then(|s| Some(s.parse::<f64>().expect("vibrancy needs to be a number")))
-                       .or_else(|| None);
-    let gamma = matches.value_of("gamma")
-                       .and_then(|s| Some(s.parse::<f64>().expect("gamma needs to be a number")))
-                       .or_else(|| None);
+    let vibrancy = parse_value::<f64>(&matches, "vibrancy", "a number")?;
+    let gamma = parse_value::<f64>(&matches, "gamma", "a number")?;
 
     let rpn = matches.value_of("rpn")
-                     .and_then(|f| Some(f.to_string()))
-                     .or_else(|| None);
+                     .map(|f| f.to_string());
+    let formula = matches.value_of("formula")
+                         .map(|f| f.to_string());
+
+    let zoom = parse_value::<f64>(&matches, "zoom", "a number")?;
+    // keep the digits of the center, deep zooms need more than f64
+    let precise_center = matches.value_of("center")
+                                .and_then(|s| {
+                                    let mut c = s.splitn(2, ',');
+                                    Some((c.next()?.trim().to_owned(), c.next()?.trim().to_owned()))
+                                });
+    let center = parse_point(&matches, "center")?;
+    let parameter = parse_point(&matches, "parameter")?;
+
+    let exponent = parse_value::<f64>(&matches, "exponent", "a number")?;
+    let root_finding = matches.value_of("root-finding")
+                              .map(|s| s.parse())
+                              .transpose()?;
+    let basins = if matches.is_present("basins") {
+        match matches.value_of("basins") {
+            Some(s) => Some(s.parse()?),
+            None => Some(RGB(0., 0., 0.)),
+        }
+    } else {
+        None
+    };
 
-    let zoom = matches.value_of("zoom")
-                     .and_then(|s| Some(s.parse::<u64>().expect("zoom needs to be a number")))
-                     .or_else(|| None);
-    let center = matches.value_of("center")
-                     .and_then(|s| {
-                         let c: Vec<&str> = s.split(",").collect();
-                         if c.len() != 2 {panic!("center needs the format `x,y`")}
-                         let x = c[0].parse::<f64>().expect("center needs the format `x,y`");
-                         let y = c[1].parse::<f64>().expect("center needs the format `x,y`");
-                         Some((x, y))
-                     })
-                     .or_else(|| None);
+    let limits = match matches.value_of("limits") {
+        Some(s) => {
+            let invalid = || FractalError::InvalidArgument(format!("limits need the format `n` or `r,g,b` with integers of at least 2, but is '{}'", s));
+            // orbits which escape in the first step leave no points
+            let l = s.split(',')
+                     .map(|x| x.trim().parse::<u64>().ok().filter(|&n| n >= 2))
+                     .collect::<Option<Vec<u64>>>()
+                     .ok_or_else(invalid)?;
+            match l.len() {
+                1 => Some([l[0], l[0], l[0]]),
+                3 => Some([l[0], l[1], l[2]]),
+                _ => return Err(invalid())
+            }
+        }
+        None => None
+    };
+    let anti = if matches.is_present("anti") { Some(true) } else { None };
 
-    let iterations = matches.value_of("iterations")
-                       .and_then(|s| Some(s.parse::<u32>().expect("iterations needs to be a unsigned integer")))
-                       .or_else(|| None);
-    let angle = matches.value_of("angle")
-                       .and_then(|s| Some(s.parse::<f64>().expect("angle needs to be a number") / 180.*PI))
-                       .or_else(|| None);
+    let iterations = parse_value::<u32>(&matches, "iterations", "an unsigned integer")?;
+    let angle = parse_value::<f64>(&matches, "angle", "a number")?
+                       .map(|a| a / 180.*PI);
 
     let rules = match (matches.value_of("start"), matches.value_of("rules"))
     {
-        (Some(s), Some(r)) => Some(Lrules::from_string(s, r)),
+        (Some(s), Some(r)) => Some(Lrules::from_string(s, r)?),
         _ => None
     };
 
+    let sequence = matches.value_of("sequence").map(|x| x.to_owned());
     let qmaprule = matches.value_of("qmaprule").map(|x| x.to_owned());
 
     let fractal_type = if matches.is_present("newton") {
         FractalType::Newton
     } else if matches.is_present("julia") {
         FractalType::Julia
+    } else if matches.is_present("quadratic-julia") {
+        FractalType::QuadraticJulia
     } else if matches.is_present("mandelbrot") {
         FractalType::Mandelbrot
+    } else if matches.is_present("burning-ship") {
+        FractalType::BurningShip
+    } else if matches.is_present("tricorn") {
+        FractalType::Tricorn
+    } else if matches.is_present("multibrot") {
+        FractalType::Multibrot
+    } else if matches.is_present("phoenix") {
+        FractalType::Phoenix
+    } else if matches.is_present("nova") {
+        FractalType::Nova
+    } else if matches.is_present("lyapunov") {
+        FractalType::Lyapunov
+    } else if matches.is_present("generic-mandelbrot") {
+        FractalType::GenericMandelbrot
     } else if matches.is_present("dragon") {
         FractalType::HeighwayDragon
     } else if matches.is_present("fern") {
@@ -504,9 +775,9 @@ pub fn parse_cl() -> Options {
         FractalType::FractalFlame
     } else if matches.is_present("json") {
         let filename = matches.value_of("json").expect("file need to be specified");
-        let mut file = fs::File::open(filename).expect("can not open file");
+        let mut file = fs::File::open(filename)?;
         let mut json = String::new();
-        file.read_to_string(&mut json).expect("can not read file");
+        file.read_to_string(&mut json)?;
         FractalType::LoadJson(json)
     } else if matches.is_present("kochcurve") {
         FractalType::KochCurve
@@ -540,10 +811,33 @@ pub fn parse_cl() -> Options {
         FractalType::Thomas
     } else if matches.is_present("doublependulum") {
         FractalType::DoublePendulum
+    } else if matches.is_present("buddhabrot") {
+        FractalType::Buddhabrot
+    } else if matches.is_present("julia-iim") {
+        FractalType::JuliaIim
+    } else if let Some(name) = matches.value_of("plugin") {
+        FractalType::Plugin(name.to_string())
     } else {
         FractalType::Random
     };
 
+    let batch = matches.value_of("batch").map(|x| x.to_owned());
+
+    let params = match matches.values_of("param") {
+        Some(values) => {
+            let mut params = PluginParams::new();
+            for p in values {
+                let mut kv = p.splitn(2, '=');
+                match (kv.next(), kv.next()) {
+                    (Some(k), Some(v)) => params.insert(k.to_string(), v.to_string()),
+                    _ => return Err(FractalError::InvalidArgument(format!("param needs the format `key=value`, but is '{}'", p)))
+                };
+            }
+            Some(params)
+        }
+        None => None
+    };
+
     let variation = matches.value_of("variation")
                            .and_then(|s| Variation::from_string_and_seed(s, seed));
 
@@ -552,10 +846,8 @@ pub fn parse_cl() -> Options {
     } else if matches.is_present("mirror-horizontal") {
         Some(Symmetry::Horizontal)
     } else if matches.is_present("rotational") {
-        let symmetries = matches.value_of("rotational")
-                                .unwrap()
-                                .parse::<usize>()
-                                .expect("the number of rotational symmetries need to be an integer");
+        let symmetries = parse_value::<usize>(&matches, "rotational", "an integer")?
+                                .expect("rotational takes a value");
         Some(Symmetry::Rotational(symmetries))
     } else if matches.is_present("no-symmetry") {
         Some(Symmetry::None)
@@ -563,10 +855,12 @@ pub fn parse_cl() -> Options {
         None
     };
 
-    Options {
+    Ok(Options {
         seed,
         filename,
         style,
+        normalization,
+        trap,
         tweet,
         quiet,
         fractal_type,
@@ -583,8 +877,19 @@ pub fn parse_cl() -> Options {
         rules,
         angle,
         rpn,
+        formula,
         zoom,
         center,
+        parameter,
+        precise_center,
+        exponent,
+        basins,
+        root_finding,
+        sequence,
         qmaprule,
-    }
+        limits,
+        anti,
+        params,
+        batch,
+    })
 }
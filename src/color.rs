@@ -1,6 +1,10 @@
+use std::str::FromStr;
+
 use serde::{Serialize, Deserialize};
 use log::info;
 
+use crate::error::FractalError;
+
 // TODO: longterm change this to (u8, u8, u8)
 
 /// data structure representing a HSV color value
@@ -53,6 +57,65 @@ impl RGB {
         let q = 1.-p;
         RGB(p*r1+q*r2, p*g1+q*g2, p*b1+q*b2)
     }
+
+    /// convert from sRGB to linear intensities, which can be averaged
+    pub fn to_linear(&self) -> RGB {
+        let &RGB(r, g, b) = self;
+        RGB(srgb2linear(r), srgb2linear(g), srgb2linear(b))
+    }
+
+    /// convert from linear intensities back to sRGB
+    pub fn to_srgb(&self) -> RGB {
+        let &RGB(r, g, b) = self;
+        RGB(linear2srgb(r), linear2srgb(g), linear2srgb(b))
+    }
+
+    /// average colors in linear RGB, i.e., the way light mixes
+    pub fn average(colors: &[RGB]) -> RGB {
+        let n = colors.len() as f64;
+        let (r, g, b) = colors.iter()
+                              .map(|c| c.to_linear())
+                              .fold((0., 0., 0.), |(r, g, b), RGB(x, y, z)| (r+x, g+y, b+z));
+        RGB(r/n, g/n, b/n).to_srgb()
+    }
+
+    pub fn to_rgba(&self) -> RGBA {
+        let &RGB(r, g, b) = self;
+        RGBA((r * 255.) as u8, (g * 255.) as u8, (b * 255.) as u8, 255)
+    }
+}
+
+impl FromStr for RGB {
+    type Err = FractalError;
+
+    /// parse a hex color like `#ff8000`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FractalError::InvalidArgument(format!("a color needs to look like `#ff8000`, but is '{}'", s));
+        let hex = s.trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(invalid())
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[2*i..2*i+2], 16)
+                                    .map(|c| c as f64 / 255.)
+                                    .map_err(|_| invalid());
+        Ok(RGB(channel(0)?, channel(1)?, channel(2)?))
+    }
+}
+
+fn srgb2linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear2srgb(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    }
 }
 
 impl RGBA {
@@ -16,7 +16,7 @@ mod twilight_dat;
 use self::twilight_dat::TWILIGHT;
 
 /// take a value `x` between 0 and 1 and return a color corresponding to this value
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Colormap {
     map: Vec<(f64, RGB)>,
     name: String,
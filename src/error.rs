@@ -0,0 +1,107 @@
+use std::fmt;
+use std::io;
+
+use serde::de::DeserializeOwned;
+
+use crate::FractalType;
+
+/// Errors which can occur while building, loading or rendering a fractal
+#[derive(Debug)]
+pub enum FractalError {
+    /// the json could not be deserialized as a `family` of fractals,
+    /// `path` points to the offending element
+    Json {
+        family: &'static str,
+        path: String,
+        message: String,
+    },
+    /// the json was written by a newer version of this crate
+    UnsupportedVersion(u32),
+    /// the json does not describe any known fractal,
+    /// contains the reason why every family was rejected
+    UnknownJson(Vec<FractalError>),
+    /// malformed rules of an L-system
+    InvalidRule(String),
+    /// malformed function in reverse polish notation
+    InvalidRpn(String),
+    /// malformed function in infix notation
+    InvalidFormula(String),
+    /// malformed rule of a quadratic map
+    InvalidQmapRule(String),
+    /// invalid command line argument
+    InvalidArgument(String),
+    /// no plugin is registered under this name
+    UnknownPlugin(String),
+    /// a plugin with this name is already registered
+    DuplicatePlugin(String),
+    /// the type can not be built directly, e.g. `FractalType::Random`
+    Unsupported(FractalType),
+    /// the fractals can not be combined
+    NotCombinable,
+    /// the render was aborted by a `CancellationToken`
+    Cancelled,
+    /// writing the image failed
+    Io(io::Error),
+}
+
+impl fmt::Display for FractalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FractalError::Json { family, path, message } =>
+                write!(f, "not a valid {} at '{}': {}", family, path, message),
+            FractalError::UnsupportedVersion(v) => write!(f, "json version {} is not supported", v),
+            FractalError::UnknownJson(errors) => {
+                write!(f, "json does not describe a known fractal")?;
+                for e in errors {
+                    write!(f, "\n  {}", e)?;
+                }
+                Ok(())
+            },
+            FractalError::InvalidRule(x) => write!(f, "invalid L-system rule: {}", x),
+            FractalError::InvalidRpn(x) => write!(f, "invalid reverse polish notation: {}", x),
+            FractalError::InvalidFormula(x) => write!(f, "invalid formula: {}", x),
+            FractalError::InvalidQmapRule(x) => write!(f, "invalid quadratic map rule: {}", x),
+            FractalError::InvalidArgument(x) => write!(f, "invalid argument: {}", x),
+            FractalError::UnknownPlugin(x) => write!(f, "no fractal type '{}' is registered", x),
+            FractalError::DuplicatePlugin(x) => write!(f, "a fractal type '{}' is already registered", x),
+            FractalError::Unsupported(x) => write!(f, "can not build a fractal of type {}", x),
+            FractalError::NotCombinable => write!(f, "only fractal flames can be combined"),
+            FractalError::Cancelled => write!(f, "the render was cancelled"),
+            FractalError::Io(x) => write!(f, "io error: {}", x),
+        }
+    }
+}
+
+impl std::error::Error for FractalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FractalError::Io(x) => Some(x),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for FractalError {
+    fn from(error: io::Error) -> Self {
+        FractalError::Io(error)
+    }
+}
+
+/// deserialize `json`, on failure keep the path to the offending element
+///
+/// # Arguments
+///
+/// * `json` - the json string to parse
+/// * `family` - human readable name of the expected type, used in the error
+pub fn from_json<T>(json: &str, family: &'static str) -> Result<T, FractalError>
+    where T: DeserializeOwned
+{
+    let jd = &mut serde_json::Deserializer::from_str(json);
+    serde_path_to_error::deserialize(jd).map_err(|err| {
+        FractalError::Json {
+            family,
+            path: err.path().to_string(),
+            message: err.inner().to_string(),
+        }
+    })
+}
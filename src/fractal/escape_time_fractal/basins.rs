@@ -0,0 +1,66 @@
+use serde::{self, Serialize, Deserialize};
+
+use super::Convergence;
+use crate::numbers::{Real, Cplx};
+use crate::color::{HSV, RGB};
+use crate::colormap::Colormap;
+
+/// end points closer than this, relative to their modulus, are the same root
+const TOLERANCE: Real = 1e-4;
+
+/// at most this many distinct roots get their own color
+const MAX_ROOTS: usize = 64;
+
+/// Colors every basin of attraction of a root finding iteration by its root.
+///
+/// The roots are spread over the colormap and the colors are shaded by the
+/// smooth iteration count, such that the boundaries of the basins glow.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Basins {
+    pub colormap: Colormap,
+    /// color of points which do not converge to a known root
+    pub non_converged: RGB,
+}
+
+fn same_root(a: Cplx, b: Cplx) -> bool {
+    (a - b).norm() < TOLERANCE * a.norm().max(1.)
+}
+
+/// index of the root which `z` converged to
+pub fn find_root(roots: &[Cplx], z: Cplx) -> Option<usize> {
+    roots.iter().position(|&r| same_root(r, z))
+}
+
+/// add the distinct end points of converged orbits to `roots`
+///
+/// Orbits converged if their count is below `max_count`. Some iterations
+/// have fixed points which are no roots, `is_root` filters them. Already
+/// known roots keep their position, such that their colors do not change.
+pub fn cluster_roots<'a>(roots: &mut Vec<Cplx>,
+                         states: impl Iterator<Item=&'a Convergence>,
+                         max_count: u64,
+                         is_root: impl Fn(Cplx) -> bool)
+{
+    for conv in states {
+        if roots.len() >= MAX_ROOTS {
+            break
+        }
+        let z = conv.value;
+        if conv.count < max_count as f64 && z.re.is_finite() && z.im.is_finite() && find_root(roots, z).is_none() && is_root(z) {
+            roots.push(z);
+        }
+    }
+}
+
+impl Basins {
+    pub fn color(&self, roots: &[Cplx], conv: &Convergence, max_count: u64) -> HSV {
+        let idx = match find_root(roots, conv.value) {
+            Some(idx) if conv.count < max_count as f64 => idx,
+            _ => return self.non_converged.to_hsv()
+        };
+
+        let RGB(r, g, b) = self.colormap.value(&((idx as f64 + 0.5) / roots.len() as f64));
+        let shade = 1. / (1. + 0.05 * conv.count);
+        RGB(r * shade, g * shade, b * shade).to_hsv()
+    }
+}
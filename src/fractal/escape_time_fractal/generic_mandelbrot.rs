@@ -0,0 +1,219 @@
+use serde::{self, Serialize, Deserialize};
+
+use log::info;
+
+use super::{EscapeTimeFractal, Convergence, EscapeTypes};
+use super::region::{near_boundary, random_zoom, describe};
+use crate::numbers::{Real, Cplx, ComplexFunction};
+use crate::fractal::FractalBuilder;
+use crate::error::FractalError;
+
+use super::style::{CountColoring, CountColored};
+use super::normalization::Normalization;
+use super::orbit_trap::OrbitTrap;
+use crate::colormap::Colormap;
+
+/// squared escape radius, like for the Julia sets of arbitrary functions
+const THRESHOLD: Real = 1e8;
+
+/// the region of the search for the boundary, around the main component
+const OVERVIEW_RADIUS: Real = 2.;
+
+/// Newton's method for critical points starts on a grid of this many
+/// points per side, covering `[-2, 2]` in both directions
+const CRITICAL_GRID: usize = 9;
+
+/// upper bound of the steps of Newton's method
+const NEWTON_STEPS: usize = 64;
+
+/// Newton's method stops when a step is smaller than this
+const NEWTON_TOLERANCE: Real = 1e-12;
+
+/// The parameter plane of `z -> f(z) + c` for an arbitrary function `f`,
+/// the counterpart of the Julia sets of `f`.
+///
+/// The orbit starts at a critical point of `f`, whose fate decides the
+/// connectedness of the Julia set of `f(z) + c`. For `f = z^2` this is
+/// the Mandelbrot set.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenericMandelbrotFractal {
+    #[serde(default)]
+    pub seed: u64,
+    pub description: String,
+    f: ComplexFunction,
+    /// derivative of `f` for the distance estimate, if `f` is a polynomial
+    #[serde(skip)]
+    df: Option<ComplexFunction>,
+    /// the start of every orbit, a root of `f'` or `0` if none is found
+    critical_point: Cplx,
+    max_count: u64,
+    shift: Cplx,
+    zoom: Real,
+    #[serde(flatten)]
+    coloring: CountColoring,
+    /// colors orbits by their closest approach, if given
+    #[serde(default)]
+    trap: Option<OrbitTrap>,
+}
+
+/// the critical point of `f` closest to `0`, a root of its derivative `df`
+///
+/// Functions like `exp` or linear polynomials have none.
+fn critical_point(df: &ComplexFunction) -> Option<Cplx> {
+    let ddf = df.differentiate();
+    let mut best: Option<Cplx> = None;
+
+    for k in 0..CRITICAL_GRID * CRITICAL_GRID {
+        let grid = |i: usize| 4. * i as Real / (CRITICAL_GRID - 1) as Real - 2.;
+        let mut z = Cplx::new(grid(k % CRITICAL_GRID), grid(k / CRITICAL_GRID));
+        for _ in 0..NEWTON_STEPS {
+            let step = df.eval(z) / ddf.eval(z);
+            z -= step;
+            if !z.re.is_finite() || !z.im.is_finite() || step.norm() < NEWTON_TOLERANCE {
+                break
+            }
+        }
+
+        let root = z.re.is_finite() && z.im.is_finite() && df.eval(z).norm() < NEWTON_TOLERANCE.sqrt();
+        let closer = match best {
+            Some(b) => z.norm() < b.norm() - NEWTON_TOLERANCE.sqrt(),
+            None => true
+        };
+        if root && closer {
+            best = Some(z);
+        }
+    }
+
+    best
+}
+
+impl FractalBuilder {
+    pub fn generic_mandelbrot(self) -> GenericMandelbrotFractal {
+        let mut rng = self.seed_rng();
+        let (style, trap) = self.style_and_trap();
+
+        let f = match self.f {
+            Some(x) => x,
+            None => ComplexFunction::random(&mut rng)
+        };
+        let critical = critical_point(&f.differentiate());
+        if critical.is_none() {
+            info!("{} has no critical point, start at 0", f.human_readable());
+        }
+        let critical_point = critical.unwrap_or_else(|| Cplx::new(0., 0.));
+
+        let mut fractal = GenericMandelbrotFractal {
+            seed: self.seed,
+            description: String::new(),
+            f,
+            df: None,
+            critical_point,
+            max_count: 1000,
+            shift: Cplx::new(0., 0.),
+            zoom: 1.,
+            coloring: CountColoring {
+                colormap: Colormap::random(&mut rng),
+                style,
+                normalization: self.normalization,
+            },
+            trap: None,
+        };
+
+        // the main component is around the `c` for which the critical
+        // point is a fixed point, like `0` for the Mandelbrot set
+        let main = critical_point - fractal.f.eval(critical_point);
+        let overview = if main.re.is_finite() && main.im.is_finite() {
+            ((main.re - OVERVIEW_RADIUS, main.re + OVERVIEW_RADIUS), (main.im - OVERVIEW_RADIUS, main.im + OVERVIEW_RADIUS))
+        } else {
+            ((-OVERVIEW_RADIUS, OVERVIEW_RADIUS), (-OVERVIEW_RADIUS, OVERVIEW_RADIUS))
+        };
+
+        fractal.shift = match self.center {
+            Some(c) => Cplx::new(c.0 as Real, c.1 as Real),
+            None => near_boundary(&mut rng, overview, fractal.max_count as f64, |c| fractal.iterate(c).count)
+        };
+        fractal.zoom = match self.zoom {
+            Some(z) => z as Real,
+            None => random_zoom(&mut rng, 0.3, 8)
+        };
+
+        // the search for a region is faster without the trap
+        fractal.trap = trap;
+
+        let name = format!("Mandelbrot Fractal of {} + c", fractal.f.human_readable());
+        fractal.description = describe(&name, fractal.shift, fractal.zoom);
+        info!("Will render {}", fractal.description);
+
+        fractal
+    }
+}
+
+impl CountColored for GenericMandelbrotFractal {
+    fn coloring(&self) -> &CountColoring {
+        &self.coloring
+    }
+
+    fn count_limit(&self) -> u64 {
+        self.max_count
+    }
+}
+
+impl EscapeTimeFractal for GenericMandelbrotFractal {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn max_count(&self) -> u64 {
+        self.max_count
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn normalization(&self) -> Option<Normalization> {
+        self.coloring.normalization()
+    }
+
+    fn prepare(&mut self, _resolution: (u32, u32)) -> Result<(), FractalError> {
+        // the distance estimate is only valid for polynomials
+        self.df = match self.f {
+            ComplexFunction::Polynom(_) => Some(self.f.differentiate()),
+            ComplexFunction::RPN(_) => None,
+        };
+        Ok(())
+    }
+
+    fn iterate(&self, state: Cplx) -> Convergence {
+        let mut ctr = 0;
+        let c = state / self.zoom + self.shift;
+        let mut state = self.critical_point;
+        // derivative with respect to `c`
+        let mut derivative = Cplx::new(0., 0.);
+
+        let mut trap = self.trap.as_ref().map(OrbitTrap::tracker);
+
+        while {
+            if let Some(ref df) = self.df {
+                derivative = df.eval(state) * derivative + 1.;
+            }
+            state = self.f.eval(state) + c;
+            ctr += 1;
+            if let Some(t) = trap.as_mut() {
+                t.visit(state);
+            }
+
+            state.norm_sqr() < THRESHOLD && ctr < self.max_count && !state.re.is_nan() && !state.im.is_nan()
+        } {}
+
+        let derivative = match self.df {
+            Some(_) if state.norm_sqr() >= THRESHOLD => Some(derivative / self.zoom),
+            _ => None
+        };
+        Convergence {count: ctr as f64, value: state, derivative, trap: trap.and_then(|t| t.hit())}
+    }
+
+    fn get_serializable(&self) -> EscapeTypes {
+        EscapeTypes::GenericMandelbrot(self.clone())
+    }
+}
@@ -1,19 +1,63 @@
+use serde::{self, Serialize, Deserialize};
+
 use log::info;
 
-use super::{EscapeTimeFractal, Convergence};
-use crate::fractal::{FractalBuilder, RngType};
-use crate::numbers::Cplx;
+use super::{EscapeTimeFractal, Convergence, EscapeTypes};
+use crate::fractal::FractalBuilder;
+use crate::error::FractalError;
+use crate::numbers::{Real, Cplx};
 
-use super::style::Stylable;
+use super::style::{Style, Stylable};
+use super::normalization::Normalization;
+use super::orbit_trap::OrbitTrap;
 use crate::color;
 use crate::numbers::ComplexFunction;
+use super::mandelbrot::julia_parameter;
+
+/// iteration count of points which belong to the set
+const MAX_COUNT: u64 = 1000;
+
+/// quadratic Julia sets of parameters close to the boundary of the
+/// Mandelbrot set need more iterations to resolve their thin parts
+const QUADRATIC_MAX_COUNT: u64 = 2000;
 
+/// the whole quadratic Julia set is visible at this zoom
+const QUADRATIC_ZOOM: Real = 0.3;
+
+fn default_bailout() -> Real {
+    1e8
+}
 
+fn default_zoom() -> Real {
+    1.
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JuliaFractal {
     f: ComplexFunction,
-    rng: RngType,
+    /// derivative of `f` for the distance estimate, if `f` is a polynomial
+    #[serde(skip)]
+    df: Option<ComplexFunction>,
+    /// replaces the default coloring, if given
+    #[serde(default)]
+    style: Option<Style>,
+    /// replaces the default coloring by hues, if given
+    #[serde(default)]
+    normalization: Option<Normalization>,
+    /// colors orbits by their closest approach, if given
+    #[serde(default)]
+    trap: Option<OrbitTrap>,
+    #[serde(default)]
+    pub seed: u64,
     pub description: String,
-    max_count: u64
+    max_count: u64,
+    /// squared radius beyond which orbits escape
+    #[serde(default = "default_bailout")]
+    bailout: Real,
+    #[serde(default)]
+    shift: Cplx,
+    #[serde(default = "default_zoom")]
+    zoom: Real,
 }
 
 impl FractalBuilder {
@@ -23,7 +67,7 @@ impl FractalBuilder {
         // fill in defaults, if members are not given
         // most defaults will be random
         let f = match self.f {
-            Some(x) => x,
+            Some(ref x) => x.clone(),
             None => ComplexFunction::random(&mut rng)
         };
 
@@ -34,20 +78,56 @@ impl FractalBuilder {
 
         let description = format!("Julia Fractal of {}", f.human_readable());
 
+        self.julia_of(f, description)
+    }
+
+    /// the Julia set of `z^2 + c`, which is connected for the parameters
+    /// `c` of the Mandelbrot set
+    pub fn quadratic_julia(self) -> JuliaFractal {
+        let mut rng = self.seed_rng();
+
+        let c = match self.parameter {
+            Some(c) => Cplx::new(c.0 as Real, c.1 as Real),
+            None => julia_parameter(&mut rng)
+        };
+        let f = ComplexFunction::Polynom(vec![c, Cplx::new(0., 0.), Cplx::new(1., 0.)]);
+        let description = format!("Julia Fractal of z^2 + ({:.4})", c);
+        let zoom = self.zoom.unwrap_or(QUADRATIC_ZOOM as f64) as Real;
+
+        JuliaFractal {
+            zoom,
+            max_count: QUADRATIC_MAX_COUNT,
+            ..self.julia_of(f, description)
+        }
+    }
+
+    fn julia_of(self, f: ComplexFunction, description: String) -> JuliaFractal {
+        let (style, trap) = self.style_and_trap();
+
         info!("Will render {}", description);
-        // FIXME: For Julia fractals the colors need to be normalized somehow
 
         JuliaFractal {
             f,
+            df: None,
+            style,
+            normalization: self.normalization,
+            trap,
             description,
-            rng,
-            max_count: 1000
+            seed: self.seed,
+            max_count: MAX_COUNT,
+            bailout: default_bailout(),
+            shift: self.center.map_or(Cplx::new(0., 0.), |c| Cplx::new(c.0 as Real, c.1 as Real)),
+            zoom: self.zoom.unwrap_or(1.) as Real,
         }
     }
 }
 
 impl Stylable for JuliaFractal {
     fn style(&self, conv: &Convergence) -> color::HSV {
+        if let Some(ref style) = self.style {
+            return style.color(conv, None, None)
+        }
+
         let fractional = conv.value.norm().ln().ln() as f64;
         let c = if fractional.is_nan() {
             conv.count as f64
@@ -67,7 +147,15 @@ impl Stylable for JuliaFractal {
     }
 
     fn style_name(&self) -> &str {
-        "vibrant"
+        match self.style {
+            Some(ref style) => &style.readable,
+            None => "vibrant"
+        }
+    }
+
+    fn style_normalized(&self, _conv: &Convergence, x: f64) -> color::HSV {
+        // stop before the hue wraps around to red again
+        color::HSV(0.8 * x, 1., 1.)
     }
 }
 
@@ -76,26 +164,65 @@ impl EscapeTimeFractal for JuliaFractal {
         &self.description
     }
 
+    fn max_count(&self) -> u64 {
+        self.max_count
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn normalization(&self) -> Option<Normalization> {
+        // styles color by themselves
+        match self.style {
+            Some(_) => None,
+            None => self.normalization
+        }
+    }
+
+    fn prepare(&mut self, _resolution: (u32, u32)) -> Result<(), FractalError> {
+        // the distance estimate is only valid for polynomials
+        self.df = match self.f {
+            ComplexFunction::Polynom(_) => Some(self.f.differentiate()),
+            ComplexFunction::RPN(_) => None,
+        };
+        Ok(())
+    }
+
     fn iterate(&self, mut state: Cplx) -> Convergence {
         let mut ctr = 0;
-        // threshold is 2^2, since we compare to the square of the norm
-        // as soon as the norm is >= 2 it is sure to diverge
-        let threshold = 1e8;
+        let threshold = self.bailout;
+        state = state / self.zoom + self.shift;
 
         // the canonical julia set is `f = z^2 + c`,
         // but here we will use some arbitrary function
 
+        // derivative with respect to the starting point
+        let mut derivative = Cplx::new(1., 0.);
+
+        let mut trap = self.trap.as_ref().map(OrbitTrap::tracker);
+
         while {
+            if let Some(ref df) = self.df {
+                derivative *= df.eval(state);
+            }
             state = self.f.eval(state);
             ctr += 1;
+            if let Some(t) = trap.as_mut() {
+                t.visit(state);
+            }
 
             state.norm_sqr() < threshold && ctr < self.max_count && !state.re.is_nan() && !state.im.is_nan()
         } {}
         // Convergence {count: 1, value: Cplx::new((ctr as Real) + 1. - (2f32.ln() / state.norm()) / 2f32.ln(), 0.) / 1000.}
-        Convergence {count: ctr as f64, value: state}
+        let derivative = match self.df {
+            Some(_) if state.norm_sqr() >= threshold => Some(derivative / self.zoom),
+            _ => None
+        };
+        Convergence {count: ctr as f64, value: state, derivative, trap: trap.and_then(|t| t.hit())}
     }
 
-    fn get_rng(&mut self) -> &mut RngType {
-        &mut self.rng
+    fn get_serializable(&self) -> EscapeTypes {
+        EscapeTypes::Julia(self.clone())
     }
 }
@@ -0,0 +1,156 @@
+//! Batched iteration of the Mandelbrot set.
+//!
+//! Blocks of pixels are advanced together in lanes of plain arrays, such
+//! that the compiler can vectorize the arithmetic of one step.
+
+use crate::numbers::{Real, Cplx};
+use super::Convergence;
+
+/// number of pixels which are iterated together
+pub const LANES: usize = 8;
+
+/// orbits which return this close (squared distance) to an earlier point are periodic
+const PERIODICITY_EPSILON: Real = 1e-24;
+
+/// iteration of the first comparison for periodicity, the point compared
+/// to is replaced at doubling intervals afterwards
+const PERIODICITY_START: u64 = 8;
+
+/// whether `c` lies in the main cardioid or the period 2 bulb of the
+/// Mandelbrot set, where no orbit escapes
+pub fn in_main_bulbs(c: Cplx) -> bool {
+    let q = (c.re - 0.25) * (c.re - 0.25) + c.im * c.im;
+    let cardioid = q * (q + c.re - 0.25) <= 0.25 * c.im * c.im;
+    let bulb = (c.re + 1.) * (c.re + 1.) + c.im * c.im <= 1. / 16.;
+    cardioid || bulb
+}
+
+/// iterate `z -> z^2 + c` starting at `z = c` for every `c` of `starts`,
+/// until `|z|^2` is not below `threshold` anymore or `max_count` is reached
+///
+/// The derivative is taken with respect to `c`. With `interior` the points
+/// in the main cardioid or the period 2 bulb and the periodic orbits get the
+/// count `max_count` without iterating that far, their `value` is arbitrary.
+pub fn mandelbrot_lanes(starts: &[Cplx], max_count: u64, threshold: Real, interior: bool) -> Vec<Convergence> {
+    let mut results: Vec<Option<Convergence>> = starts.iter().map(|_| None).collect();
+    if interior {
+        for (result, &c) in results.iter_mut().zip(starts) {
+            if in_main_bulbs(c) {
+                *result = Some(Convergence {count: max_count as f64, value: c, derivative: None, trap: None});
+            }
+        }
+    }
+
+    // a lane which finishes continues with the next pixel, such that
+    // slow pixels do not keep the other lanes waiting
+    let mut queue = (0..starts.len()).filter(|&k| results[k].is_none()).collect::<Vec<usize>>().into_iter();
+    let mut lanes = Lanes::new();
+    for l in 0..LANES {
+        if let Some(k) = queue.next() {
+            lanes.load(l, k, starts[k]);
+        }
+    }
+
+    while lanes.index.iter().any(Option::is_some) {
+        lanes.step();
+
+        for l in 0..LANES {
+            let k = match lanes.index[l] {
+                Some(k) => k,
+                None => continue
+            };
+            let (re, im) = (lanes.re[l], lanes.im[l]);
+            let norm_sqr = re * re + im * im;
+            let escaped = norm_sqr >= threshold || norm_sqr.is_nan();
+            let periodic = interior && {
+                let (dx, dy) = (re - lanes.saved_re[l], im - lanes.saved_im[l]);
+                dx * dx + dy * dy < PERIODICITY_EPSILON
+            };
+            let count = lanes.count[l];
+
+            if escaped || periodic || count >= max_count {
+                results[k] = Some(Convergence {
+                    count: if periodic && !escaped { max_count } else { count } as f64,
+                    value: Cplx::new(re, im),
+                    derivative: if norm_sqr >= threshold { Some(Cplx::new(lanes.d_re[l], lanes.d_im[l])) } else { None },
+                    trap: None,
+                });
+                match queue.next() {
+                    Some(next) => lanes.load(l, next, starts[next]),
+                    None => lanes.index[l] = None,
+                }
+            } else if interior && count == lanes.next_save[l] {
+                lanes.saved_re[l] = re;
+                lanes.saved_im[l] = im;
+                lanes.next_save[l] *= 2;
+            }
+        }
+    }
+
+    results.into_iter()
+           .map(|r| r.expect("every pixel is iterated"))
+           .collect()
+}
+
+/// the state of `LANES` orbits, which are iterated together
+struct Lanes {
+    /// the pixel of every lane, `None` for idle lanes
+    index: [Option<usize>; LANES],
+    c_re: [Real; LANES],
+    c_im: [Real; LANES],
+    re: [Real; LANES],
+    im: [Real; LANES],
+    d_re: [Real; LANES],
+    d_im: [Real; LANES],
+    saved_re: [Real; LANES],
+    saved_im: [Real; LANES],
+    count: [u64; LANES],
+    next_save: [u64; LANES],
+}
+
+impl Lanes {
+    fn new() -> Lanes {
+        Lanes {
+            index: [None; LANES],
+            c_re: [0.; LANES],
+            c_im: [0.; LANES],
+            re: [0.; LANES],
+            im: [0.; LANES],
+            d_re: [1.; LANES],
+            d_im: [0.; LANES],
+            saved_re: [0.; LANES],
+            saved_im: [0.; LANES],
+            count: [0; LANES],
+            next_save: [PERIODICITY_START; LANES],
+        }
+    }
+
+    /// start the orbit of pixel `k` at `c` in lane `l`
+    fn load(&mut self, l: usize, k: usize, c: Cplx) {
+        self.index[l] = Some(k);
+        self.c_re[l] = c.re;
+        self.c_im[l] = c.im;
+        self.re[l] = c.re;
+        self.im[l] = c.im;
+        self.d_re[l] = 1.;
+        self.d_im[l] = 0.;
+        self.saved_re[l] = c.re;
+        self.saved_im[l] = c.im;
+        self.count[l] = 0;
+        self.next_save[l] = PERIODICITY_START;
+    }
+
+    /// advance all lanes by one iteration, idle lanes included, which
+    /// keeps this loop free of branches
+    fn step(&mut self) {
+        for l in 0..LANES {
+            let (x, y) = (self.re[l], self.im[l]);
+            let (dx, dy) = (self.d_re[l], self.d_im[l]);
+            self.d_re[l] = 2. * (x * dx - y * dy) + 1.;
+            self.d_im[l] = 2. * (x * dy + y * dx);
+            self.re[l] = x * x - y * y + self.c_re[l];
+            self.im[l] = 2. * x * y + self.c_im[l];
+            self.count[l] += 1;
+        }
+    }
+}
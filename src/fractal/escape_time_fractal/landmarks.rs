@@ -0,0 +1,106 @@
+//! Landmarks of the Mandelbrot set, found by Newton's method on the orbit of `0`.
+//!
+//! Nuclei are the centers of the minibrots, where the orbit of `0` is
+//! periodic. Misiurewicz points are the tips and spiral centers of the
+//! boundary, where the orbit becomes periodic after a few steps.
+
+use crate::numbers::{Real, Cplx};
+
+/// upper bound of the steps of Newton's method
+const NEWTON_STEPS: usize = 64;
+
+/// Newton's method stops when a step is smaller than this, relative to `|c|`
+const NEWTON_TOLERANCE: Real = 1e-14;
+
+/// longest preperiod and period of Misiurewicz points which are searched
+const MAX_PREPERIOD: usize = 16;
+const MAX_PERIOD: usize = 16;
+
+/// the orbit `z_1, z_2, ...` of `z_0 = 0` up to `n` steps, as long as it stays bounded
+fn orbit(c: Cplx, n: usize) -> Vec<Cplx> {
+    let mut z = Cplx::new(0., 0.);
+    let mut orbit = Vec::with_capacity(n);
+    for _ in 0..n {
+        z = z * z + c;
+        if z.norm_sqr() > 4. {
+            break
+        }
+        orbit.push(z);
+    }
+    orbit
+}
+
+/// `z_n` and its derivative with respect to `c`
+fn iterate_with_derivative(c: Cplx, n: usize) -> (Cplx, Cplx) {
+    let mut z = Cplx::new(0., 0.);
+    let mut dz = Cplx::new(0., 0.);
+    for _ in 0..n {
+        dz = z * dz * 2. + 1.;
+        z = z * z + c;
+    }
+    (z, dz)
+}
+
+/// find a root of `f` close to `c`, where `f` returns the value and its derivative
+fn newton<F>(mut c: Cplx, f: F) -> Option<Cplx>
+    where F: Fn(Cplx) -> (Cplx, Cplx)
+{
+    for _ in 0..NEWTON_STEPS {
+        let (value, derivative) = f(c);
+        let step = value / derivative;
+        c -= step;
+        if !c.re.is_finite() || !c.im.is_finite() {
+            return None
+        }
+        if step.norm() < NEWTON_TOLERANCE * c.norm().max(1.) {
+            return Some(c)
+        }
+    }
+    None
+}
+
+/// the nucleus of the minibrot closest to `c`
+///
+/// Its period is the step at which the orbit of `c` comes closest to `0`,
+/// orbits which escape earlier than `max_period` steps are not considered.
+pub fn nucleus(c: Cplx, max_period: usize) -> Option<Cplx> {
+    let period = orbit(c, max_period)
+        .iter()
+        .enumerate()
+        .fold((0, Real::INFINITY), |best, (n, z)| if z.norm() < best.1 { (n + 1, z.norm()) } else { best })
+        .0;
+    if period == 0 {
+        return None
+    }
+
+    newton(c, |c| iterate_with_derivative(c, period))
+}
+
+/// the Misiurewicz point closest to `c`
+///
+/// Its preperiod `m` and period `p` are the ones for which `z_{m+p}` of the
+/// orbit of `c` comes closest to `z_m`.
+pub fn misiurewicz(c: Cplx) -> Option<Cplx> {
+    let orbit = orbit(c, MAX_PREPERIOD + MAX_PERIOD);
+    let mut best = None;
+    let mut best_distance = Real::INFINITY;
+    for m in 1..=MAX_PREPERIOD {
+        for p in 1..=MAX_PERIOD {
+            if m + p > orbit.len() {
+                break
+            }
+            let distance = (orbit[m + p - 1] - orbit[m - 1]).norm();
+            if distance < best_distance {
+                best = Some((m, p));
+                best_distance = distance;
+            }
+        }
+    }
+
+    let (m, p) = best?;
+    newton(c, |c| {
+        let (zm, dzm) = iterate_with_derivative(c, m);
+        let (zmp, dzmp) = iterate_with_derivative(c, m + p);
+        (zmp - zm, dzmp - dzm)
+    })
+}
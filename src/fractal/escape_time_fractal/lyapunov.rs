@@ -0,0 +1,196 @@
+use serde::{self, Serialize, Deserialize};
+
+use log::info;
+use rand::Rng;
+
+use super::{EscapeTimeFractal, Convergence, EscapeTypes};
+use super::region::{random_zoom, describe};
+use crate::numbers::{Real, Cplx};
+use crate::fractal::FractalBuilder;
+use crate::error::FractalError;
+
+use super::style::Stylable;
+use crate::color;
+use crate::colormap::Colormap;
+
+/// the classic view of `a, b` in `[2, 4]`, for images of aspect ratio 3:2
+const OVERVIEW_SHIFT: (Real, Real) = (3., 3.);
+const OVERVIEW_ZOOM: Real = 0.75;
+
+/// the sum of logarithms is accumulated as a product, which is flushed
+/// before it leaves this range
+const PRODUCT_RANGE: (Real, Real) = (1e-100, 1e100);
+
+/// Markus-Lyapunov fractal, the Lyapunov exponent of the logistic map
+/// `x -> r x (1 - x)`, where `r` follows a periodic sequence of the
+/// parameters `a` and `b` of the plane.
+///
+/// Negative exponents mark stable orbits, positive ones chaos, both are
+/// colored by their own colormap.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LyapunovFractal {
+    #[serde(default)]
+    pub seed: u64,
+    pub description: String,
+    /// letters `A` and `B`, selecting the parameter of every step
+    sequence: String,
+    /// steps before the exponent is measured
+    warmup: u64,
+    /// steps over which the exponent is averaged
+    iterations: u64,
+    shift: Cplx,
+    zoom: Real,
+    stable: Colormap,
+    chaotic: Colormap,
+}
+
+/// the sequence in upper case, if it only consists of `A` and `B`
+fn parse_sequence(sequence: &str) -> Result<String, FractalError> {
+    let upper = sequence.to_uppercase();
+    if upper.is_empty() || upper.chars().any(|c| c != 'A' && c != 'B') {
+        return Err(FractalError::InvalidArgument(
+            format!("sequence needs to consist of the letters `A` and `B`, but is '{}'", sequence)
+        ))
+    }
+    Ok(upper)
+}
+
+/// a random sequence, which contains both letters
+fn random_sequence<T: Rng>(rng: &mut T) -> String {
+    let length = rng.gen_range(2, 9);
+    let mut letters: Vec<char> = (0..length).map(|_| if rng.gen::<f64>() < 0.5 { 'A' } else { 'B' }).collect();
+    if letters.iter().all(|&c| c == letters[0]) {
+        let i = rng.gen_range(0, length);
+        letters[i] = if letters[i] == 'A' { 'B' } else { 'A' };
+    }
+    letters.into_iter().collect()
+}
+
+impl FractalBuilder {
+    pub fn lyapunov(self) -> Result<LyapunovFractal, FractalError> {
+        let mut rng = self.seed_rng();
+
+        let sequence = match self.sequence {
+            Some(ref s) => parse_sequence(s)?,
+            None => random_sequence(&mut rng),
+        };
+
+        let warmup = rng.gen_range(50, 200);
+        let iterations = rng.gen_range(100, 500);
+
+        let zoom = match self.zoom {
+            Some(z) => z as Real,
+            None => random_zoom(&mut rng, OVERVIEW_ZOOM, 3)
+        };
+        // the overview shows everything, closer views a random detail of it
+        let shift = match self.center {
+            Some(c) => Cplx::new(c.0 as Real, c.1 as Real),
+            None if zoom <= OVERVIEW_ZOOM => Cplx::new(OVERVIEW_SHIFT.0, OVERVIEW_SHIFT.1),
+            None => Cplx::new(rng.gen_range(2.5, 3.8), rng.gen_range(2.5, 3.8)),
+        };
+
+        let stable = Colormap::random(&mut rng);
+        let chaotic = Colormap::random(&mut rng);
+
+        let description = describe(&format!("Lyapunov Fractal of {}", sequence), shift, zoom);
+        info!("Will render {}", description);
+
+        Ok(LyapunovFractal {
+            seed: self.seed,
+            description,
+            sequence,
+            warmup,
+            iterations,
+            shift,
+            zoom,
+            stable,
+            chaotic,
+        })
+    }
+}
+
+impl LyapunovFractal {
+    /// the Lyapunov exponent at the parameters `a` and `b`
+    fn exponent(&self, a: Real, b: Real) -> Real {
+        let sequence = self.sequence.as_bytes();
+        let r = |n: u64| if sequence[(n % sequence.len() as u64) as usize] == b'A' { a } else { b };
+
+        let mut x = 0.5;
+        for n in 0..self.warmup {
+            x = r(n) * x * (1. - x);
+        }
+
+        let mut sum = 0.;
+        let mut product = 1.;
+        for n in self.warmup..self.warmup + self.iterations {
+            let rn = r(n);
+            product *= (rn * (1. - 2. * x)).abs();
+            x = rn * x * (1. - x);
+            if !x.is_finite() {
+                // the orbit left the unit interval and diverges
+                return Real::INFINITY
+            }
+            if product < PRODUCT_RANGE.0 || product > PRODUCT_RANGE.1 {
+                sum += product.ln();
+                product = 1.;
+            }
+        }
+
+        (sum + product.ln()) / self.iterations as Real
+    }
+}
+
+impl Stylable for LyapunovFractal {
+    fn style(&self, conv: &Convergence) -> color::HSV {
+        let lambda = conv.value.re;
+        if lambda <= 0. {
+            // superstable orbits at -inf get the end of the colormap
+            self.stable.value(&(1. - lambda.exp())).to_hsv()
+        } else {
+            self.chaotic.value(&lambda.tanh()).to_hsv()
+        }
+    }
+
+    fn style_name(&self) -> &str {
+        self.stable.name()
+    }
+
+    fn style_normalized(&self, conv: &Convergence, _x: f64) -> color::HSV {
+        self.style(conv)
+    }
+}
+
+impl EscapeTimeFractal for LyapunovFractal {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn max_count(&self) -> u64 {
+        self.warmup + self.iterations
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn prepare(&mut self, _resolution: (u32, u32)) -> Result<(), FractalError> {
+        // a fractal loaded from json might have any sequence
+        self.sequence = parse_sequence(&self.sequence)?;
+        Ok(())
+    }
+
+    fn iterate(&self, state: Cplx) -> Convergence {
+        let p = state / self.zoom + self.shift;
+        let lambda = self.exponent(p.re, p.im);
+        Convergence {
+            count: self.max_count() as f64,
+            value: Cplx::new(lambda, 0.),
+            derivative: None,
+            trap: None,
+        }
+    }
+
+    fn get_serializable(&self) -> EscapeTypes {
+        EscapeTypes::Lyapunov(self.clone())
+    }
+}
@@ -1,16 +1,22 @@
 use rand::Rng;
+use rand::seq::SliceRandom;
 
 use serde::{self, Serialize, Deserialize};
 
 use log::info;
 
-use std::f64::consts::PI;
-
 use super::{EscapeTimeFractal, Convergence, EscapeTypes};
+use super::perturbation::{parse_fixed, precision, reference_orbit, iterate_delta};
+use super::kernel::mandelbrot_lanes;
+use super::region::zoom_into_boundary;
+use super::landmarks::{nucleus, misiurewicz};
 use crate::numbers::{Real, Cplx};
-use crate::fractal::{FractalBuilder, RngType, default_rng};
+use crate::fractal::FractalBuilder;
+use crate::error::FractalError;
 
-use super::style::Stylable;
+use super::style::{Style, Stylable};
+use super::normalization::Normalization;
+use super::orbit_trap::OrbitTrap;
 use crate::color;
 use crate::colormap::Colormap;
 
@@ -18,81 +24,212 @@ fn default_colormap() -> Colormap {
     Colormap::viridis()
 }
 
+/// iteration count of points which belong to the set, except for deep zooms
+const MAX_COUNT: u64 = 1000;
+
+/// the search for interesting locations starts at this view of the whole set
+const OVERVIEW_CENTER: (Real, Real) = (-0.75, 0.);
+const OVERVIEW_ZOOM: Real = 0.4;
+
+/// random points of an interesting view, which are tested for a parameter
+/// of a connected Julia set
+const JULIA_CANDIDATES: usize = 256;
+
+/// beyond this zoom `f64` can not resolve neighboring pixels anymore
+const DEEP_ZOOM: Real = 1e10;
+
+/// deep zooms cycle through the colormap every this many iterations, since
+/// the pixels of the image escape within a small fraction of `max_count`
+const COLOR_CYCLE: f64 = 100.;
+
+/// squared escape radius if a style is used, large enough for a good distance estimate
+const STYLE_THRESHOLD: Real = 1e10;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MandelbrotFractal {
-    #[serde(skip)]
-    #[serde(default = "default_rng")]
-    rng: RngType,
+    #[serde(default)]
+    pub seed: u64,
     pub description: String,
     max_count: u64,
     shift: Cplx,
     zoom: Real,
     #[serde(default = "default_colormap")]
     colormap: Colormap,
+    /// replaces the colormap, if given
+    #[serde(default)]
+    style: Option<Style>,
+    /// replaces the linear coloring, if given
+    #[serde(default)]
+    normalization: Option<Normalization>,
+    /// colors orbits by their closest approach, if given
+    #[serde(default)]
+    trap: Option<OrbitTrap>,
+    /// center of deep zooms as decimal strings of arbitrary precision
+    #[serde(default)]
+    precise_center: Option<(String, String)>,
+    /// reference orbit for perturbation, calculated before rendering
+    #[serde(skip)]
+    orbit: Vec<Cplx>,
 }
 
 impl FractalBuilder {
-    pub fn mandelbrot(self) -> MandelbrotFractal {
+    pub fn mandelbrot(self) -> Result<MandelbrotFractal, FractalError> {
         let mut rng = self.seed_rng();
+        let (style, trap) = self.style_and_trap();
 
         let zoom = if let Some(z) = self.zoom {
             z
         } else {
-            2u64.pow(rng.gen_range(0, 14))
+            2f64.powi(rng.gen_range(0, 14))
         };
 
-        // guess a random point on the complex plane which could be interesting
-        let extra_r = 1. + rng.gen_range(0.0, 0.1);
-        let shift = if let Some(c) = self.center {
-            Cplx::new(c.0 as Real, c.1 as Real)
-        } else if rng.gen::<f32>() < 0.5 {
-            // either near the cardiod
-            let phi = rng.gen_range(0., 2.*PI as Real);
-            let r = (1. - phi.cos()) / 2.;
-            let r = r * extra_r;  // go a bit outside
-            let x = r * phi.cos() + 0.25;
-            let y = r * phi.sin();
-            Cplx::new(x, y)
+        let (shift, zoom) = if let Some((ref x, ref y)) = self.precise_center {
+            let invalid = |s: &str| FractalError::InvalidArgument(format!("'{}' is not a decimal number", s));
+            (Cplx::new(x.trim().parse().map_err(|_| invalid(x))?, y.trim().parse().map_err(|_| invalid(y))?), zoom)
+        } else if let Some(c) = self.center {
+            (Cplx::new(c.0 as Real, c.1 as Real), zoom)
         } else {
-            // or near the circle
-            let phi = rng.gen_range(0., 2.*PI as Real);
-            let r = 0.25;
-            let r = r * extra_r;  // go a bit outside
-            let x = r * phi.cos() - 1.;
-            let y = r * phi.sin();
-            Cplx::new(x, y)
+            // the search is limited to the precision of f64
+            let (shift, found) = interesting_location(&mut rng, zoom.min(DEEP_ZOOM), MAX_COUNT);
+            (shift, self.zoom.unwrap_or(found))
         };
 
-
         let colormap = Colormap::random(&mut rng);
 
-        let description = format!("Mandelbrot Fractal at ~({:.3}), zoom {}x", shift, zoom);
+        // deep zooms need a high precision center and more iterations
+        let (precise_center, max_count) = if zoom > DEEP_ZOOM {
+            let center = match self.precise_center {
+                Some(ref c) => c.clone(),
+                None => (shift.re.to_string(), shift.im.to_string()),
+            };
+            parse_fixed(&center.0, 64)?;
+            parse_fixed(&center.1, 64)?;
+            (Some(center), std::cmp::max(MAX_COUNT, (100. * zoom.log2()) as u64))
+        } else {
+            (None, MAX_COUNT)
+        };
+
+        let description = if zoom < 1e6 {
+            format!("Mandelbrot Fractal at ~({:.3}), zoom {}x", shift, zoom)
+        } else {
+            format!("Mandelbrot Fractal at ~({:.3}), zoom {:.1e}x", shift, zoom)
+        };
 
         info!("Will render {}", description);
-        // FIXME: For Mandelbrot fractals the colors need to be normalized somehow
 
-        MandelbrotFractal {
+        Ok(MandelbrotFractal {
             description,
-            rng,
-            max_count: 1000,
+            seed: self.seed,
+            max_count,
             shift,
             zoom: zoom as Real,
             colormap,
-        }
+            style,
+            normalization: self.normalization,
+            trap,
+            precise_center,
+            orbit: Vec::new(),
+        })
+    }
+}
+
+impl MandelbrotFractal {
+    /// threshold is 2^2, since we compare to the square of the norm
+    /// as soon as the norm is >= 2 it is sure to diverge
+    fn threshold(&self) -> Real {
+        if self.style.is_some() { STYLE_THRESHOLD } else { 4. }
+    }
+
+    /// the batched kernel handles everything but perturbation and traps
+    fn iterate_lanes(&self, states: &[Cplx]) -> Vec<Convergence> {
+        let starts: Vec<Cplx> = states.iter().map(|&s| s / self.zoom + self.shift).collect();
+        // only the coloring by iteration counts ignores the values of
+        // interior points, such that they can be skipped
+        let interior = self.style.is_none();
+        mandelbrot_lanes(&starts, self.max_count, self.threshold(), interior)
+            .into_iter()
+            .map(|conv| Convergence {
+                derivative: conv.derivative.map(|d| d / self.zoom),
+                ..conv
+            })
+            .collect()
+    }
+}
+
+/// a view of the boundary at about `target_zoom`, found by zooming into the
+/// busiest parts of coarse renders and sometimes centered on a minibrot or
+/// a Misiurewicz point close by
+fn interesting_location<R: Rng>(rng: &mut R, target_zoom: Real, max_count: u64) -> (Cplx, Real) {
+    let counts = |points: &[Cplx]| {
+        mandelbrot_lanes(points, max_count, 4., true).iter().map(|c| c.count).collect()
+    };
+    let overview = Cplx::new(OVERVIEW_CENTER.0, OVERVIEW_CENTER.1);
+    let (center, zoom) = zoom_into_boundary(rng, overview, OVERVIEW_ZOOM, target_zoom, max_count as f64, counts);
+
+    let landmark = match rng.gen_range(0, 3) {
+        0 => nucleus(center, max_count as usize),
+        1 => misiurewicz(center),
+        _ => None
+    };
+
+    // landmarks outside of the view belong to other structures
+    match landmark {
+        Some(l) if (l - center).norm() < 0.5 / zoom => (l, zoom),
+        _ => (center, zoom)
+    }
+}
+
+/// a parameter `c` of the Mandelbrot set close to its boundary, such that
+/// the Julia set of `z^2 + c` is connected and rich in structure
+///
+/// One of the points of an interesting view, which belong to the set, or
+/// the nucleus of the view, if none does.
+pub fn julia_parameter<R: Rng>(rng: &mut R) -> Cplx {
+    let target_zoom = (2 as Real).powi(rng.gen_range(2, 9));
+    let (center, zoom) = interesting_location(rng, target_zoom, MAX_COUNT);
+
+    let candidates: Vec<Cplx> = (0..JULIA_CANDIDATES)
+        .map(|_| center + Cplx::new(rng.gen_range(-0.5, 0.5), rng.gen_range(-0.5, 0.5)) / zoom)
+        .collect();
+    let inside: Vec<Cplx> = mandelbrot_lanes(&candidates, MAX_COUNT, 4., true)
+        .iter()
+        .zip(&candidates)
+        .filter(|(conv, _)| conv.count >= MAX_COUNT as f64)
+        .map(|(_, &c)| c)
+        .collect();
+
+    match inside.choose(rng) {
+        Some(&c) => c,
+        None => nucleus(center, MAX_COUNT as usize).unwrap_or(center)
     }
 }
 
 impl Stylable for MandelbrotFractal {
     // TODO: more and nicer styles
     fn style(&self, conv: &Convergence) -> color::HSV {
+        if let Some(ref style) = self.style {
+            return style.color(conv, None, None)
+        }
+
         let c = conv.count;
-        let h = c / (self.max_count - 1) as f64;
+        let h = if self.precise_center.is_some() {
+            (c / COLOR_CYCLE).fract()
+        } else {
+            c / (self.max_count - 1) as f64
+        };
 
         self.colormap.value(&h).to_hsv()
     }
 
     fn style_name(&self) -> &str {
-        self.colormap.name()
+        match self.style {
+            Some(ref style) => &style.readable,
+            None => self.colormap.name()
+        }
+    }
+
+    fn style_normalized(&self, _conv: &Convergence, x: f64) -> color::HSV {
+        self.colormap.value(&x).to_hsv()
     }
 }
 
@@ -101,25 +238,72 @@ impl EscapeTimeFractal for MandelbrotFractal {
         &self.description
     }
 
+    fn max_count(&self) -> u64 {
+        self.max_count
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn normalization(&self) -> Option<Normalization> {
+        // styles color by themselves
+        match self.style {
+            Some(_) => None,
+            None => self.normalization
+        }
+    }
+
+    fn prepare(&mut self, resolution: (u32, u32)) -> Result<(), FractalError> {
+        if let Some((ref x, ref y)) = self.precise_center {
+            let bits = precision(self.zoom, resolution.1);
+            self.orbit = reference_orbit((x, y), bits, self.max_count, self.threshold())?;
+        }
+        Ok(())
+    }
+
     fn iterate(&self, mut state: Cplx) -> Convergence {
+        if !self.orbit.is_empty() {
+            let (ctr, value, derivative) = iterate_delta(&self.orbit, state / self.zoom, self.max_count, self.threshold());
+            let derivative = if ctr < self.max_count { Some(derivative / self.zoom) } else { None };
+            // traps are not supported, since only the difference to the reference orbit is known
+            return Convergence {count: ctr as f64, value, derivative, trap: None}
+        }
+
+        if self.trap.is_none() {
+            return self.iterate_lanes(&[state]).remove(0)
+        }
+
         let mut ctr = 0;
-        // threshold is 2^2, since we compare to the square of the norm
-        // as soon as the norm is >= 2 it is sure to diverge
-        let threshold = 4.;
+        let threshold = self.threshold();
         state = state / self.zoom + self.shift;
         let start = state;
+        // derivative with respect to `start`
+        let mut derivative = Cplx::new(1., 0.);
+
+        let mut trap = self.trap.as_ref().map(OrbitTrap::tracker);
 
         while {
+            derivative = state * derivative * 2. + 1.;
             state = state * state + start;
             ctr += 1;
+            if let Some(t) = trap.as_mut() {
+                t.visit(state);
+            }
 
             state.norm_sqr() < threshold && ctr < self.max_count && !state.re.is_nan() && !state.im.is_nan()
         } {}
-        Convergence {count: ctr as f64, value: state}
+
+        let derivative = if state.norm_sqr() >= threshold { Some(derivative / self.zoom) } else { None };
+        Convergence {count: ctr as f64, value: state, derivative, trap: trap.and_then(|t| t.hit())}
     }
 
-    fn get_rng(&mut self) -> &mut RngType {
-        &mut self.rng
+    fn iterate_batch(&self, states: &[Cplx]) -> Vec<Convergence> {
+        if self.orbit.is_empty() && self.trap.is_none() {
+            self.iterate_lanes(states)
+        } else {
+            states.iter().map(|&s| self.iterate(s)).collect()
+        }
     }
 
     fn get_serializable(&self) -> EscapeTypes {
@@ -1,40 +1,144 @@
-mod newton;
-mod julia;
+pub mod newton;
+pub mod julia;
 pub mod mandelbrot;
+pub mod quadratic;
+pub mod multibrot;
+pub mod phoenix;
+pub mod nova;
+pub mod lyapunov;
+pub mod generic_mandelbrot;
 pub mod style;
+pub mod normalization;
+pub mod basins;
+pub mod root_finding;
+pub mod orbit_trap;
+pub mod kernel;
+pub mod perturbation;
+mod landmarks;
+mod region;
 
 use serde::{self, Serialize, Deserialize};
 use log::info;
 use rayon::prelude::*;
-use itertools::iproduct;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::numbers::{Real, Cplx};
 use crate::color;
 use self::style::Stylable;
+use self::orbit_trap::TrapHit;
+use self::normalization::{Normalization, Normalizer};
 use super::estimate_quality_after;
 
-use crate::fractal::FractalBuilder;
+use crate::fractal::{FractalBuilder, Supersampling};
+use crate::fractal::progress::{Progress, RenderControl};
+use crate::error::{FractalError, from_json};
 use self::mandelbrot::MandelbrotFractal;
 use self::newton::NewtonFractal;
+use self::julia::JuliaFractal;
+use self::quadratic::{BurningShipFractal, TricornFractal};
+use self::multibrot::MultibrotFractal;
+use self::phoenix::PhoenixFractal;
+use self::nova::NovaFractal;
+use self::lyapunov::LyapunovFractal;
+use self::generic_mandelbrot::GenericMandelbrotFractal;
+
+/// adaptive supersampling refines pixels whose color channels differ
+/// by more than this from one of their neighbors
+const ADAPTIVE_THRESHOLD: f64 = 0.05;
+
+/// the part of the complex plane which is rendered
+#[derive(Clone, Copy)]
+pub struct View {
+    resolution: (i32, i32),
+    scale: (f64, f64),
+    center: (f64, f64),
+}
 
-use super::RngType;
+impl View {
+    /// map pixel `(i, j)`, shifted by `offset` pixels, to the complex plane
+    fn point(&self, pixel: (i32, i32), offset: (f64, f64)) -> Cplx {
+        let (i, j) = pixel;
+        let (x, y) = self.resolution;
+        let (u, v) = offset;
+        let xp = ((i-x/2) as f64 + u) * self.scale.0 + self.center.0;
+        let yp = ((j-y/2) as f64 + v) * self.scale.1 + self.center.1;
+        Cplx {re: xp as Real, im: yp as Real}
+    }
+}
+
+/// whether the color of pixel `k` differs noticeably from one of its 4 neighbors
+fn differs_from_neighbors(rgb: &[color::RGB], k: usize, width: usize) -> bool {
+    let differs = |other: &color::RGB| {
+        let color::RGB(r1, g1, b1) = rgb[k];
+        let &color::RGB(r2, g2, b2) = other;
+        (r1-r2).abs().max((g1-g2).abs()).max((b1-b2).abs()) > ADAPTIVE_THRESHOLD
+    };
+
+    let (i, j) = (k % width, k / width);
+    let mut neighbors = Vec::with_capacity(4);
+    if i > 0 { neighbors.push(k - 1) }
+    if i + 1 < width { neighbors.push(k + 1) }
+    if j > 0 { neighbors.push(k - width) }
+    if k + width < rgb.len() { neighbors.push(k + width) }
+
+    neighbors.into_iter().any(|n| differs(&rgb[n]))
+}
 
 pub struct Convergence {
     pub count: f64,
-    pub value: Cplx
+    pub value: Cplx,
+    /// derivative of `value` with respect to the starting point, only known
+    /// for escaped orbits of fractals which track it
+    pub derivative: Option<Cplx>,
+    /// closest approach of the orbit to the orbit trap, if the fractal has one
+    pub trap: Option<TrapHit>,
+}
+
+impl Convergence {
+    /// estimate of the distance to the boundary of the set
+    ///
+    /// During rendering the derivative is measured per pixel, such that
+    /// the distance is in units of pixels.
+    /// See <https://iquilezles.org/articles/distancefractals/>
+    pub fn distance(&self) -> Option<Real> {
+        let derivative = self.derivative?;
+        let r = self.value.norm();
+        let d = r * r.ln() / derivative.norm();
+        if d.is_finite() && r > 1. {
+            Some(d)
+        } else {
+            None
+        }
+    }
+}
+
+/// change the derivative from units of the complex plane to units of pixels
+fn in_pixels(conv: Convergence, scale: (f64, f64)) -> Convergence {
+    Convergence {
+        derivative: conv.derivative.map(|d| d * scale.0 as Real),
+        ..conv
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum EscapeTypes {
     Newton(NewtonFractal),
     Mandelbrot(MandelbrotFractal),
-    // Julia(JuliaFractal),
+    Julia(JuliaFractal),
+    BurningShip(BurningShipFractal),
+    Tricorn(TricornFractal),
+    Multibrot(MultibrotFractal),
+    Phoenix(PhoenixFractal),
+    Nova(NovaFractal),
+    Lyapunov(LyapunovFractal),
+    GenericMandelbrot(GenericMandelbrotFractal),
     None
 }
 
 impl FractalBuilder {
-    pub fn escape_type_from_json(json: &str) -> Result<EscapeTypes, serde_json::Error> {
-        serde_json::from_str(json)
+    pub fn escape_type_from_json(json: &str) -> Result<EscapeTypes, FractalError> {
+        from_json(json, "escape time fractal")
     }
 }
 
@@ -44,33 +148,115 @@ impl FractalBuilder {
 pub trait EscapeTimeFractal : Sync + Stylable {
     fn description(&self) -> &str;
     fn iterate(&self, state: Cplx) -> Convergence;
-    fn get_rng(&mut self) -> &mut RngType;
+
+    /// iterate many points at once, fractals with a batched kernel override this
+    fn iterate_batch(&self, states: &[Cplx]) -> Vec<Convergence> {
+        states.iter().map(|&s| self.iterate(s)).collect()
+    }
+
+    /// orbits which reach this iteration count belong to the set
+    fn max_count(&self) -> u64;
+
+    /// the seed from which all random decisions during rendering are derived
+    fn seed(&self) -> u64;
+
+    /// how to map the iteration counts onto the colors, `None` if the style does not use it
+    fn normalization(&self) -> Option<Normalization> {
+        None
+    }
+
+    /// called once before rendering at `resolution`
+    fn prepare(&mut self, _resolution: (u32, u32)) -> Result<(), FractalError> {
+        Ok(())
+    }
+
+    /// called once with the states of all pixels, before they are colored
+    fn analyze(&mut self, _states: &[Convergence]) {
+    }
 
     fn get_serializable(&self) -> EscapeTypes {
         EscapeTypes::None
     }
 
-    fn raster(&self, resolution: (u32, u32), scale: (f64, f64), center: (f64, f64)) -> Vec<Convergence> {
+    /// color of a point, with the iteration count mapped by `normalizer`, if given
+    fn colorize(&self, conv: &Convergence, normalizer: Option<&Normalizer>) -> color::HSV {
+        match normalizer {
+            Some(n) if conv.count < self.max_count() as f64 => self.style_normalized(conv, n.apply(conv.count)),
+            Some(_) => color::HSV(0., 0., 0.),
+            None => self.style(conv),
+        }
+    }
+
+    fn raster(&self, resolution: (u32, u32), scale: (f64, f64), center: (f64, f64), control: &RenderControl)
+        -> Result<Vec<Convergence>, FractalError>
+    {
         let (x, y) = resolution;
         let (x, y) = (x as i32, y as i32);
-        let (xscale, yscale) = scale;
-        let (cx, cy) = center;
-        let pixels: Vec<(i32, i32)> = iproduct!(0..y, 0..x).collect();
-        pixels.par_iter()
-              .map(|&(j, i)| {
-                  let xp = (i-x/2) as f64 * xscale + cx;
-                  let yp = (j-y/2) as f64 * yscale + cy;
-                  let p = Cplx {re: xp as Real, im: yp as Real};
-                  self.iterate(p)
+        let view = View { resolution: (x, y), scale, center };
+        let rows_done = AtomicUsize::new(0);
+        let rows: Result<Vec<Vec<Convergence>>, FractalError> = (0..y).into_par_iter()
+              .map(|j| {
+                  control.check()?;
+                  let points: Vec<Cplx> = (0..x).map(|i| view.point((i, j), (0., 0.))).collect();
+                  let row = self.iterate_batch(&points)
+                                .into_iter()
+                                .map(|conv| in_pixels(conv, scale))
+                                .collect();
+                  let done = rows_done.fetch_add(1, Ordering::Relaxed) + 1;
+                  control.report(Progress::Rows { done, total: y as usize });
+                  Ok(row)
               })
-              .collect()
+              .collect();
+
+        Ok(rows?.into_iter().flatten().collect())
+    }
+
+    /// replace the colors of the pixels selected by `supersampling` by the
+    /// average over multiple samples, averaged in linear RGB
+    fn supersample(&self, rgb: Vec<color::RGB>,
+                          view: View,
+                          supersampling: Supersampling,
+                          seed: u64,
+                          normalizer: Option<&Normalizer>,
+                          control: &RenderControl)
+        -> Result<Vec<color::RGB>, FractalError>
+    {
+        let width = view.resolution.0 as usize;
+
+        let pixels: Result<Vec<color::RGB>, FractalError> = (0..rgb.len()).into_par_iter()
+            .map(|k| {
+                control.check()?;
+                let refine = match supersampling {
+                    Supersampling::None => false,
+                    Supersampling::Adaptive(_) => differs_from_neighbors(&rgb, k, width),
+                    _ => true,
+                };
+                if !refine {
+                    return Ok(rgb[k].clone())
+                }
+
+                let pixel = ((k % width) as i32, (k / width) as i32);
+                let points: Vec<Cplx> = supersampling.offsets(seed, k)
+                    .into_iter()
+                    .map(|offset| view.point(pixel, offset))
+                    .collect();
+                let samples: Vec<color::RGB> = self.iterate_batch(&points)
+                    .into_iter()
+                    .map(|conv| self.colorize(&in_pixels(conv, view.scale), normalizer).to_rgb())
+                    .collect();
+                Ok(color::RGB::average(&samples))
+            })
+            .collect();
+
+        pixels
     }
 
-    // TODO: implement supersampling
     fn render(&mut self, resolution: (u32, u32),
                          scale: Option<f64>,
-                         center: Option<(f64, f64)>)
-        -> (Vec<u8>, bool)
+                         center: Option<(f64, f64)>,
+                         supersampling: Supersampling,
+                         control: &RenderControl)
+        -> Result<(Vec<u8>, bool), FractalError>
     {
         let scale = match scale {
             Some(x) => (x, x),
@@ -82,29 +268,39 @@ pub trait EscapeTimeFractal : Sync + Stylable {
 
         let center = center.unwrap_or((0., 0.));
 
-        let states = self.raster(resolution, scale, center);
+        self.prepare(resolution)?;
+        let states = self.raster(resolution, scale, center, control)?;
         let total_iterations: i64 = states.par_iter()
                                      .map(|i| i.count as i64)
                                      .sum();
         info!("{:.2}M iterations", total_iterations as f64/1e6);
 
-        let hsv: Vec<color::HSV> = states.par_iter()
-                                         .map(|i| self.style(i))
-                                         .collect();
+        self.analyze(&states);
 
-        let buffer: Vec<u8> = hsv.par_iter()
-                                .map(|hsv| {
-                                    let color::RGB(r, g, b) = hsv.to_rgb();
-                                    let a = 255;
+        // fit the normalization to the distribution of the escaping orbits
+        let normalizer = self.normalization().map(|n| {
+            let max_count = self.max_count() as f64;
+            n.fit(states.iter().map(|i| i.count).filter(|&c| c < max_count).collect())
+        });
 
-                                    vec![(r * 255.) as u8, (g * 255.) as u8, (b * 255.) as u8, a]
-                                })
-                                .flatten()
-                                .collect();
+        let mut rgb: Vec<color::RGB> = states.par_iter()
+                                             .map(|i| self.colorize(i, normalizer.as_ref()).to_rgb())
+                                             .collect();
+
+        if supersampling.is_enabled() {
+            let view = View { resolution: (resolution.0 as i32, resolution.1 as i32), scale, center };
+            rgb = self.supersample(rgb, view, supersampling, self.seed(), normalizer.as_ref(), control)?;
+        }
+
+        let rgba: Vec<color::RGBA> = rgb.par_iter().map(|c| c.to_rgba()).collect();
+
+        let buffer: Vec<u8> = rgba.par_iter()
+                                 .map(|&color::RGBA(r, g, b, a)| vec![r, g, b, a])
+                                 .flatten()
+                                 .collect();
 
-        let rgb: Vec<color::RGBA> = hsv.iter().map(|c| c.to_rgba()).collect();
-        let good = estimate_quality_after(&rgb, &resolution);
+        let good = estimate_quality_after(&rgba, &resolution);
 
-        (buffer, good)
+        Ok((buffer, good))
     }
 }
@@ -0,0 +1,154 @@
+use rand::Rng;
+
+use serde::{self, Serialize, Deserialize};
+
+use log::info;
+
+use super::{EscapeTimeFractal, Convergence, EscapeTypes};
+use super::region::{near_boundary, random_zoom, describe};
+use crate::numbers::{Real, Cplx};
+use crate::fractal::FractalBuilder;
+use crate::error::FractalError;
+
+use super::style::{CountColoring, CountColored};
+use super::normalization::Normalization;
+use super::orbit_trap::OrbitTrap;
+use crate::colormap::Colormap;
+
+/// the whole set lies in this region of the complex plane for every exponent
+const OVERVIEW: ((Real, Real), (Real, Real)) = ((-1.6, 1.6), (-1.6, 1.6));
+
+/// squared escape radius, large enough for a good distance estimate
+const THRESHOLD: Real = 1e10;
+
+/// The Multibrot set, `z -> z^d + c` with an arbitrary real exponent `d > 1`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MultibrotFractal {
+    #[serde(default)]
+    pub seed: u64,
+    pub description: String,
+    exponent: Real,
+    max_count: u64,
+    shift: Cplx,
+    zoom: Real,
+    #[serde(flatten)]
+    coloring: CountColoring,
+    /// colors orbits by their closest approach, if given
+    #[serde(default)]
+    trap: Option<OrbitTrap>,
+}
+
+impl FractalBuilder {
+    pub fn multibrot(self) -> Result<MultibrotFractal, FractalError> {
+        let mut rng = self.seed_rng();
+        let (style, trap) = self.style_and_trap();
+
+        let exponent = match self.exponent {
+            Some(d) if d > 1. => d as Real,
+            Some(d) => return Err(FractalError::InvalidArgument(format!("the exponent needs to be larger than 1, but is {}", d))),
+            None if rng.gen::<f64>() < 0.5 => rng.gen_range(3, 8) as Real,
+            None => (rng.gen_range(2.5 as Real, 6.) * 10.).round() / 10.
+        };
+
+        let mut fractal = MultibrotFractal {
+            seed: self.seed,
+            description: String::new(),
+            exponent,
+            max_count: 1000,
+            shift: Cplx::new(0., 0.),
+            zoom: 1.,
+            coloring: CountColoring {
+                colormap: Colormap::random(&mut rng),
+                style,
+                normalization: self.normalization,
+            },
+            trap: None,
+        };
+
+        fractal.shift = match self.center {
+            Some(c) => Cplx::new(c.0 as Real, c.1 as Real),
+            None => near_boundary(&mut rng, OVERVIEW, fractal.max_count as f64, |c| fractal.iterate(c).count)
+        };
+        fractal.zoom = match self.zoom {
+            Some(z) => z as Real,
+            None => random_zoom(&mut rng, 0.35, 10)
+        };
+
+        // the search for a region is faster without the trap
+        fractal.trap = trap;
+
+        let name = format!("Multibrot Fractal of z^{}", exponent);
+        fractal.description = describe(&name, fractal.shift, fractal.zoom);
+        info!("Will render {}", fractal.description);
+
+        Ok(fractal)
+    }
+}
+
+impl MultibrotFractal {
+    /// `z^(d-1)`, exact for integer exponents
+    fn pow_reduced(&self, z: Cplx) -> Cplx {
+        if self.exponent.fract() == 0. {
+            z.powi(self.exponent as i32 - 1)
+        } else {
+            z.powf(self.exponent - 1.)
+        }
+    }
+}
+
+impl CountColored for MultibrotFractal {
+    fn coloring(&self) -> &CountColoring {
+        &self.coloring
+    }
+
+    fn count_limit(&self) -> u64 {
+        self.max_count
+    }
+}
+
+impl EscapeTimeFractal for MultibrotFractal {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn max_count(&self) -> u64 {
+        self.max_count
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn normalization(&self) -> Option<Normalization> {
+        self.coloring.normalization()
+    }
+
+    fn iterate(&self, mut state: Cplx) -> Convergence {
+        let mut ctr = 0;
+        state = state / self.zoom + self.shift;
+        let start = state;
+        // derivative with respect to `start`
+        let mut derivative = Cplx::new(1., 0.);
+
+        let mut trap = self.trap.as_ref().map(OrbitTrap::tracker);
+
+        while {
+            let reduced = self.pow_reduced(state);
+            derivative = reduced * derivative * self.exponent + 1.;
+            state = reduced * state + start;
+            ctr += 1;
+            if let Some(t) = trap.as_mut() {
+                t.visit(state);
+            }
+
+            state.norm_sqr() < THRESHOLD && ctr < self.max_count && !state.re.is_nan() && !state.im.is_nan()
+        } {}
+
+        let derivative = if state.norm_sqr() >= THRESHOLD { Some(derivative / self.zoom) } else { None };
+        Convergence {count: ctr as f64, value: state, derivative, trap: trap.and_then(|t| t.hit())}
+    }
+
+    fn get_serializable(&self) -> EscapeTypes {
+        EscapeTypes::Multibrot(self.clone())
+    }
+}
@@ -5,23 +5,45 @@ use serde::{self, Serialize, Deserialize};
 use log::info;
 
 use super::{EscapeTimeFractal, Convergence, EscapeTypes};
-use crate::fractal::{FractalBuilder, RngType, default_rng};
+use crate::fractal::FractalBuilder;
 use crate::numbers::{Coef, Cplx, ComplexFunction};
 use super::style::{Style, Stylable, style_serialize, style_deserialize};
+use super::basins::{Basins, cluster_roots};
+use super::root_finding::RootFinding;
 use crate::color;
+use crate::colormap::Colormap;
+use crate::error::FractalError;
+
+/// at most this many roots are listed in the description
+const LISTED_ROOTS: usize = 6;
+
+fn default_method() -> RootFinding {
+    RootFinding::Newton
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NewtonFractal {
     a: Coef,
     f: ComplexFunction,
+    /// older versions only knew Newton's method
+    #[serde(default = "default_method")]
+    method: RootFinding,
+    /// exact derivatives of `f` needed by `method`, calculated on construction
     #[serde(skip)]
-    #[serde(default = "default_rng")]
-    rng: RngType,
+    derivatives: Vec<ComplexFunction>,
+    #[serde(default)]
+    pub seed: u64,
     pub description: String,
     #[serde(serialize_with = "style_serialize", deserialize_with = "style_deserialize")]
     style: Style,
     random_color: f64,
-    random_count: f64
+    random_count: f64,
+    /// colors by the root every point converges to, replaces the style, if given
+    #[serde(default)]
+    basins: Option<Basins>,
+    /// the distinct roots found so far
+    #[serde(default)]
+    roots: Vec<Cplx>,
 }
 
 impl FractalBuilder {
@@ -40,20 +62,12 @@ impl FractalBuilder {
             None => Coef::random(&mut rng)
         };
 
+        let explicit_style = self.style.is_some();
         let style = match self.style {
             Some(x) => x,
             None => Style::random_style(&mut rng)
         };
 
-
-        let mut description = match a {
-            Coef::Real(x) if (1. - x).abs() < 1e-4 => "Newton Fractal of ".to_string(),
-            Coef::Real(x) => format!("Generalized Newton Fractal (x = {}) of ", x),
-            Coef::Complex(y) => format!("Generalized Newton Fractal (x = {}) of ", y)
-        };
-        description += &f.human_readable();
-
-        info!("Will render {}", description);
         info!("use style '{}'", style);
 
         // use randomness to determine the colors
@@ -63,25 +77,98 @@ impl FractalBuilder {
         info!("rcol {}", random_color);
         info!("rcnt {}", random_count);
 
-        NewtonFractal {
+        let basins = match self.basins {
+            Some(non_converged) => Some(Basins { colormap: Colormap::random(&mut rng), non_converged }),
+            None if !explicit_style && rng.gen::<f64>() < 0.5 => Some(Basins {
+                colormap: Colormap::random(&mut rng),
+                non_converged: color::RGB(0., 0., 0.),
+            }),
+            None => None
+        };
+
+        let method = match self.root_finding {
+            Some(x) => x,
+            None => RootFinding::random(&mut rng)
+        };
+
+        let mut fractal = NewtonFractal {
             a,
+            method,
+            derivatives: method.derivatives(&f),
             f,
-            description,
-            rng,
+            description: String::new(),
+            seed: self.seed,
             style,
             random_color,
-            random_count
+            random_count,
+            basins,
+            roots: Vec::new(),
+        };
+
+        // find the roots in the visible region, rendering may find more
+        if fractal.basins.is_some() {
+            let n = 24;
+            let states: Vec<Convergence> = (0..n*n)
+                .map(|k| Cplx::new((k % n) as f64 / n as f64 * 2. - 1., (k / n) as f64 / n as f64 * 2. - 1.))
+                .map(|z| fractal.iterate(z))
+                .collect();
+            let max_count = fractal.max_count();
+            let f = &fractal.f;
+            cluster_roots(&mut fractal.roots, states.iter(), max_count, |z| is_root(f, z));
+        }
+
+        fractal.description = fractal.describe();
+        info!("Will render {}", fractal.description);
+
+        fractal
+    }
+}
+
+/// the iterations converge to fixed points, but not all of them are roots
+fn is_root(f: &ComplexFunction, z: Cplx) -> bool {
+    f.eval(z).norm() < 1e-6
+}
+
+impl NewtonFractal {
+    fn describe(&self) -> String {
+        let name = self.method.name();
+        let mut description = match self.a {
+            Coef::Real(x) if (1. - x).abs() < 1e-4 => format!("{} Fractal of ", name),
+            Coef::Real(x) => format!("Generalized {} Fractal (x = {}) of ", name, x),
+            Coef::Complex(y) => format!("Generalized {} Fractal (x = {}) of ", name, y)
+        };
+        description += &self.f.human_readable();
+
+        if self.basins.is_some() && !self.roots.is_empty() {
+            let roots: Vec<String> = self.roots.iter()
+                                               .take(LISTED_ROOTS)
+                                               // `+ 0.` avoids printing `-0`
+                                               .map(|r| Cplx::new((r.re * 1e3).round() / 1e3 + 0., (r.im * 1e3).round() / 1e3 + 0.))
+                                               .map(|r| format!("{}", r))
+                                               .collect();
+            description += &format!(" with roots {}", roots.join(", "));
+            if self.roots.len() > LISTED_ROOTS {
+                description += ", ...";
+            }
         }
+
+        description
     }
 }
 
 impl Stylable for NewtonFractal {
     fn style(&self, conv: &Convergence) -> color::HSV {
-        (self.style.callable)(conv, Some(self.random_color), Some(self.random_count))
+        match self.basins {
+            Some(ref basins) => basins.color(&self.roots, conv, self.max_count()),
+            None => self.style.color(conv, Some(self.random_color), Some(self.random_count))
+        }
     }
 
     fn style_name(&self) -> &str {
-        &self.style.readable
+        match self.basins {
+            Some(_) => "basins",
+            None => &self.style.readable
+        }
     }
 }
 
@@ -90,15 +177,53 @@ impl EscapeTimeFractal for NewtonFractal {
         &self.description
     }
 
+    fn max_count(&self) -> u64 {
+        1000
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn prepare(&mut self, _resolution: (u32, u32)) -> Result<(), FractalError> {
+        self.method.validate()?;
+
+        // a fractal loaded from json does not know its derivatives yet
+        if self.derivatives.is_empty() {
+            self.derivatives = self.method.derivatives(&self.f);
+        }
+        Ok(())
+    }
+
+    fn analyze(&mut self, states: &[Convergence]) {
+        if self.basins.is_some() {
+            let (known, max_count) = (self.roots.len(), self.max_count());
+            let f = &self.f;
+            cluster_roots(&mut self.roots, states.iter(), max_count, |z| is_root(f, z));
+            if self.roots.len() > known {
+                self.description = self.describe();
+            }
+        }
+    }
+
     fn iterate(&self, mut state: Cplx) -> Convergence {
         let mut ctr = 0.;
         let threshold = 1e-12;
         let mut tmp;
 
+        let computed;
+        let derivatives = if self.derivatives.is_empty() {
+            computed = self.method.derivatives(&self.f);
+            &computed
+        } else {
+            &self.derivatives
+        };
+        let step = |state: Cplx| self.method.step(&self.f, derivatives, state);
+
         let kernel: Box<dyn Fn(Cplx) -> Cplx> = match self.a {
-            Coef::Real(x) if (1. - x).abs() < 1e-4 => Box::new(move |state| state - self.f.eval(state) / self.f.derivative(&state)),
-            Coef::Real(x) => Box::new(move |state| state - x * self.f.eval(state) / self.f.derivative(&state)),
-            Coef::Complex(z) => Box::new(move |state| state - z * self.f.eval(state) / self.f.derivative(&state)),
+            Coef::Real(x) if (1. - x).abs() < 1e-4 => Box::new(move |state| state + step(state)),
+            Coef::Real(x) => Box::new(move |state| state + x * step(state)),
+            Coef::Complex(z) => Box::new(move |state| state + z * step(state)),
         };
 
         // this is a do while loop, mind that the "body" is actually the
@@ -108,17 +233,13 @@ impl EscapeTimeFractal for NewtonFractal {
             state = kernel(state);
             ctr += 1.;
 
-            (state - tmp).norm_sqr() > threshold && ctr < 1000. && !state.re.is_nan() && !state.im.is_nan()
+            (state - tmp).norm_sqr() > threshold && ctr < self.max_count() as f64 && !state.re.is_nan() && !state.im.is_nan()
         } {}
         // for smooth color, add a normalized distance
         if (state - tmp).norm_sqr() < threshold {
             ctr += (state - tmp).norm_sqr() / threshold;
         }
-        Convergence {count: ctr as f64, value: state}
-    }
-
-    fn get_rng(&mut self) -> &mut RngType {
-        &mut self.rng
+        Convergence {count: ctr as f64, value: state, derivative: None, trap: None}
     }
 
     fn get_serializable(&self) -> EscapeTypes {
@@ -0,0 +1,126 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{self, Serialize, Deserialize};
+
+use crate::error::FractalError;
+
+/// how iteration counts are mapped to the range `[0, 1]` of a colormap
+///
+/// All mappings are fitted to the distribution of iteration counts of
+/// the escaping pixels of a render, pixels inside of the set are black.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Normalization {
+    /// linear between the smallest and the largest count
+    Linear,
+    /// linear in the logarithm of the count
+    Log,
+    /// linear in the square root of the count
+    Sqrt,
+    /// histogram equalization, every color is used by the same number of pixels
+    Equalize,
+    /// linear between two percentiles, counts outside are clipped
+    Percentile(f64, f64),
+}
+
+impl Normalization {
+    fn transform(&self, count: f64) -> f64 {
+        match *self {
+            Normalization::Log => count.max(1.).ln(),
+            Normalization::Sqrt => count.sqrt(),
+            _ => count
+        }
+    }
+
+    /// fit the mapping to the iteration counts of the escaping pixels
+    pub fn fit(&self, mut counts: Vec<f64>) -> Normalizer {
+        counts.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let percentile = |p: f64| {
+            let idx = (p / 100. * (counts.len() - 1) as f64).round() as usize;
+            counts[idx.min(counts.len() - 1)]
+        };
+
+        let (low, high) = match *self {
+            _ if counts.is_empty() => (0., 0.),
+            Normalization::Percentile(lo, hi) => (percentile(lo), percentile(hi)),
+            _ => (self.transform(counts[0]), self.transform(counts[counts.len() - 1]))
+        };
+
+        Normalizer {
+            normalization: *self,
+            sorted: counts,
+            low,
+            high,
+        }
+    }
+}
+
+/// a `Normalization` fitted to the iteration counts of one render
+pub struct Normalizer {
+    normalization: Normalization,
+    sorted: Vec<f64>,
+    low: f64,
+    high: f64,
+}
+
+impl Normalizer {
+    /// map `count` to `[0, 1]`
+    pub fn apply(&self, count: f64) -> f64 {
+        if self.sorted.is_empty() {
+            return 0.
+        }
+
+        match self.normalization {
+            Normalization::Equalize => {
+                let rank = self.sorted.partition_point(|&x| x <= count);
+                rank as f64 / self.sorted.len() as f64
+            }
+            _ if self.high <= self.low => 0.,
+            n => ((n.transform(count) - self.low) / (self.high - self.low)).clamp(0., 1.)
+        }
+    }
+}
+
+impl FromStr for Normalization {
+    type Err = FractalError;
+
+    /// parse `linear`, `log`, `sqrt`, `equalize` or `percentile:low,high`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FractalError::InvalidArgument(
+            format!("normalization needs to be `linear`, `log`, `sqrt`, `equalize` or `percentile:low,high`, but is '{}'", s)
+        );
+
+        let mut parts = s.splitn(2, ':');
+        match (parts.next().unwrap_or(""), parts.next()) {
+            ("linear", None) => Ok(Normalization::Linear),
+            ("log", None) => Ok(Normalization::Log),
+            ("sqrt", None) => Ok(Normalization::Sqrt),
+            ("equalize", None) => Ok(Normalization::Equalize),
+            ("percentile", None) => Ok(Normalization::Percentile(1., 99.)),
+            ("percentile", Some(p)) => {
+                let mut p = p.splitn(2, ',');
+                let lo = p.next().and_then(|x| x.trim().parse::<f64>().ok()).ok_or_else(invalid)?;
+                let hi = p.next().and_then(|x| x.trim().parse::<f64>().ok()).ok_or_else(invalid)?;
+                if !(0. ..=100.).contains(&lo) || !(0. ..=100.).contains(&hi) || lo >= hi {
+                    return Err(invalid())
+                }
+                Ok(Normalization::Percentile(lo, hi))
+            }
+            _ => Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Normalization::Linear => write!(f, "linear"),
+            Normalization::Log => write!(f, "log"),
+            Normalization::Sqrt => write!(f, "sqrt"),
+            Normalization::Equalize => write!(f, "equalize"),
+            Normalization::Percentile(lo, hi) => write!(f, "percentile:{},{}", lo, hi),
+        }
+    }
+}
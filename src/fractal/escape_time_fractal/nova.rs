@@ -0,0 +1,129 @@
+use serde::{self, Serialize, Deserialize};
+
+use log::info;
+
+use super::{EscapeTimeFractal, Convergence, EscapeTypes};
+use super::region::{near_boundary, random_zoom, describe};
+use crate::numbers::{Real, Cplx, Coef};
+use crate::fractal::FractalBuilder;
+
+use super::style::{CountColoring, CountColored};
+use super::normalization::Normalization;
+use crate::colormap::Colormap;
+
+/// the interesting part of the parameter plane
+const OVERVIEW: ((Real, Real), (Real, Real)) = ((-1.5, 1.), (-1.2, 1.2));
+
+/// The Nova fractal, a relaxed Newton iteration for `z^3 - 1` with an added `c`,
+/// `z -> z - R (z^3 - 1) / (3 z^2) + c`.
+///
+/// The pixels are the parameters `c`, every orbit starts at the root `z = 1`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NovaFractal {
+    #[serde(default)]
+    pub seed: u64,
+    pub description: String,
+    relaxation: Cplx,
+    max_count: u64,
+    shift: Cplx,
+    zoom: Real,
+    #[serde(flatten)]
+    coloring: CountColoring,
+}
+
+impl FractalBuilder {
+    pub fn nova(self) -> NovaFractal {
+        let mut rng = self.seed_rng();
+
+        let a = match self.a {
+            Some(x) => x,
+            None => Coef::random(&mut rng)
+        };
+        let relaxation = match a {
+            Coef::Real(x) => Cplx::new(x, 0.),
+            Coef::Complex(z) => z,
+        };
+
+        let mut fractal = NovaFractal {
+            seed: self.seed,
+            description: String::new(),
+            relaxation,
+            max_count: 500,
+            shift: Cplx::new(0., 0.),
+            zoom: 1.,
+            coloring: CountColoring {
+                colormap: Colormap::random(&mut rng),
+                style: self.style,
+                normalization: self.normalization,
+            },
+        };
+
+        fractal.shift = match self.center {
+            Some(c) => Cplx::new(c.0 as Real, c.1 as Real),
+            None => near_boundary(&mut rng, OVERVIEW, fractal.max_count as f64, |c| fractal.iterate(c).count)
+        };
+        fractal.zoom = match self.zoom {
+            Some(z) => z as Real,
+            None => random_zoom(&mut rng, 0.4, 10)
+        };
+
+        let name = format!("Nova Fractal (R = {:.1})", relaxation);
+        fractal.description = describe(&name, fractal.shift, fractal.zoom);
+        info!("Will render {}", fractal.description);
+
+        fractal
+    }
+}
+
+impl CountColored for NovaFractal {
+    fn coloring(&self) -> &CountColoring {
+        &self.coloring
+    }
+
+    fn count_limit(&self) -> u64 {
+        self.max_count
+    }
+}
+
+impl EscapeTimeFractal for NovaFractal {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn max_count(&self) -> u64 {
+        self.max_count
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn normalization(&self) -> Option<Normalization> {
+        self.coloring.normalization()
+    }
+
+    fn iterate(&self, state: Cplx) -> Convergence {
+        let mut ctr = 0;
+        let threshold = 1e-10;
+        let c = state / self.zoom + self.shift;
+        let mut z = Cplx::new(1., 0.);
+        let mut tmp;
+
+        // this orbit converges instead of escaping, points which
+        // never converge count as part of the set
+        while {
+            tmp = z;
+            z = z - self.relaxation * (z * z * z - 1.) / (z * z * 3.) + c;
+            ctr += 1;
+
+            (z - tmp).norm_sqr() > threshold && ctr < self.max_count && !z.re.is_nan() && !z.im.is_nan()
+        } {}
+
+        let ctr = if z.re.is_nan() || z.im.is_nan() { self.max_count } else { ctr };
+        Convergence {count: ctr as f64, value: z, derivative: None, trap: None}
+    }
+
+    fn get_serializable(&self) -> EscapeTypes {
+        EscapeTypes::Nova(self.clone())
+    }
+}
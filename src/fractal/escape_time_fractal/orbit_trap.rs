@@ -0,0 +1,235 @@
+use std::fmt;
+use std::str::FromStr;
+use std::f64::consts::PI;
+
+use rand::{Rng, SeedableRng};
+
+use serde::{self, Serialize, Deserialize};
+
+use crate::numbers::{Real, Cplx};
+use crate::fractal::{FractalBuilder, RngType, Style, stream_seed};
+use crate::error::FractalError;
+
+/// traps are drawn from their own random stream, such that a trap does not
+/// change the rest of the fractal
+const TRAP_STREAM: u64 = 0;
+
+/// side length in pixels of random image traps
+const IMAGE_WIDTH: usize = 8;
+
+/// a shape in the complex plane, orbits are colored by how close they get to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrbitTrap {
+    Point(Cplx),
+    /// the line through `point` with direction `angle`
+    Line { point: Cplx, angle: Real },
+    /// two perpendicular lines through `center`, rotated by `angle`
+    Cross { center: Cplx, angle: Real },
+    Circle { center: Cplx, radius: Real },
+    /// grayscale bitmap of `width` columns on the square with the lower left
+    /// `corner` and the side length `size`, dark pixels are close
+    Image { corner: Cplx, size: Real, width: usize, pixels: Vec<f64> },
+}
+
+/// the shape of an orbit trap, the geometry is chosen randomly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrapKind {
+    Point,
+    Line,
+    Cross,
+    Circle,
+    Image,
+}
+
+/// the closest approach of an orbit to a trap
+#[derive(Debug, Clone, Copy)]
+pub struct TrapHit {
+    pub distance: Real,
+    /// direction from the center of the trap to the closest point, in `[0, 1)`
+    pub angle: f64,
+}
+
+impl TrapKind {
+    pub fn random<T: Rng>(rng: &mut T) -> TrapKind {
+        match rng.gen_range(0, 5) {
+            0 => TrapKind::Point,
+            1 => TrapKind::Line,
+            2 => TrapKind::Cross,
+            3 => TrapKind::Circle,
+            _ => TrapKind::Image,
+        }
+    }
+}
+
+impl FromStr for TrapKind {
+    type Err = FractalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "point" => Ok(TrapKind::Point),
+            "line" => Ok(TrapKind::Line),
+            "cross" => Ok(TrapKind::Cross),
+            "circle" => Ok(TrapKind::Circle),
+            "image" => Ok(TrapKind::Image),
+            _ => Err(FractalError::InvalidArgument(
+                format!("orbit trap needs to be `point`, `line`, `cross`, `circle` or `image`, but is '{}'", s)
+            ))
+        }
+    }
+}
+
+impl fmt::Display for TrapKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            TrapKind::Point => "point",
+            TrapKind::Line => "line",
+            TrapKind::Cross => "cross",
+            TrapKind::Circle => "circle",
+            TrapKind::Image => "image",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl OrbitTrap {
+    /// a trap of the given `kind`, or of a random one, near the origin where
+    /// most orbits pass by
+    pub fn random<T: Rng>(rng: &mut T, kind: Option<TrapKind>) -> OrbitTrap {
+        let kind = match kind {
+            Some(k) => k,
+            None => TrapKind::random(rng)
+        };
+        match kind {
+            TrapKind::Point => OrbitTrap::Point(near_origin(rng, 0.5)),
+            TrapKind::Line => OrbitTrap::Line {
+                point: near_origin(rng, 0.5),
+                angle: rng.gen_range(0., PI),
+            },
+            TrapKind::Cross => OrbitTrap::Cross {
+                center: near_origin(rng, 0.5),
+                angle: rng.gen_range(0., PI / 2.),
+            },
+            TrapKind::Circle => OrbitTrap::Circle {
+                center: near_origin(rng, 0.3),
+                radius: rng.gen_range(0.2, 1.),
+            },
+            TrapKind::Image => {
+                let size = rng.gen_range(0.5, 2.);
+                let corner = near_origin(rng, 0.2) - Cplx::new(size, size) / 2.;
+
+                // mirrored random pattern, which looks less like noise
+                let mut pixels = vec![1.; IMAGE_WIDTH * IMAGE_WIDTH];
+                for j in 0..IMAGE_WIDTH {
+                    for i in 0..IMAGE_WIDTH / 2 {
+                        let intensity = if rng.gen::<f64>() < 0.5 { 0. } else { 1. };
+                        pixels[j * IMAGE_WIDTH + i] = intensity;
+                        pixels[j * IMAGE_WIDTH + IMAGE_WIDTH - 1 - i] = intensity;
+                    }
+                }
+
+                OrbitTrap::Image { corner, size, width: IMAGE_WIDTH, pixels }
+            }
+        }
+    }
+
+    pub fn kind(&self) -> TrapKind {
+        match self {
+            OrbitTrap::Point(_) => TrapKind::Point,
+            OrbitTrap::Line { .. } => TrapKind::Line,
+            OrbitTrap::Cross { .. } => TrapKind::Cross,
+            OrbitTrap::Circle { .. } => TrapKind::Circle,
+            OrbitTrap::Image { .. } => TrapKind::Image,
+        }
+    }
+
+    fn center(&self) -> Cplx {
+        match *self {
+            OrbitTrap::Point(p) => p,
+            OrbitTrap::Line { point, .. } => point,
+            OrbitTrap::Cross { center, .. } | OrbitTrap::Circle { center, .. } => center,
+            OrbitTrap::Image { corner, size, .. } => corner + Cplx::new(size, size) / 2.,
+        }
+    }
+
+    /// distance of `z` to the trap
+    pub fn measure(&self, z: Cplx) -> TrapHit {
+        let distance = match *self {
+            OrbitTrap::Point(p) => (z - p).norm(),
+            OrbitTrap::Line { point, angle } => {
+                // rotate the line onto the real axis
+                let w = (z - point) * Cplx::from_polar(1., -angle);
+                w.im.abs()
+            }
+            OrbitTrap::Cross { center, angle } => {
+                let w = (z - center) * Cplx::from_polar(1., -angle);
+                w.re.abs().min(w.im.abs())
+            }
+            OrbitTrap::Circle { center, radius } => ((z - center).norm() - radius).abs(),
+            OrbitTrap::Image { corner, size, width, ref pixels } => {
+                let pixel = size / width as Real;
+                let w = (z - corner) / size;
+                if (0. ..1.).contains(&w.re) && (0. ..1.).contains(&w.im) {
+                    let i = (w.re * width as Real) as usize;
+                    let j = (w.im * width as Real) as usize;
+                    pixels.get(j * width + i).map_or(pixel, |intensity| intensity * pixel)
+                } else {
+                    // outside of the image everything is as far as a white pixel
+                    let dx = (-w.re).max(w.re - 1.).max(0.);
+                    let dy = (-w.im).max(w.im - 1.).max(0.);
+                    dx.hypot(dy) * size + pixel
+                }
+            }
+        };
+
+        let angle = (z - self.center()).arg() / (2. * PI) + 0.5;
+        TrapHit { distance, angle: angle.fract() }
+    }
+
+    pub fn tracker(&self) -> TrapTracker<'_> {
+        TrapTracker { trap: self, closest: None }
+    }
+}
+
+fn near_origin<T: Rng>(rng: &mut T, r: Real) -> Cplx {
+    Cplx::new(rng.gen_range(-r, r), rng.gen_range(-r, r))
+}
+
+/// follows an orbit and remembers its closest approach to a trap
+pub struct TrapTracker<'a> {
+    trap: &'a OrbitTrap,
+    closest: Option<TrapHit>,
+}
+
+impl<'a> TrapTracker<'a> {
+    pub fn visit(&mut self, z: Cplx) {
+        let hit = self.trap.measure(z);
+        if !hit.distance.is_finite() {
+            return
+        }
+        match self.closest {
+            Some(ref closest) if closest.distance <= hit.distance => (),
+            _ => self.closest = Some(hit)
+        }
+    }
+
+    pub fn hit(&self) -> Option<TrapHit> {
+        self.closest
+    }
+}
+
+impl FractalBuilder {
+    /// the style and the orbit trap of an escape time fractal
+    ///
+    /// A trap is generated if one is requested or if the style needs one,
+    /// requested traps are colored by the trap style, unless a style is given.
+    pub(super) fn style_and_trap(&self) -> (Option<Style>, Option<OrbitTrap>) {
+        let needs_trap = matches!(self.style, Some(ref s) if s.needs_trap());
+        if self.trap.is_none() && !needs_trap {
+            return (self.style.clone(), None)
+        }
+
+        let mut rng = RngType::seed_from_u64(stream_seed(self.seed, TRAP_STREAM));
+        let style = self.style.clone().or_else(|| Some(Style::trap()));
+        (style, Some(OrbitTrap::random(&mut rng, self.trap)))
+    }
+}
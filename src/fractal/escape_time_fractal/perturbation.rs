@@ -0,0 +1,123 @@
+//! Deep zooms by perturbation theory.
+//!
+//! Only a single reference orbit is calculated in high precision, every pixel
+//! iterates its difference to the reference in `f64`, which stays accurate
+//! far beyond the zoom level where the coordinates themselves are resolvable.
+//! See <https://mathr.co.uk/blog/2021-05-14_deep_zoom_theory_and_practice.html>
+
+use num::{BigInt, ToPrimitive, Zero};
+
+use crate::numbers::{Real, Cplx};
+use crate::error::FractalError;
+
+/// parse a decimal number as fixed point number with `bits` binary digits after the point
+///
+/// # Arguments
+///
+/// * `s` - decimal number of arbitrary length, e.g. `-0.7436438870371587047521915`
+/// * `bits` - number of fractional bits of the result
+pub fn parse_fixed(s: &str, bits: usize) -> Result<BigInt, FractalError> {
+    let invalid = || FractalError::InvalidArgument(format!("'{}' is not a decimal number", s));
+
+    let t = s.trim();
+    let (negative, t) = match t.chars().next() {
+        Some('-') => (true, &t[1..]),
+        Some('+') => (false, &t[1..]),
+        _ => (false, t),
+    };
+    let mut parts = t.splitn(2, '.');
+    let int = parts.next().unwrap_or("");
+    let frac = parts.next().unwrap_or("");
+
+    if int.is_empty() && frac.is_empty() || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(invalid())
+    }
+
+    let digits: BigInt = format!("0{}{}", int, frac).parse().map_err(|_| invalid())?;
+    let value = (digits << bits) / num::pow(BigInt::from(10), frac.len());
+
+    Ok(if negative { -value } else { value })
+}
+
+/// convert a fixed point number with `bits` fractional bits to `f64`
+pub fn fixed_to_real(x: &BigInt, bits: usize) -> Real {
+    // keep 64 significant bits to stay in the range of f64
+    let keep = std::cmp::min(bits, 64);
+    let reduced: BigInt = x >> (bits - keep);
+    reduced.to_f64().unwrap_or(Real::NAN) / (2 as Real).powi(keep as i32)
+}
+
+/// number of fractional bits needed to resolve pixels at `zoom` on an image `height` pixels high
+pub fn precision(zoom: Real, height: u32) -> usize {
+    let pixels = zoom * height as Real;
+    pixels.log2().max(0.) as usize + 64
+}
+
+/// iterate `z -> z^2 + c` in high precision until `|z|^2` exceeds `threshold` or `max_count` is reached
+///
+/// The reference orbit starts with `z_0 = 0`.
+pub fn reference_orbit(center: (&str, &str), bits: usize, max_count: u64, threshold: Real) -> Result<Vec<Cplx>, FractalError> {
+    let cr = parse_fixed(center.0, bits)?;
+    let ci = parse_fixed(center.1, bits)?;
+    let threshold = BigInt::from(threshold.ceil() as u64) << bits;
+
+    let mut zr = BigInt::zero();
+    let mut zi = BigInt::zero();
+    let mut orbit = vec![Cplx::new(0., 0.)];
+    for _ in 0..max_count {
+        let zr2: BigInt = (&zr * &zr) >> bits;
+        let zi2: BigInt = (&zi * &zi) >> bits;
+        let zri: BigInt = (&zr * &zi) >> (bits - 1);
+        zr = zr2 - zi2 + &cr;
+        zi = zri + &ci;
+        orbit.push(Cplx::new(fixed_to_real(&zr, bits), fixed_to_real(&zi, bits)));
+
+        let norm: BigInt = ((&zr * &zr) >> bits) + ((&zi * &zi) >> bits);
+        if norm > threshold {
+            break
+        }
+    }
+
+    Ok(orbit)
+}
+
+/// iterate the difference `dz` of a pixel at `c + dc` to the reference orbit of `c`
+/// until `|z|^2` exceeds `threshold` or `max_count` is reached
+///
+/// Returns the number of iterations, the last value and its derivative with respect to `dc`.
+///
+/// # Remarks
+///
+/// Like the direct iteration, the pixel starts at `z_1 = c + dc` and counts
+/// the iterations from there, such that the counts agree for both.
+///
+/// If the pixel orbit comes closer to zero than to the reference orbit, the
+/// difference loses its precision (a glitch). In this case, and when the
+/// reference escapes before the pixel does, the pixel is rebased onto the
+/// start of the reference orbit, which is always valid, since it starts at 0.
+pub fn iterate_delta(orbit: &[Cplx], dc: Cplx, max_count: u64, threshold: Real) -> (u64, Cplx, Cplx) {
+    let mut dz = dc;
+    let mut z = orbit[1] + dz;
+    let mut derivative = Cplx::new(1., 0.);
+    let mut m = 1;
+    let mut ctr = 0;
+
+    while ctr < max_count {
+        if z.norm_sqr() < dz.norm_sqr() || m == orbit.len() - 1 {
+            dz = z;
+            m = 0;
+        }
+
+        derivative = z * derivative * 2. + 1.;
+        dz = orbit[m] * dz * 2. + dz * dz + dc;
+        m += 1;
+        z = orbit[m] + dz;
+        ctr += 1;
+
+        if z.norm_sqr() >= threshold || z.re.is_nan() || z.im.is_nan() {
+            break
+        }
+    }
+
+    (ctr, z, derivative)
+}
@@ -0,0 +1,141 @@
+use rand::Rng;
+
+use serde::{self, Serialize, Deserialize};
+
+use log::info;
+
+use super::{EscapeTimeFractal, Convergence, EscapeTypes};
+use super::region::{near_boundary, random_zoom, describe};
+use crate::numbers::{Real, Cplx};
+use crate::fractal::FractalBuilder;
+
+use super::style::{CountColoring, CountColored};
+use super::normalization::Normalization;
+use super::orbit_trap::OrbitTrap;
+use crate::colormap::Colormap;
+
+/// the whole Julia set lies in this region of the complex plane
+const OVERVIEW: ((Real, Real), (Real, Real)) = ((-1.5, 1.5), (-1.5, 1.5));
+
+/// The Phoenix fractal, a Julia set of `z_{n+1} = z_n^2 + c + p z_{n-1}`.
+///
+/// The pixels are the starting points `z_0`, while `z_{-1} = 0`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PhoenixFractal {
+    #[serde(default)]
+    pub seed: u64,
+    pub description: String,
+    c: Cplx,
+    p: Cplx,
+    max_count: u64,
+    shift: Cplx,
+    zoom: Real,
+    #[serde(flatten)]
+    coloring: CountColoring,
+    /// colors orbits by their closest approach, if given
+    #[serde(default)]
+    trap: Option<OrbitTrap>,
+}
+
+impl FractalBuilder {
+    pub fn phoenix(self) -> PhoenixFractal {
+        let mut rng = self.seed_rng();
+        let (style, trap) = self.style_and_trap();
+
+        // close to Ushiki's original parameters, which give a connected set
+        let c = Cplx::new(rng.gen_range(0.54, 0.58), rng.gen_range(-0.02, 0.02));
+        let p = Cplx::new(rng.gen_range(-0.52, -0.48), rng.gen_range(-0.02, 0.02));
+
+        let mut fractal = PhoenixFractal {
+            seed: self.seed,
+            description: String::new(),
+            c,
+            p,
+            max_count: 1000,
+            shift: Cplx::new(0., 0.),
+            zoom: 1.,
+            coloring: CountColoring {
+                colormap: Colormap::random(&mut rng),
+                style,
+                normalization: self.normalization,
+            },
+            trap: None,
+        };
+
+        // either the whole set or a detail of its boundary
+        let detail = rng.gen::<f64>() < 0.5;
+        fractal.shift = match self.center {
+            Some(c) => Cplx::new(c.0 as Real, c.1 as Real),
+            None if detail => near_boundary(&mut rng, OVERVIEW, fractal.max_count as f64, |c| fractal.iterate(c).count),
+            None => Cplx::new(0., 0.)
+        };
+        fractal.zoom = match self.zoom {
+            Some(z) => z as Real,
+            None if detail => random_zoom(&mut rng, 0.35, 6),
+            None => 0.35
+        };
+
+        // the search for a region is faster without the trap
+        fractal.trap = trap;
+
+        let name = format!("Phoenix Fractal (c = {:.3}, p = {:.3})", c, p);
+        fractal.description = describe(&name, fractal.shift, fractal.zoom);
+        info!("Will render {}", fractal.description);
+
+        fractal
+    }
+}
+
+impl CountColored for PhoenixFractal {
+    fn coloring(&self) -> &CountColoring {
+        &self.coloring
+    }
+
+    fn count_limit(&self) -> u64 {
+        self.max_count
+    }
+}
+
+impl EscapeTimeFractal for PhoenixFractal {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn max_count(&self) -> u64 {
+        self.max_count
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn normalization(&self) -> Option<Normalization> {
+        self.coloring.normalization()
+    }
+
+    fn iterate(&self, mut state: Cplx) -> Convergence {
+        let mut ctr = 0;
+        let threshold = 4.;
+        state = state / self.zoom + self.shift;
+        let mut previous = Cplx::new(0., 0.);
+
+        let mut trap = self.trap.as_ref().map(OrbitTrap::tracker);
+
+        while {
+            let next = state * state + self.c + self.p * previous;
+            previous = state;
+            state = next;
+            ctr += 1;
+            if let Some(t) = trap.as_mut() {
+                t.visit(state);
+            }
+
+            state.norm_sqr() < threshold && ctr < self.max_count && !state.re.is_nan() && !state.im.is_nan()
+        } {}
+        Convergence {count: ctr as f64, value: state, derivative: None, trap: trap.and_then(|t| t.hit())}
+    }
+
+    fn get_serializable(&self) -> EscapeTypes {
+        EscapeTypes::Phoenix(self.clone())
+    }
+}
@@ -0,0 +1,187 @@
+//! Variants of the Mandelbrot set, which fold `z` before squaring it.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::{self, Serialize, Deserialize};
+
+use log::info;
+
+use super::{EscapeTimeFractal, Convergence, EscapeTypes};
+use super::region::{near_boundary, random_zoom, describe};
+use crate::numbers::{Real, Cplx};
+use crate::fractal::FractalBuilder;
+
+use super::style::{CountColoring, CountColored};
+use super::normalization::Normalization;
+use super::orbit_trap::OrbitTrap;
+use crate::colormap::Colormap;
+
+/// a map `z -> fold(z)^2 + c`
+pub trait QuadraticVariant: fmt::Debug + Clone + Send + Sync {
+    const NAME: &'static str;
+    /// the whole set lies in this region of the complex plane
+    const OVERVIEW: ((Real, Real), (Real, Real));
+    /// the smallest random zoom, which shows the whole set
+    const ZOOM: Real;
+
+    fn fold(z: Cplx) -> Cplx;
+    fn serializable(fractal: QuadraticFractal<Self>) -> EscapeTypes;
+}
+
+/// The Burning Ship, `z -> (|Re z| + i |Im z|)^2 + c`.
+#[derive(Debug, Clone)]
+pub struct BurningShip;
+
+impl QuadraticVariant for BurningShip {
+    const NAME: &'static str = "Burning Ship Fractal";
+    const OVERVIEW: ((Real, Real), (Real, Real)) = ((-2.2, 1.2), (-2., 0.8));
+    const ZOOM: Real = 0.4;
+
+    fn fold(z: Cplx) -> Cplx {
+        Cplx::new(z.re.abs(), z.im.abs())
+    }
+
+    fn serializable(fractal: QuadraticFractal<Self>) -> EscapeTypes {
+        EscapeTypes::BurningShip(fractal)
+    }
+}
+
+/// The Tricorn or Mandelbar set, `z -> conj(z)^2 + c`.
+#[derive(Debug, Clone)]
+pub struct Tricorn;
+
+impl QuadraticVariant for Tricorn {
+    const NAME: &'static str = "Tricorn Fractal";
+    const OVERVIEW: ((Real, Real), (Real, Real)) = ((-2.2, 1.2), (-1.6, 1.6));
+    const ZOOM: Real = 0.35;
+
+    fn fold(z: Cplx) -> Cplx {
+        z.conj()
+    }
+
+    fn serializable(fractal: QuadraticFractal<Self>) -> EscapeTypes {
+        EscapeTypes::Tricorn(fractal)
+    }
+}
+
+pub type BurningShipFractal = QuadraticFractal<BurningShip>;
+pub type TricornFractal = QuadraticFractal<Tricorn>;
+
+/// The set of the quadratic map of the variant `V`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuadraticFractal<V: QuadraticVariant> {
+    #[serde(default)]
+    pub seed: u64,
+    pub description: String,
+    max_count: u64,
+    shift: Cplx,
+    zoom: Real,
+    #[serde(flatten)]
+    coloring: CountColoring,
+    /// colors orbits by their closest approach, if given
+    #[serde(default)]
+    trap: Option<OrbitTrap>,
+    #[serde(skip)]
+    variant: PhantomData<V>,
+}
+
+impl FractalBuilder {
+    pub fn burning_ship(self) -> BurningShipFractal {
+        self.quadratic()
+    }
+
+    pub fn tricorn(self) -> TricornFractal {
+        self.quadratic()
+    }
+
+    fn quadratic<V: QuadraticVariant>(self) -> QuadraticFractal<V> {
+        let mut rng = self.seed_rng();
+        let (style, trap) = self.style_and_trap();
+
+        let mut fractal = QuadraticFractal {
+            seed: self.seed,
+            description: String::new(),
+            max_count: 1000,
+            shift: Cplx::new(0., 0.),
+            zoom: 1.,
+            coloring: CountColoring {
+                colormap: Colormap::random(&mut rng),
+                style,
+                normalization: self.normalization,
+            },
+            trap: None,
+            variant: PhantomData,
+        };
+
+        fractal.shift = match self.center {
+            Some(c) => Cplx::new(c.0 as Real, c.1 as Real),
+            None => near_boundary(&mut rng, V::OVERVIEW, fractal.max_count as f64, |c| fractal.iterate(c).count)
+        };
+        fractal.zoom = match self.zoom {
+            Some(z) => z as Real,
+            None => random_zoom(&mut rng, V::ZOOM, 10)
+        };
+
+        // the search for a region is faster without the trap
+        fractal.trap = trap;
+
+        fractal.description = describe(V::NAME, fractal.shift, fractal.zoom);
+        info!("Will render {}", fractal.description);
+
+        fractal
+    }
+}
+
+impl<V: QuadraticVariant> CountColored for QuadraticFractal<V> {
+    fn coloring(&self) -> &CountColoring {
+        &self.coloring
+    }
+
+    fn count_limit(&self) -> u64 {
+        self.max_count
+    }
+}
+
+impl<V: QuadraticVariant> EscapeTimeFractal for QuadraticFractal<V> {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn max_count(&self) -> u64 {
+        self.max_count
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn normalization(&self) -> Option<Normalization> {
+        self.coloring.normalization()
+    }
+
+    fn iterate(&self, mut state: Cplx) -> Convergence {
+        let mut ctr = 0;
+        let threshold = 4.;
+        state = state / self.zoom + self.shift;
+        let start = state;
+
+        let mut trap = self.trap.as_ref().map(OrbitTrap::tracker);
+
+        while {
+            state = V::fold(state);
+            state = state * state + start;
+            ctr += 1;
+            if let Some(t) = trap.as_mut() {
+                t.visit(state);
+            }
+
+            state.norm_sqr() < threshold && ctr < self.max_count && !state.re.is_nan() && !state.im.is_nan()
+        } {}
+        Convergence {count: ctr as f64, value: state, derivative: None, trap: trap.and_then(|t| t.hit())}
+    }
+
+    fn get_serializable(&self) -> EscapeTypes {
+        V::serializable(self.clone())
+    }
+}
@@ -0,0 +1,145 @@
+//! Random selection of interesting regions of the complex plane.
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::numbers::{Real, Cplx};
+
+/// number of random points tested by `near_boundary`
+const CANDIDATES: usize = 500;
+
+/// side length in points of the coarse renders of `zoom_into_boundary`
+const SEARCH_GRID: usize = 32;
+
+/// guess a random point close to the boundary of a set
+///
+/// The boundary is where the interesting structures are. Of random points
+/// in `bounds`, the one which needs the most iterations without reaching
+/// `max_count` is closest to the boundary.
+///
+/// # Arguments
+///
+/// * `bounds` - `((x_min, x_max), (y_min, y_max))` of the region to search
+/// * `max_count` - iteration count of points which belong to the set
+/// * `count` - the iteration count of a point
+pub fn near_boundary<R, F>(rng: &mut R, bounds: ((Real, Real), (Real, Real)), max_count: f64, count: F) -> Cplx
+    where R: Rng,
+          F: Fn(Cplx) -> f64
+{
+    let ((x_min, x_max), (y_min, y_max)) = bounds;
+    let mut best = Cplx::new((x_min + x_max) / 2., (y_min + y_max) / 2.);
+    let mut best_count = 0.;
+
+    for _ in 0..CANDIDATES {
+        let c = Cplx::new(rng.gen_range(x_min, x_max), rng.gen_range(y_min, y_max));
+        let n = count(c);
+        if n > best_count && n < max_count {
+            best = c;
+            best_count = n;
+        }
+    }
+
+    best
+}
+
+/// find an interesting view by repeatedly zooming into its busiest part
+///
+/// Every step renders the square window of side length `1 / zoom` around
+/// `center` coarsely and continues in one of its overlapping sub-windows of
+/// half the size, chosen randomly with a preference for the most boundary
+/// of the set and the most varying iteration counts. The search ends when
+/// `target_zoom` is reached or when every sub-window is flat.
+///
+/// # Arguments
+///
+/// * `max_count` - iteration count of points which belong to the set
+/// * `counts` - the iteration counts of a batch of points
+pub fn zoom_into_boundary<R, F>(rng: &mut R, center: Cplx, zoom: Real, target_zoom: Real, max_count: f64, counts: F) -> (Cplx, Real)
+    where R: Rng,
+          F: Fn(&[Cplx]) -> Vec<f64>
+{
+    let mut center = center;
+    let mut zoom = zoom;
+    let half = SEARCH_GRID / 2;
+    let stride = SEARCH_GRID / 4;
+    // position of a grid point relative to the center in units of the window
+    let relative = |i: usize| (i as Real + 0.5) / SEARCH_GRID as Real - 0.5;
+
+    while zoom < target_zoom {
+        let side = 1. / zoom;
+        let points: Vec<Cplx> = (0..SEARCH_GRID * SEARCH_GRID)
+            .map(|k| center + Cplx::new(relative(k % SEARCH_GRID), relative(k / SEARCH_GRID)) * side)
+            .collect();
+        let n = counts(&points);
+
+        let mut candidates = Vec::new();
+        for j in 0..3 {
+            for i in 0..3 {
+                let corner = (i * stride, j * stride);
+                candidates.push((corner, score(&n, corner, half, max_count)));
+            }
+        }
+
+        // all weights are zero, if the whole window is flat
+        let corner = match candidates.choose_weighted(rng, |c| c.1.powi(4)) {
+            Ok(c) => c.0,
+            Err(_) => break
+        };
+        let offset = Cplx::new(
+            (corner.0 + half / 2) as Real / SEARCH_GRID as Real - 0.5,
+            (corner.1 + half / 2) as Real / SEARCH_GRID as Real - 0.5,
+        );
+        center += offset * side;
+        zoom *= 2.;
+    }
+
+    (center, zoom)
+}
+
+/// how interesting the square of `size` points at `corner` of a coarse
+/// render is: the fraction of neighbors of which only one belongs to the
+/// set, plus the spread of the logarithmic counts of the escaping points
+fn score(counts: &[f64], corner: (usize, usize), size: usize, max_count: f64) -> f64 {
+    let at = |i: usize, j: usize| counts[(corner.1 + j) * SEARCH_GRID + corner.0 + i];
+    let inside = |i: usize, j: usize| at(i, j) >= max_count;
+
+    let mut boundary = 0;
+    for j in 0..size {
+        for i in 0..size {
+            if i + 1 < size && inside(i, j) != inside(i + 1, j) {
+                boundary += 1;
+            }
+            if j + 1 < size && inside(i, j) != inside(i, j + 1) {
+                boundary += 1;
+            }
+        }
+    }
+
+    let logs: Vec<f64> = (0..size * size)
+        .map(|k| at(k % size, k / size))
+        .filter(|&c| c < max_count)
+        .map(|c| c.max(1.).ln())
+        .collect();
+    let spread = if logs.len() > 1 {
+        let mean = logs.iter().sum::<f64>() / logs.len() as f64;
+        (logs.iter().map(|l| (l - mean) * (l - mean)).sum::<f64>() / logs.len() as f64).sqrt()
+    } else {
+        0.
+    };
+
+    boundary as f64 / (size * size) as f64 + spread
+}
+
+/// a random zoom between `base` and `base * 2^max_exponent`
+pub fn random_zoom<R: Rng>(rng: &mut R, base: Real, max_exponent: i32) -> Real {
+    base * (2 as Real).powi(rng.gen_range(0, max_exponent + 1))
+}
+
+/// description of a fractal of `name` looking at `shift` with `zoom`
+pub fn describe(name: &str, shift: Cplx, zoom: Real) -> String {
+    if zoom < 1e6 {
+        format!("{} at ~({:.3}), zoom {}x", name, shift, zoom)
+    } else {
+        format!("{} at ~({:.3}), zoom {:.1e}x", name, shift, zoom)
+    }
+}
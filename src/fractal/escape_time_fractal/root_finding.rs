@@ -0,0 +1,136 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+
+use serde::{self, Serialize, Deserialize};
+
+use crate::numbers::{Cplx, ComplexFunction};
+use crate::error::FractalError;
+
+/// the highest supported order of Householder's method, the symbolic
+/// derivatives grow quickly with the order
+const MAX_ORDER: u32 = 6;
+
+/// iterations which converge to the roots of a function `f`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RootFinding {
+    /// `z - f / f'`, converges quadratically
+    Newton,
+    /// `z - 2 f f' / (2 f'^2 - f f'')`, converges cubically
+    Halley,
+    /// `z - f f' / (f'^2 - f f'')`, converges quadratically also to multiple roots
+    Schroeder,
+    /// `z + d (1/f)^(d-1) / (1/f)^(d)`, converges with order `d + 1`
+    Householder(u32),
+}
+
+impl RootFinding {
+    pub fn random<T: Rng>(rng: &mut T) -> RootFinding {
+        let r = rng.gen::<f64>();
+        if r < 0.5 {
+            RootFinding::Newton
+        } else if r < 0.7 {
+            RootFinding::Halley
+        } else if r < 0.85 {
+            RootFinding::Schroeder
+        } else {
+            RootFinding::Householder(rng.gen_range(3, 5))
+        }
+    }
+
+    /// whether the order of Householder's method is supported, fractals
+    /// loaded from json are not checked by `from_str`
+    pub fn validate(&self) -> Result<(), FractalError> {
+        match *self {
+            RootFinding::Householder(d) if !(1..=MAX_ORDER).contains(&d) => Err(FractalError::InvalidArgument(
+                format!("the order of Householder's method needs to be 1 <= d <= {}, but is {}", MAX_ORDER, d)
+            )),
+            _ => Ok(())
+        }
+    }
+
+    /// the name used in descriptions
+    pub fn name(&self) -> String {
+        match *self {
+            RootFinding::Newton => "Newton".to_string(),
+            RootFinding::Halley => "Halley".to_string(),
+            RootFinding::Schroeder => "Schröder".to_string(),
+            RootFinding::Householder(d) => format!("Householder (order {})", d),
+        }
+    }
+
+    /// the derivatives needed by `step`, which are expensive to calculate
+    pub fn derivatives(&self, f: &ComplexFunction) -> Vec<ComplexFunction> {
+        match *self {
+            RootFinding::Newton => vec![f.differentiate()],
+            RootFinding::Halley | RootFinding::Schroeder => {
+                let df = f.differentiate();
+                let ddf = df.differentiate();
+                vec![df, ddf]
+            }
+            RootFinding::Householder(d) => {
+                let mut g = f.reciprocal();
+                for _ in 1..d {
+                    g = g.differentiate();
+                }
+                let dg = g.differentiate();
+                vec![g, dg]
+            }
+        }
+    }
+
+    /// the step from `z` towards a root of `f`, given the `derivatives`
+    pub fn step(&self, f: &ComplexFunction, derivatives: &[ComplexFunction], z: Cplx) -> Cplx {
+        match *self {
+            RootFinding::Newton => -f.eval(z) / derivatives[0].eval(z),
+            RootFinding::Halley => {
+                let (f, df, ddf) = (f.eval(z), derivatives[0].eval(z), derivatives[1].eval(z));
+                -f * df * 2. / (df * df * 2. - f * ddf)
+            }
+            RootFinding::Schroeder => {
+                let (f, df, ddf) = (f.eval(z), derivatives[0].eval(z), derivatives[1].eval(z));
+                -f * df / (df * df - f * ddf)
+            }
+            RootFinding::Householder(d) => derivatives[0].eval(z) * d as f64 / derivatives[1].eval(z),
+        }
+    }
+}
+
+impl FromStr for RootFinding {
+    type Err = FractalError;
+
+    /// parse `newton`, `halley`, `schroeder` or `householder:d`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FractalError::InvalidArgument(
+            format!("root finding needs to be `newton`, `halley`, `schroeder` or `householder:d` with 1 <= d <= {}, but is '{}'", MAX_ORDER, s)
+        );
+
+        let mut parts = s.splitn(2, ':');
+        match (parts.next().unwrap_or(""), parts.next()) {
+            ("newton", None) => Ok(RootFinding::Newton),
+            ("halley", None) => Ok(RootFinding::Halley),
+            ("schroeder", None) | ("schröder", None) => Ok(RootFinding::Schroeder),
+            ("householder", Some(d)) => match d.trim().parse::<u32>() {
+                Ok(d) => {
+                    let method = RootFinding::Householder(d);
+                    method.validate().map_err(|_| invalid())?;
+                    Ok(method)
+                },
+                _ => Err(invalid())
+            },
+            _ => Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for RootFinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RootFinding::Newton => write!(f, "newton"),
+            RootFinding::Halley => write!(f, "halley"),
+            RootFinding::Schroeder => write!(f, "schroeder"),
+            RootFinding::Householder(d) => write!(f, "householder:{}", d),
+        }
+    }
+}
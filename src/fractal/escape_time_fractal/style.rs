@@ -1,38 +1,292 @@
+//! Styles color the points of escape time fractals by their `Convergence`.
+//!
+//! A style is plain data: every channel measures a quantity of the
+//! convergence and maps it by a list of operations onto `[0, 1]`. Either
+//! three channels give hue, saturation and value, or a single one is the
+//! index into a colormap. Styles are stored with their full definition,
+//! such that users can define their own in JSON, e.g.,
+//!
+//! ```json
+//! {"name": "bands", "coloring": {"colormap": {
+//!     "index": {"quantity": "count", "ops": [{"div": 50}, "fract"]},
+//!     "colormap": {"map": [[0, [0, 0, 0]], [1, [1, 1, 1]]], "name": "bw"}
+//! }}}
+//! ```
+
 use rand::Rng;
 use std::fmt;
+use std::str::FromStr;
 
 use crate::color;
+use crate::colormap::Colormap;
+use crate::error::FractalError;
 use super::Convergence;
+use super::normalization::Normalization;
 
 use serde::ser::Serializer;
-use serde::{Deserialize, Deserializer};
+use serde::de::Error;
+use serde::{Serialize, Deserialize, Deserializer};
 
 use crate::fractal::RngType;
 
+use self::Op::*;
+use self::Quantity::{Count, Norm, Distance, TrapDistance, TrapAngle};
+
 pub fn style_serialize<S>(x: &Style, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    serializer.serialize_str(&x.name())
+    x.definition().serialize(serializer)
 }
 
+/// either the name of a built-in style or a full definition
 pub fn style_deserialize<'de, D>(deserializer: D) -> Result<Style, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
-    Ok(Style::from_string(&s).unwrap_or_else(Style::vibrant))
+    match StyleRepr::deserialize(deserializer)? {
+        StyleRepr::Name(name) => Style::from_string(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown style '{}'", name))),
+        StyleRepr::Definition(definition) => Ok(Style::from(definition)),
+    }
+}
+
+impl Serialize for Style {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        style_serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Style {
+    fn deserialize<D>(deserializer: D) -> Result<Style, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        style_deserialize(deserializer)
+    }
+}
+
+/// older versions stored only the names of styles
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StyleRepr {
+    Name(String),
+    Definition(StyleDefinition),
+}
+
+/// the serialized form of a `Style`
+#[derive(Serialize, Deserialize)]
+struct StyleDefinition {
+    name: String,
+    coloring: Coloring,
+}
+
+impl From<StyleDefinition> for Style {
+    fn from(definition: StyleDefinition) -> Style {
+        Style { readable: definition.name, coloring: definition.coloring }
+    }
 }
 
 pub trait Stylable {
     fn style(&self, _: &Convergence) -> color::HSV;
     fn style_name(&self) -> &str;
+
+    /// color for the normalized iteration count `x` in `[0, 1]`, see `Normalization`
+    fn style_normalized(&self, conv: &Convergence, _x: f64) -> color::HSV {
+        self.style(conv)
+    }
+}
+
+/// a quantity of the convergence of a point
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quantity {
+    /// the iteration count
+    Count,
+    /// the norm of the final value
+    Norm,
+    /// the argument of the final value in `[0, 1)`
+    Argument,
+    /// the estimated distance to the boundary in pixels, unknown inside of the set
+    Distance,
+    /// the closest approach to the orbit trap
+    TrapDistance,
+    /// the direction of the closest approach to the orbit trap in `[0, 1)`
+    TrapAngle,
+    Constant(f64),
+}
+
+impl Quantity {
+    fn measure(&self, conv: &Convergence) -> Option<f64> {
+        match *self {
+            Quantity::Count => Some(conv.count),
+            Quantity::Norm => Some(conv.value.norm()),
+            Quantity::Argument => Some((conv.value.arg() / (2. * std::f64::consts::PI) + 0.5).fract()),
+            Quantity::Distance => conv.distance(),
+            Quantity::TrapDistance => conv.trap.map(|hit| hit.distance),
+            Quantity::TrapAngle => conv.trap.map(|hit| hit.angle),
+            Quantity::Constant(x) => Some(x),
+        }
+    }
+}
+
+/// a number, which may depend on the random parameters `random_color` and
+/// `random_count` of the fractal, both are `1` if the fractal has none
+///
+/// Serialized as a plain number, if it does not depend on them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ParamRepr", into = "ParamRepr")]
+pub struct Param {
+    pub base: f64,
+    pub random_color: f64,
+    pub random_count: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ParamRepr {
+    Fixed(f64),
+    Random {
+        #[serde(default)]
+        base: f64,
+        #[serde(default)]
+        random_color: f64,
+        #[serde(default)]
+        random_count: f64,
+    },
+}
+
+impl From<ParamRepr> for Param {
+    fn from(repr: ParamRepr) -> Param {
+        match repr {
+            ParamRepr::Fixed(base) => Param::from(base),
+            ParamRepr::Random { base, random_color, random_count } => Param { base, random_color, random_count },
+        }
+    }
+}
+
+impl From<Param> for ParamRepr {
+    fn from(p: Param) -> ParamRepr {
+        if p.random_color == 0. && p.random_count == 0. {
+            ParamRepr::Fixed(p.base)
+        } else {
+            ParamRepr::Random { base: p.base, random_color: p.random_color, random_count: p.random_count }
+        }
+    }
+}
+
+impl From<f64> for Param {
+    fn from(base: f64) -> Param {
+        Param { base, random_color: 0., random_count: 0. }
+    }
+}
+
+impl Param {
+    fn color(base: f64, factor: f64) -> Param {
+        Param { base, random_color: factor, random_count: 0. }
+    }
+
+    fn count(base: f64, factor: f64) -> Param {
+        Param { base, random_color: 0., random_count: factor }
+    }
+
+    fn value(&self, random: (f64, f64)) -> f64 {
+        self.base + self.random_color * random.0 + self.random_count * random.1
+    }
+}
+
+/// a step of the transfer function of a channel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Scale(Param),
+    Div(Param),
+    Add(Param),
+    Min(f64),
+    Max(f64),
+    Pow(f64),
+    Ln,
+    Exp,
+    Tanh,
+    Neg,
+    /// `1 - x`
+    OneMinus,
+    /// the fractional part, for cyclic hues
+    Fract,
+}
+
+impl Op {
+    fn apply(&self, x: f64, random: (f64, f64)) -> f64 {
+        match *self {
+            Op::Scale(p) => x * p.value(random),
+            Op::Div(p) => x / p.value(random),
+            Op::Add(p) => x + p.value(random),
+            Op::Min(y) => x.min(y),
+            Op::Max(y) => x.max(y),
+            Op::Pow(y) => x.powf(y),
+            Op::Ln => x.ln(),
+            Op::Exp => x.exp(),
+            Op::Tanh => x.tanh(),
+            Op::Neg => -x,
+            Op::OneMinus => 1. - x,
+            Op::Fract => x.fract(),
+        }
+    }
 }
 
-#[derive(Clone)]
+/// a quantity mapped by a sequence of operations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Channel {
+    pub quantity: Quantity,
+    #[serde(default)]
+    pub ops: Vec<Op>,
+}
+
+impl Channel {
+    pub fn new(quantity: Quantity, ops: Vec<Op>) -> Channel {
+        Channel { quantity, ops }
+    }
+
+    pub fn constant(x: f64) -> Channel {
+        Channel::new(Quantity::Constant(x), Vec::new())
+    }
+
+    /// `None` if the quantity is unknown for this point, `NaN` becomes `0`
+    fn apply(&self, conv: &Convergence, random: (f64, f64)) -> Option<f64> {
+        let x = self.ops.iter().fold(self.quantity.measure(conv)?, |x, op| op.apply(x, random));
+        Some(if x.is_nan() { 0. } else { x })
+    }
+
+    fn needs_trap(&self) -> bool {
+        matches!(self.quantity, Quantity::TrapDistance | Quantity::TrapAngle)
+    }
+}
+
+/// how the channels turn into a color
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Coloring {
+    Hsv { hue: Channel, saturation: Channel, value: Channel },
+    Colormap { index: Channel, colormap: Colormap },
+}
+
+impl Coloring {
+    fn channels(&self) -> Vec<&Channel> {
+        match self {
+            Coloring::Hsv { hue, saturation, value } => vec![hue, saturation, value],
+            Coloring::Colormap { index, .. } => vec![index],
+        }
+    }
+}
+
+/// points for which a quantity is unknown are black
+#[derive(Clone, PartialEq)]
 pub struct Style {
-    pub callable: fn(&Convergence, Option<f64>, Option<f64>) -> color::HSV,
     pub readable: String,
+    pub coloring: Coloring,
 }
 
 impl fmt::Debug for Style {
@@ -41,6 +295,27 @@ impl fmt::Debug for Style {
     }
 }
 
+impl FromStr for Style {
+    type Err = FractalError;
+
+    /// the name of a built-in style or a definition in JSON
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(style) = Style::from_string(s) {
+            return Ok(style)
+        }
+        serde_json::from_str::<StyleDefinition>(s)
+            .map(Style::from)
+            .map_err(|e| FractalError::InvalidArgument(
+                format!("style needs to be the name of a built-in style or a definition in JSON, but is '{}': {}", s, e)
+            ))
+    }
+}
+
+/// hue cycling with the norm of the final value
+fn norm_hue(factor: Param) -> Channel {
+    Channel::new(Norm, vec![Scale(10f64.into()), Scale(factor), Fract])
+}
+
 impl Style {
     pub fn from_string(s: &str) -> Option<Style> {
         match s {
@@ -48,26 +323,111 @@ impl Style {
             "spooky" => Some(Style::spooky()),
             "strong" => Some(Style::strong()),
             "pastell" => Some(Style::pastell()),
+            "distance" => Some(Style::distance()),
+            "trap" => Some(Style::trap()),
+            "trap_angle" => Some(Style::trap_angle()),
             _ => None
         }
     }
+
+    pub fn new(name: &str, coloring: Coloring) -> Style {
+        Style { readable: name.to_string(), coloring }
+    }
+
+    fn hsv(name: &str, hue: Channel, saturation: Channel, value: Channel) -> Style {
+        Style::new(name, Coloring::Hsv { hue, saturation, value })
+    }
+
     pub fn vibrant() -> Style {
-        Style { callable: style_vibrant, readable: "vibrant".to_string() }
+        Style::hsv("vibrant",
+            norm_hue(Param::color(0.1, 1.)),
+            Channel::new(Count, vec![Div(Param::count(10., 40.)), Min(1.), OneMinus]),
+            Channel::constant(1.),
+        )
     }
     pub fn spooky() -> Style {
-        Style { callable: style_spooky, readable: "spooky".to_string() }
+        Style::hsv("spooky",
+            norm_hue(Param::color(0., 1.)),
+            Channel::constant(1.),
+            Channel::new(Count, vec![Div(Param::count(10., 50.)), Min(1.)]),
+        )
     }
     pub fn strong() -> Style {
-        Style { callable: style_strong, readable: "strong".to_string() }
+        Style::hsv("strong",
+            norm_hue(Param::color(0., 1.)),
+            Channel::constant(1.),
+            Channel::new(Count, vec![Div(Param::count(10., 100.)), Pow(0.7), Min(1.)]),
+        )
     }
     pub fn pastell() -> Style {
-        Style { callable: style_pastell, readable: "pastell".to_string() }
+        Style::hsv("pastell",
+            norm_hue(Param::color(0., 1.)),
+            Channel::new(Count, vec![Div(Param::count(10., 40.)), Min(1.)]),
+            Channel::constant(1.),
+        )
+    }
+    /// dark boundaries, which glow white into colored bands of equal distance
+    ///
+    /// Needs the distance estimate, which only Mandelbrot, Multibrot and
+    /// polynomial Julia fractals provide, thus it is never chosen randomly.
+    pub fn distance() -> Style {
+        Style::hsv("distance",
+            Channel::new(Distance, vec![Max(1.), Ln, Div(10f64.into()), Add(Param::color(0., 1.)), Fract]),
+            Channel::new(Distance, vec![Div(Param::count(2., 8.)), Neg, Exp, OneMinus]),
+            Channel::new(Distance, vec![Scale(2f64.into()), Tanh]),
+        )
+    }
+    /// orbits which come close to the trap glow white, farther ones fade
+    /// through colored bands into black
+    ///
+    /// Needs an orbit trap, which the builder adds to fractals with this style.
+    pub fn trap() -> Style {
+        Style::hsv("trap",
+            Channel::new(TrapDistance, vec![Scale(20f64.into()), Add(1f64.into()), Ln, Div(5f64.into()), Add(Param::color(0., 1.)), Fract]),
+            Channel::new(TrapDistance, vec![Scale(Param::count(5., 20.)), Neg, Exp, OneMinus]),
+            Channel::new(TrapDistance, vec![Scale(2f64.into()), Neg, Exp]),
+        )
+    }
+    /// hue by the direction from which orbits approach the trap, brightness
+    /// by their distance
+    pub fn trap_angle() -> Style {
+        Style::hsv("trap_angle",
+            Channel::new(TrapAngle, vec![Add(Param::color(0., 1.)), Fract]),
+            Channel::constant(0.8),
+            Channel::new(TrapDistance, vec![Scale(Param::count(1., 4.)), Neg, Exp]),
+        )
+    }
+
+    pub fn needs_trap(&self) -> bool {
+        self.coloring.channels().iter().any(|c| c.needs_trap())
     }
 
     pub fn name(&self) -> String {
         self.readable.to_owned()
     }
 
+    fn definition(&self) -> StyleDefinition {
+        StyleDefinition { name: self.readable.clone(), coloring: self.coloring.clone() }
+    }
+
+    /// the color of a point, the random parameters default to `1`
+    pub fn color(&self, conv: &Convergence, random_color: Option<f64>, random_count: Option<f64>) -> color::HSV {
+        let random = (random_color.unwrap_or(1.), random_count.unwrap_or(1.));
+        let black = color::HSV(0., 0., 0.);
+        match self.coloring {
+            Coloring::Hsv { ref hue, ref saturation, ref value } => {
+                match (hue.apply(conv, random), saturation.apply(conv, random), value.apply(conv, random)) {
+                    (Some(h), Some(s), Some(v)) => color::HSV(h, s, v),
+                    _ => black
+                }
+            }
+            Coloring::Colormap { ref index, ref colormap } => match index.apply(conv, random) {
+                Some(x) => colormap.value(&x).to_hsv(),
+                None => black
+            },
+        }
+    }
+
     pub fn num() -> usize {
         4
     }
@@ -91,55 +451,71 @@ impl fmt::Display for Style {
     }
 }
 
+/// color escaping points by the logarithm of their iteration count, points
+/// which do not escape within `max_count` iterations are black
+///
+/// The logarithm keeps the structure visible, even if most points escape fast.
+pub fn log_count(info: &Convergence, max_count: u64, colormap: &Colormap) -> color::HSV {
+    if info.count >= max_count as f64 {
+        return color::HSV(0., 0., 0.)
+    }
 
-fn style_pastell(info: &Convergence, random_color: Option<f64>, random_count: Option<f64>) -> color::HSV {
-    let random_color = random_color.unwrap_or(1.);
-    let random_count = random_count.unwrap_or(1.);
-
-    let hue = (info.value.norm() as f64 * 10. * random_color).fract();
-    let hue = if hue.is_nan() {0.} else {hue};
-    let value = 1f64;
-    let tmp = info.count as f64 / (10. + 40. * random_count);
-    let saturation = 1f64.min(tmp);
-
-    color::HSV(hue, saturation, value)
+    let x = info.count.max(1.).ln() / (max_count as f64).ln();
+    colormap.value(&x).to_hsv()
 }
 
-fn style_vibrant(info: &Convergence, random_color: Option<f64>, random_count: Option<f64>) -> color::HSV {
-    let random_color = random_color.unwrap_or(1.);
-    let random_count = random_count.unwrap_or(1.);
-
-    let hue = (info.value.norm() as f64 * 10. * (random_color + 0.1)).fract();
-    let hue = if hue.is_nan() {0.} else {hue};
-    let value = 1f64;
-    let tmp = info.count as f64 / (10. + 40. * random_count);
-    let saturation = 1. - 1f64.min(tmp);
-
-    color::HSV(hue, saturation, value)
+/// The coloring of fractals, which color escaping points by the logarithm
+/// of their iteration count, unless a style or a normalization replaces it.
+///
+/// Fractals embed it flattened, such that its fields appear as their own
+/// in JSON.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CountColoring {
+    pub colormap: Colormap,
+    /// replaces the colormap, if given
+    #[serde(default)]
+    pub style: Option<Style>,
+    /// replaces the logarithmic coloring, if given
+    #[serde(default)]
+    pub normalization: Option<Normalization>,
 }
 
-fn style_strong(info: &Convergence, random_color: Option<f64>, random_count: Option<f64>) -> color::HSV {
-    let random_color = random_color.unwrap_or(1.);
-    let random_count = random_count.unwrap_or(1.);
+impl CountColoring {
+    /// the normalization of the iteration counts, styles color by themselves
+    pub fn normalization(&self) -> Option<Normalization> {
+        match self.style {
+            Some(_) => None,
+            None => self.normalization
+        }
+    }
+}
 
-    let hue = (info.value.norm() as f64 * 10. * random_color).fract();
-    let hue = if hue.is_nan() {0.} else {hue};
-    let saturation = 1f64;
-    let tmp = info.count as f64 / (10. + 100. * random_count);
-    let value = 1f64.min(tmp.powf(0.7));
+/// Fractals, which are colored by a `CountColoring`, are `Stylable` through it.
+pub trait CountColored {
+    fn coloring(&self) -> &CountColoring;
 
-    color::HSV(hue, saturation, value)
+    /// points which reach this iteration count belong to the set
+    fn count_limit(&self) -> u64;
 }
 
-fn style_spooky(info: &Convergence, random_color: Option<f64>, random_count: Option<f64>) -> color::HSV {
-    let random_color = random_color.unwrap_or(1.);
-    let random_count = random_count.unwrap_or(1.);
+impl<T: CountColored> Stylable for T {
+    fn style(&self, conv: &Convergence) -> color::HSV {
+        let coloring = self.coloring();
+        match coloring.style {
+            Some(ref style) => style.color(conv, None, None),
+            None => log_count(conv, self.count_limit(), &coloring.colormap)
+        }
+    }
 
-    let hue = (info.value.norm() as f64 * 10. * random_color).fract();
-    let hue = if hue.is_nan() {0.} else {hue};
-    let saturation = 1f64;
-    let tmp = info.count as f64 / (10. + 50. * random_count);
-    let value = 1f64.min(tmp);
+    fn style_name(&self) -> &str {
+        let coloring = self.coloring();
+        match coloring.style {
+            Some(ref style) => &style.readable,
+            None => coloring.colormap.name()
+        }
+    }
 
-    color::HSV(hue, saturation, value)
+    fn style_normalized(&self, _conv: &Convergence, x: f64) -> color::HSV {
+        self.coloring().colormap.value(&x).to_hsv()
+    }
 }
@@ -0,0 +1,187 @@
+mod sampler;
+
+use log::info;
+
+use serde::{self, Serialize, Deserialize};
+use serde::Deserializer;
+use serde::de::Error;
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+
+use super::{IteratedFunctionSystem, SuggestedIterations};
+use sampler::BuddhabrotSampler;
+use crate::fractal::FractalBuilder;
+use crate::histogram::{BoundsTypes, ColoredHistogram};
+use crate::color::RGBA;
+
+use super::Samplable;
+
+use super::{IterationFractalType, RngType, default_partitions};
+use super::DEFAULT_PARTITIONS;
+
+fn default_gamma() -> f64 {
+    4.0
+}
+
+fn default_vibrancy() -> f64 {
+    0.5
+}
+
+fn default_bounds() -> BoundsTypes {
+    BoundsTypes::StrictBounds
+}
+
+/// orbits which escape in the first step leave no points, such that
+/// lower limits would never produce a sample
+const MIN_LIMIT: u64 = 2;
+
+fn limits_deserialize<'de, D>(deserializer: D) -> Result<[u64; 3], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let limits = <[u64; 3]>::deserialize(deserializer)?;
+    if limits.iter().any(|&l| l < MIN_LIMIT) {
+        return Err(D::Error::custom(format!("limits need to be at least {}, but are {:?}", MIN_LIMIT, limits)))
+    }
+    Ok(limits)
+}
+
+/// The Buddhabrot, the density of all orbits of `z -> z^2 + c`, which start
+/// at `z = 0` and escape.
+///
+/// Every color channel counts the orbits, which escape within its own
+/// iteration limit. Distinct limits result in the Nebulabrot. The
+/// anti-Buddhabrot counts the orbits, which do not escape, instead.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Buddhabrot {
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default = "default_partitions")]
+    pub partitions: usize,
+    pub description: String,
+    /// iteration limits of the red, green and blue channel
+    #[serde(deserialize_with = "limits_deserialize")]
+    pub limits: [u64; 3],
+    /// count the orbits, which do not escape within the limit
+    #[serde(default)]
+    pub anti: bool,
+    #[serde(default = "default_bounds")]
+    pub bounds: BoundsTypes,
+    #[serde(default = "default_gamma")]
+    pub gamma: f64,
+    #[serde(default = "default_vibrancy")]
+    pub vibrancy: f64
+}
+
+impl Buddhabrot {
+    fn name(limits: &[u64; 3], anti: bool) -> String {
+        let name = match (limits[0] == limits[1] && limits[1] == limits[2], anti) {
+            (true, false) => "Buddhabrot",
+            (false, false) => "Nebulabrot",
+            (true, true) => "Anti-Buddhabrot",
+            (false, true) => "Anti-Nebulabrot",
+        };
+
+        if limits[0] == limits[1] && limits[1] == limits[2] {
+            format!("{} with {} iterations", name, limits[0])
+        } else {
+            format!("{} with {}, {} and {} iterations", name, limits[0], limits[1], limits[2])
+        }
+    }
+}
+
+impl FractalBuilder
+{
+    pub fn buddhabrot(self) -> Buddhabrot {
+        let mut rng = self.seed_rng();
+
+        let anti = self.anti.unwrap_or_else(|| rng.gen::<f64>() < 0.2);
+
+        // orbits which do not escape are expensive, such that the
+        // anti-Buddhabrot uses lower limits
+        let limits = match self.limits {
+            Some(l) => l,
+            None if rng.gen::<f64>() < 0.5 => {
+                let n = if anti { rng.gen_range(20, 200) } else { rng.gen_range(200, 3000) };
+                [n, n, n]
+            }
+            None => {
+                let n = rng.gen_range(20, 100);
+                let mut limits = if anti { [n, 2 * n, 5 * n] } else { [n, 10 * n, 50 * n] };
+                limits.shuffle(&mut rng);
+                limits
+            }
+        };
+
+        let gamma = self.gamma.unwrap_or(4.);
+        let vibrancy = self.vibrancy.unwrap_or_else(|| rng.gen());
+        let bounds = self.bounds.unwrap_or(BoundsTypes::StrictBounds);
+
+        let description = Buddhabrot::name(&limits, anti);
+
+        info!("Will render {}", description);
+
+        Buddhabrot {
+            seed: self.seed,
+            partitions: DEFAULT_PARTITIONS,
+            description,
+            limits,
+            anti,
+            bounds,
+            gamma,
+            vibrancy,
+        }
+    }
+}
+
+impl IteratedFunctionSystem for Buddhabrot
+{
+    fn needs_strict_bounds(&self) -> BoundsTypes {
+        self.bounds
+    }
+
+    fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    fn vibrancy(&self) -> f64 {
+        self.vibrancy
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn suggested_iterations_draft(&self) -> SuggestedIterations {
+        SuggestedIterations::PerPixel(10)
+    }
+
+    /// every orbit visits the whole disk of the escape radius, which a
+    /// logarithmic brightness would light up as haze
+    fn normalize(&self, hist: &ColoredHistogram) -> Vec<RGBA> {
+        hist.normalize_power(0.5)
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn partitions(&self) -> usize {
+        self.partitions
+    }
+
+    fn get_sampler(&self, seed: u64) -> Box<dyn Samplable + Send> {
+        Box::new(BuddhabrotSampler {
+            rng: RngType::seed_from_u64(seed),
+            limits: self.limits,
+            anti: self.anti,
+            path: Vec::new(),
+            orbit: Vec::new(),
+            exhausted: false,
+        })
+    }
+
+    fn get_serializable(&self) -> IterationFractalType {
+        IterationFractalType::Buddhabrot(self.clone())
+    }
+}
@@ -0,0 +1,104 @@
+use crate::color::RGB;
+use crate::numbers::{Real, Cplx};
+use rand::Rng;
+use super::super::{Perturbable, Samplable};
+use crate::fractal::escape_time_fractal::kernel::in_main_bulbs;
+
+/// squared radius beyond which orbits escape
+const THRESHOLD: Real = 4.;
+
+/// number of orbits which are traced for a single sample, before giving up
+const MAX_TRACES: usize = 10_000;
+
+pub struct BuddhabrotSampler<T>
+    where T: Rng
+{
+    pub rng: T,
+    pub limits: [u64; 3],
+    pub anti: bool,
+    /// the orbit of the current starting point
+    pub path: Vec<Cplx>,
+    /// points of accepted orbits, which are not returned yet
+    pub orbit: Vec<([Real; 2], RGB)>,
+    /// whether `MAX_TRACES` orbits in a row were rejected, the limits
+    /// probably never accept one
+    pub exhausted: bool,
+}
+
+impl<T> BuddhabrotSampler<T>
+    where T: Rng
+{
+    /// follow the orbit of a random starting point and keep it, if it
+    /// counts for at least one of the channels
+    fn trace(&mut self) {
+        let c = Cplx::new(self.rng.gen_range(-2., 1.), self.rng.gen_range(-1.5, 1.5));
+        if !self.anti && in_main_bulbs(c) {
+            return
+        }
+
+        let max = *self.limits.iter().max().unwrap_or(&0);
+        let mut z = Cplx::new(0., 0.);
+        let mut escaped = None;
+        self.path.clear();
+        for n in 1..=max {
+            z = z * z + c;
+            if z.norm_sqr() > THRESHOLD {
+                escaped = Some(n);
+                break
+            }
+            self.path.push(z);
+        }
+
+        // a channel counts the orbit, if it escapes within its limit,
+        // or for the anti-Buddhabrot, if it does not
+        let anti = self.anti;
+        let counts = |limit: u64| match escaped {
+            Some(n) => (n <= limit) != anti,
+            None => anti,
+        };
+        let channels: Vec<bool> = self.limits.iter().map(|&l| counts(l)).collect();
+        if !channels.iter().any(|&x| x) {
+            return
+        }
+
+        // rotated, such that the Buddha sits upright
+        let limits = self.limits;
+        for (i, z) in self.path.iter().enumerate().rev() {
+            let channel = |k: usize| if channels[k] && (i as u64) < limits[k] { 1. } else { 0. };
+            let color = RGB(channel(0), channel(1), channel(2));
+            if color != RGB(0., 0., 0.) {
+                self.orbit.push(([z.im, z.re], color));
+            }
+        }
+    }
+}
+
+impl<T> Iterator for BuddhabrotSampler<T>
+    where T: Rng
+{
+    type Item = ([Real; 2], RGB);
+
+    fn next(&mut self) -> Option<([Real; 2], RGB)> {
+        let mut traces = 0;
+        while self.orbit.is_empty() && !self.exhausted {
+            self.trace();
+            traces += 1;
+            self.exhausted = traces >= MAX_TRACES;
+        }
+
+        // unsuitable limits might never accept an orbit, then the
+        // histogram discards this sample, instead of waiting forever
+        Some(self.orbit.pop().unwrap_or(([Real::NAN, Real::NAN], RGB(0., 0., 0.))))
+    }
+}
+
+impl<T> Perturbable for BuddhabrotSampler<T>
+    where T: Rng
+{
+    fn perturb(&mut self) {
+        // the starting points are random anyway
+    }
+}
+
+impl<T> Samplable for BuddhabrotSampler<T>
+    where T: Rng {}
@@ -6,7 +6,7 @@ use rand::distributions::Standard;
 use std::iter;
 
 use crate::color::{HSV, RGB};
-use super::{Transformation, AffineTransformation, NonlinearTransformation, FractalFlame, Symmetry};
+use super::{Transformation, AffineTransformation, NonlinearTransformation, FractalFlame, Symmetry, DEFAULT_PARTITIONS};
 use crate::fractal::FractalBuilder;
 
 use crate::numbers::Real;
@@ -123,7 +123,8 @@ impl FractalBuilder
 
 
         FractalFlame {
-            rng,
+            seed: self.seed,
+            partitions: DEFAULT_PARTITIONS,
             description,
             probabilities,
             colors,
@@ -1,7 +1,7 @@
 use log::{debug, info};
 
 use crate::{color::RGB, histogram::BoundsTypes};
-use super::{Transformation, NonlinearTransformation, FractalFlame, AffineTransformation};
+use super::{Transformation, NonlinearTransformation, FractalFlame, AffineTransformation, DEFAULT_PARTITIONS};
 use crate::fractal::FractalBuilder;
 
 use rand::Rng;
@@ -71,7 +71,8 @@ impl FractalBuilder
         debug!("Variation              : {:?}", variation);
 
         FractalFlame {
-            rng,
+            seed: self.seed,
+            partitions: DEFAULT_PARTITIONS,
             description,
             probabilities,
             colors,
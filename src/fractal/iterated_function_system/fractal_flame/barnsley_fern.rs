@@ -1,14 +1,12 @@
 use log::{debug, info};
 
 use crate::{color::RGB, histogram::BoundsTypes};
-use super::{Transformation, NonlinearTransformation, FractalFlame};
+use super::{Transformation, NonlinearTransformation, FractalFlame, DEFAULT_PARTITIONS};
 use crate::fractal::FractalBuilder;
 
 impl FractalBuilder
 {
     pub fn barnsley_fern(self) -> FractalFlame {
-        let rng = self.seed_rng();
-
         let number_of_functions = 4;
         let probabilities = vec![0.01, 0.86, 0.93, 1.];
 
@@ -57,7 +55,8 @@ impl FractalBuilder
         debug!("Variation              : {:?}", variation);
 
         FractalFlame {
-            rng,
+            seed: self.seed,
+            partitions: DEFAULT_PARTITIONS,
             description,
             probabilities,
             colors,
@@ -5,7 +5,7 @@ use rand::distributions::Standard;
 use std::iter;
 
 use crate::{color::{HSV, RGB}, histogram::BoundsTypes};
-use super::{Transformation, MobiusTransformation, AffineTransformation, NonlinearTransformation, FractalFlame, Symmetry};
+use super::{Transformation, MobiusTransformation, AffineTransformation, NonlinearTransformation, FractalFlame, Symmetry, DEFAULT_PARTITIONS};
 use crate::fractal::FractalBuilder;
 
 use crate::numbers::Real;
@@ -121,7 +121,8 @@ impl FractalBuilder
         debug!("Symmetry               : {:?}", symmetry);
 
         FractalFlame {
-            rng,
+            seed: self.seed,
+            partitions: DEFAULT_PARTITIONS,
             description,
             probabilities,
             colors,
@@ -8,7 +8,7 @@ mod mobius_flame;
 mod affine_flame;
 
 use serde::{self, Serialize, Deserialize};
-use rand::{Rng, SeedableRng};
+use rand::SeedableRng;
 
 use super::IteratedFunctionSystem;
 use super::IteratedFunctionSystemSampler;
@@ -17,7 +17,8 @@ use super::{Transformation,NonlinearTransformation,AffineTransformation,MobiusTr
 use crate::color::RGB;
 
 use super::{Samplable};
-use super::{IterationFractalType, RngType, default_rng};
+use super::{IterationFractalType, RngType, default_partitions};
+use super::DEFAULT_PARTITIONS;
 
 
 fn default_gamma() -> f64 {
@@ -47,9 +48,10 @@ fn default_bounds() -> BoundsTypes {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FractalFlame
 {
-    #[serde(skip)]
-    #[serde(default = "default_rng")]
-    rng: RngType,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default = "default_partitions")]
+    pub partitions: usize,
     pub description: String,
     pub probabilities: Vec<f64>,
     pub colors: Vec<Option<RGB>>,
@@ -87,13 +89,16 @@ impl IteratedFunctionSystem for FractalFlame
         &self.description
     }
 
-    fn get_rng(&mut self) -> &mut RngType
-    {
-        &mut self.rng
+    fn seed(&self) -> u64 {
+        self.seed
     }
 
-    fn get_sampler(&mut self) -> Box<dyn Samplable + Send> {
-        let rng = RngType::seed_from_u64(self.rng.gen::<u64>());
+    fn partitions(&self) -> usize {
+        self.partitions
+    }
+
+    fn get_sampler(&self, seed: u64) -> Box<dyn Samplable + Send> {
+        let rng = RngType::seed_from_u64(seed);
 
         let p = [0.05, 0.05];
         let rgb = RGB(0., 0., 0.);
@@ -2,7 +2,7 @@ use log::{debug, info};
 use rand::Rng;
 
 use crate::{color::{HSV, RGB}, histogram::BoundsTypes};
-use super::{Transformation, NonlinearTransformation, FractalFlame};
+use super::{Transformation, NonlinearTransformation, FractalFlame, DEFAULT_PARTITIONS};
 use crate::fractal::FractalBuilder;
 
 use crate::numbers::Real;
@@ -63,7 +63,8 @@ impl FractalBuilder
         debug!("Variation              : {:?}", variation);
 
         FractalFlame {
-            rng,
+            seed: self.seed,
+            partitions: DEFAULT_PARTITIONS,
             description,
             probabilities,
             colors,
@@ -0,0 +1,135 @@
+mod sampler;
+
+use log::info;
+
+use serde::{self, Serialize, Deserialize};
+use rand::{Rng, SeedableRng};
+
+use super::IteratedFunctionSystem;
+use sampler::JuliaIimSampler;
+use crate::fractal::FractalBuilder;
+use crate::fractal::escape_time_fractal::mandelbrot::julia_parameter;
+use crate::histogram::BoundsTypes;
+use crate::color::{RGB, HSV};
+use crate::numbers::{Real, Cplx};
+
+use super::Samplable;
+
+use super::{IterationFractalType, RngType, default_partitions};
+use super::DEFAULT_PARTITIONS;
+
+fn default_gamma() -> f64 {
+    4.0
+}
+
+fn default_vibrancy() -> f64 {
+    0.5
+}
+
+fn default_bounds() -> BoundsTypes {
+    BoundsTypes::StrictBounds
+}
+
+/// The Julia set of `z -> z^2 + c` by the inverse iteration method.
+///
+/// Every step follows one of the two preimages `±sqrt(z - c)` at random,
+/// which converges to the Julia set from everywhere. The colors of the
+/// branches are mixed like the colors of the transformations of a flame.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JuliaIim {
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default = "default_partitions")]
+    pub partitions: usize,
+    pub description: String,
+    pub c: Cplx,
+    /// colors of the positive and the negative branch of the square root
+    pub colors: [RGB; 2],
+    #[serde(default = "default_bounds")]
+    pub bounds: BoundsTypes,
+    #[serde(default = "default_gamma")]
+    pub gamma: f64,
+    #[serde(default = "default_vibrancy")]
+    pub vibrancy: f64
+}
+
+impl FractalBuilder
+{
+    pub fn julia_iim(self) -> JuliaIim {
+        let mut rng = self.seed_rng();
+
+        let c = match self.parameter {
+            Some(c) => Cplx::new(c.0 as Real, c.1 as Real),
+            None => julia_parameter(&mut rng)
+        };
+
+        let hue: f64 = rng.gen();
+        let colors = [
+            HSV(hue, 1., 1.).to_rgb(),
+            HSV((hue + rng.gen_range(0.2, 0.8)).fract(), 1., 1.).to_rgb(),
+        ];
+
+        let gamma = self.gamma.unwrap_or(4.);
+        let vibrancy = self.vibrancy.unwrap_or_else(|| rng.gen());
+        let bounds = self.bounds.unwrap_or(BoundsTypes::StrictBounds);
+
+        let description = format!("Julia Set of z^2 + ({:.4}) by inverse iteration", c);
+
+        info!("Will render {}", description);
+
+        JuliaIim {
+            seed: self.seed,
+            partitions: DEFAULT_PARTITIONS,
+            description,
+            c,
+            colors,
+            bounds,
+            gamma,
+            vibrancy,
+        }
+    }
+}
+
+impl IteratedFunctionSystem for JuliaIim
+{
+    fn needs_strict_bounds(&self) -> BoundsTypes {
+        self.bounds
+    }
+
+    fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    fn vibrancy(&self) -> f64 {
+        self.vibrancy
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn partitions(&self) -> usize {
+        self.partitions
+    }
+
+    fn get_sampler(&self, seed: u64) -> Box<dyn Samplable + Send> {
+        // the repelling fixed point belongs to the Julia set, such that
+        // already the first samples lie on it
+        let beta = (Cplx::new(0.25, 0.) - self.c).sqrt() + 0.5;
+        Box::new(JuliaIimSampler {
+            rng: RngType::seed_from_u64(seed),
+            c: self.c,
+            colors: self.colors.clone(),
+            z: beta,
+            rgb: RGB(1., 1., 1.),
+        })
+    }
+
+    fn get_serializable(&self) -> IterationFractalType {
+        IterationFractalType::JuliaIim(self.clone())
+    }
+}
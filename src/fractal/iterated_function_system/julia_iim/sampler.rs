@@ -0,0 +1,47 @@
+use crate::color::RGB;
+use crate::numbers::{Real, Cplx};
+use rand::Rng;
+use super::super::{Perturbable, Samplable};
+
+pub struct JuliaIimSampler<T>
+    where T: Rng
+{
+    pub rng: T,
+    pub c: Cplx,
+    pub colors: [RGB; 2],
+    pub z: Cplx,
+    pub rgb: RGB,
+}
+
+impl<T> Iterator for JuliaIimSampler<T>
+    where T: Rng
+{
+    type Item = ([Real; 2], RGB);
+
+    fn next(&mut self) -> Option<([Real; 2], RGB)> {
+        let root = (self.z - self.c).sqrt();
+        let branch = if self.rng.gen::<bool>() { 0 } else { 1 };
+        self.z = if branch == 0 { root } else { -root };
+
+        let RGB(r, g, b) = self.rgb;
+        let RGB(tr, tg, tb) = self.colors[branch];
+        self.rgb = RGB(
+            (r + tr)/2.,
+            (g + tg)/2.,
+            (b + tb)/2.
+        );
+
+        Some(([self.z.re, self.z.im], self.rgb.clone()))
+    }
+}
+
+impl<T> Perturbable for JuliaIimSampler<T>
+    where T: Rng
+{
+    fn perturb(&mut self) {
+        // the orbit starts on the Julia set and never leaves it
+    }
+}
+
+impl<T> Samplable for JuliaIimSampler<T>
+    where T: Rng {}
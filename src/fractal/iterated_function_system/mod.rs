@@ -4,6 +4,8 @@ pub mod variation;
 pub mod symmetry;
 pub mod quadratic_map;
 pub mod ode;
+pub mod buddhabrot;
+pub mod julia_iim;
 
 pub mod transformation;
 pub use self::transformation::{Transformation,AffineTransformation,MobiusTransformation,NonlinearTransformation};
@@ -20,30 +22,52 @@ use crate::histogram::{BoundsTypes, bounds_without_outliers, bounds_zoom, Colore
 use self::{ode::OdeFractal, quality::probably_good};
 
 use super::{FractalBuilder, estimate_quality_after};
+use super::progress::{Progress, RenderControl};
+use crate::error::{FractalError, from_json};
 
 use num_cpus;
-use std::thread;
-use std::sync::mpsc::channel;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use serde::{self, Serialize, Deserialize};
 
 use fractal_flame::FractalFlame;
 use quadratic_map::QuadraticMap;
+use buddhabrot::Buddhabrot;
+use julia_iim::JuliaIim;
 
-use super::{RngType, default_rng};
+use rand::SeedableRng;
+use super::{RngType, stream_seed};
+
+/// number of independent samplers of a render, unless the fractal needs a specific number
+///
+/// The partitions are fixed, such that the image does not depend on the number of CPUs.
+pub const DEFAULT_PARTITIONS: usize = 16;
+
+fn default_partitions() -> usize {
+    DEFAULT_PARTITIONS
+}
+
+// random streams derived from the seed of the fractal
+// partition `i` of the render uses `PARTITION_STREAM + i`
+const QUALITY_STREAM: u64 = 0;
+const CORRELATION_STREAM: u64 = 1;
+const WARMUP_STREAM: u64 = 2;
+const PARTITION_STREAM: u64 = 3;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum IterationFractalType {
     IFS(FractalFlame),
     QuadraticMap(QuadraticMap),
     OdeFractal(OdeFractal),
+    Buddhabrot(Buddhabrot),
+    JuliaIim(JuliaIim),
     None,
 }
 
 impl FractalBuilder
 {
-    pub fn ifs_from_json(json: &str) -> Result<IterationFractalType, serde_json::Error> {
-        serde_json::from_str(json)
+    pub fn ifs_from_json(json: &str) -> Result<IterationFractalType, FractalError> {
+        from_json(json, "iterated function system")
     }
 }
 
@@ -52,19 +76,17 @@ pub enum SuggestedIterations {
     PerPixel(usize),
 }
 
-pub enum SuggestedParallelism {
-    Limited(usize),
-    Unlimited,
-}
-
 /// The `IteratedFunctionSystem` trait applies to all ``Chaos Game type'' fractals.
 pub trait IteratedFunctionSystem : Sync {
     fn description(&self) -> &str;
     fn needs_strict_bounds(&self) -> BoundsTypes;
     fn gamma(&self) -> f64;
     fn vibrancy(&self) -> f64;
-    fn get_rng(&mut self) -> &mut RngType;
-    fn get_sampler(&mut self) -> Box<dyn Samplable + Send>;
+    /// the seed from which all random decisions during rendering are derived
+    fn seed(&self) -> u64;
+    /// the number of independent samplers, which are merged in a fixed order
+    fn partitions(&self) -> usize;
+    fn get_sampler(&self, seed: u64) -> Box<dyn Samplable + Send>;
     fn get_serializable(&self) -> IterationFractalType;
 
     fn suggested_iterations(&self) -> SuggestedIterations {
@@ -75,12 +97,13 @@ pub trait IteratedFunctionSystem : Sync {
         SuggestedIterations::PerPixel(100)
     }
 
-    fn suggested_parallelism(&self) -> SuggestedParallelism {
-        SuggestedParallelism::Unlimited
+    /// the colors of the pixels, the brightness is logarithmic in the number of samples
+    fn normalize(&self, hist: &ColoredHistogram) -> Vec<RGBA> {
+        hist.normalize()
     }
 
-    fn estimate_quality_before(&mut self) -> bool {
-        let sampler = self.get_sampler();
+    fn estimate_quality_before(&self) -> bool {
+        let sampler = self.get_sampler(stream_seed(self.seed(), QUALITY_STREAM));
 
         // warm up and get sample to derive bounds
         let values: Vec<([Real; 2], RGB)> = sampler.skip(1000)
@@ -94,15 +117,17 @@ pub trait IteratedFunctionSystem : Sync {
         let b = bounds_without_outliers(coords.iter(), 100);
 
         // estimate if the fractal will be interesting
-        probably_good(&coords, b)
+        let mut rng = RngType::seed_from_u64(stream_seed(self.seed(), CORRELATION_STREAM));
+        probably_good(&coords, b, &mut rng)
     }
 
     fn render(&mut self, resolution: (u32, u32),
                          samples: SuggestedIterations,
-                         parallelism: SuggestedParallelism,
-                         supersampling: bool
+                         partitions: usize,
+                         supersampling: bool,
+                         control: &RenderControl
         )
-        -> (Vec<u8>, bool)
+        -> Result<(Vec<u8>, bool), FractalError>
     {
         let (x, y) = resolution;
 
@@ -112,10 +137,6 @@ pub trait IteratedFunctionSystem : Sync {
             (x, y)
         };
 
-        // use N-1 additional threads (where N is the number of logical CPU)
-        // this way one thread is idle and can calculate the remainder and merge the results
-
-
         let (total_samples, warmup) = match samples {
             SuggestedIterations::Absolute(samples) => (
                 samples,
@@ -127,12 +148,7 @@ pub trait IteratedFunctionSystem : Sync {
             ),
         };
 
-        let parallelism = match parallelism {
-            SuggestedParallelism::Unlimited => num_cpus::get(),
-            SuggestedParallelism::Limited(n) => n,
-        };
-
-        let sampler = self.get_sampler();
+        let sampler = self.get_sampler(stream_seed(self.seed(), WARMUP_STREAM));
 
         // warm up and get sample to derive bounds
         // these samples will be discarded
@@ -147,26 +163,43 @@ pub trait IteratedFunctionSystem : Sync {
             BoundsTypes::ZoomedBounds => bounds_zoom(values.iter().map(|&(ref z, _)| z), x as Real/y as Real),
         };
 
-        // we might miss up to parallelism - 1 samples, but we do not care about this
-        let iterations_per_task = total_samples / parallelism;
+        // we might miss up to partitions - 1 samples, but we do not care about this
+        let iterations_per_task = total_samples / partitions;
 
         let mut hist = ColoredHistogram::new((x, y), b, self.vibrancy(), self.gamma());
 
-        let (tx, rx) = channel();
-        for _ in 0..parallelism {
-            let tx = tx.clone();
-            let mut sampler = self.get_sampler();
-            sampler.perturb();
-            let mut hist = hist.clone();
-            thread::spawn(move || {
-                hist.feed(sampler.take(iterations_per_task));
-                tx.send(hist).unwrap();
-            });
-        }
-
-        for _ in 0..parallelism {
-            let h = rx.recv().unwrap();
-            hist.merge(&h);
+        // feed the samples in chunks, such that we can report progress and abort
+        const CHUNK: usize = 100_000;
+        let samples_total = iterations_per_task * partitions;
+        let samples_done = AtomicUsize::new(0);
+
+        // render as many partitions at once as we have CPUs and merge them
+        // in the order of the partitions, since floating point addition
+        // is not associative
+        let this: &Self = self;
+        let ids: Vec<usize> = (0..partitions).collect();
+        for batch in ids.chunks(num_cpus::get()) {
+            let hists: Vec<ColoredHistogram> = batch.par_iter()
+                .map(|&i| {
+                    let mut sampler = this.get_sampler(stream_seed(this.seed(), PARTITION_STREAM + i as u64));
+                    sampler.perturb();
+                    let mut hist = hist.clone();
+                    let mut remaining = iterations_per_task;
+                    while remaining > 0 && !control.is_cancelled() {
+                        let n = std::cmp::min(CHUNK, remaining);
+                        hist.feed(sampler.by_ref().take(n));
+                        remaining -= n;
+                        let done = samples_done.fetch_add(n, Ordering::Relaxed) + n;
+                        control.report(Progress::Samples { done, total: samples_total });
+                    }
+                    hist
+                })
+                .collect();
+
+            control.check()?;
+            for h in &hists {
+                hist.merge(h);
+            }
         }
 
         let hist = if supersampling {
@@ -176,7 +209,7 @@ pub trait IteratedFunctionSystem : Sync {
             hist
         };
 
-        let rgb = hist.normalize();
+        let rgb = self.normalize(&hist);
 
         let buffer: Vec<u8> = rgb.par_iter()
                                  .map(|rgba| {
@@ -193,7 +226,7 @@ pub trait IteratedFunctionSystem : Sync {
                                  .collect();
 
         let good = estimate_quality_after(&rgb, &resolution);
-        (buffer, good)
+        Ok((buffer, good))
     }
 }
 
@@ -229,20 +262,23 @@ impl<T> Iterator for IteratedFunctionSystemSampler<T>
             }
         }
 
+        let identity = AffineTransformation::identity();
         let mut is_symmetry_transformation = false;
-        let transformed = match self.transformations[index] {
+        // variations like Popcorn use the coefficients of the affine
+        // transformation, Mobius transformations have none
+        let (transformed, affine) = match self.transformations[index] {
             Transformation::Affine(ref x) => {
                 is_symmetry_transformation = x.symmetry;
-                x.transform(self.p)
+                (x.transform(self.p), x)
             },
             Transformation::Mobius(ref x) => {
-                x.transform(self.p)
+                (x.transform(self.p), &identity)
             }
         };
 
         // do not apply variation to symmetry transforms and do not bother about linear (identity)
         if !is_symmetry_transformation {
-            self.p = self.variation.transform(transformed);
+            self.p = self.variation.transform(transformed, affine, &mut self.rng);
         } else {
             self.p = transformed;
         }
@@ -258,7 +294,7 @@ impl<T> Iterator for IteratedFunctionSystemSampler<T>
             )
         };
 
-        let p = self.final_transform.transform(self.p);
+        let p = self.final_transform.transform(self.p, &identity, &mut self.rng);
         let rgb = match self.final_color {
             Some(ref c) => {
                 let RGB(rf, gf, bf) = c.clone();
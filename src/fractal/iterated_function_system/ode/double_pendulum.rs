@@ -112,7 +112,7 @@ impl FractalBuilder
         info!("Will render {}", description);
 
         OdeFractal {
-            rng,
+            seed: self.seed,
             description,
             ode,
             color,
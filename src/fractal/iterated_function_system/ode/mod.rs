@@ -9,7 +9,7 @@ use serde::{self, Serialize, Deserialize};
 use rand::{Rng, SeedableRng};
 use rand::distributions::{Distribution, Normal};
 
-use super::{IteratedFunctionSystem, SuggestedIterations, SuggestedParallelism};
+use super::{IteratedFunctionSystem, SuggestedIterations};
 use sampler::OdeFractalSampler;
 use ode_system::OdeSystem;
 use lorenz::LorenzOde;
@@ -20,7 +20,7 @@ use crate::{color::RGB, histogram::BoundsTypes, numbers::Real};
 
 use super::{Perturbable, Samplable};
 
-use super::{IterationFractalType, RngType, default_rng};
+use super::{IterationFractalType, RngType};
 
 fn default_gamma() -> f64 {
     4.0
@@ -45,9 +45,8 @@ pub enum OdeTypes {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OdeFractal
 {
-    #[serde(skip)]
-    #[serde(default = "default_rng")]
-    rng: RngType,
+    #[serde(default)]
+    pub seed: u64,
     pub description: String,
     pub color: RGB,
     pub ode: OdeTypes,
@@ -77,10 +76,6 @@ impl IteratedFunctionSystem for OdeFractal
         SuggestedIterations::Absolute((self.total_time as f64 / self.timestep) as usize)
     }
 
-    fn suggested_parallelism(&self) -> SuggestedParallelism {
-        SuggestedParallelism::Limited(self.replica)
-    }
-
     fn gamma(&self) -> f64 {
         self.gamma
     }
@@ -93,13 +88,17 @@ impl IteratedFunctionSystem for OdeFractal
         &self.description
     }
 
-    fn get_rng(&mut self) -> &mut RngType
-    {
-        &mut self.rng
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// every replica is an independent trajectory
+    fn partitions(&self) -> usize {
+        self.replica
     }
 
-    fn get_sampler(&mut self) -> Box<dyn Samplable + Send> {
-        let rng = RngType::seed_from_u64(self.rng.gen::<u64>());
+    fn get_sampler(&self, seed: u64) -> Box<dyn Samplable + Send> {
+        let rng = RngType::seed_from_u64(seed);
 
         let ode: Box<dyn OdeSystem> = match &self.ode {
             OdeTypes::Lorenz(x) => Box::new(x.clone()),
@@ -8,10 +8,12 @@ use super::{IteratedFunctionSystem, SuggestedIterations};
 use sampler::QuadraticMapSampler;
 use crate::{color::RGB, histogram::BoundsTypes};
 use crate::numbers::Real;
+use crate::error::FractalError;
 
 use super::{Perturbable, Samplable};
 
-use super::{IterationFractalType, RngType, default_rng};
+use super::{IterationFractalType, RngType, default_partitions};
+use super::DEFAULT_PARTITIONS;
 
 
 fn default_gamma() -> f64 {
@@ -29,9 +31,10 @@ fn default_bounds() -> BoundsTypes {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct QuadraticMap
 {
-    #[serde(skip)]
-    #[serde(default = "default_rng")]
-    rng: RngType,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default = "default_partitions")]
+    pub partitions: usize,
     pub description: String,
     pub color: RGB,
     pub a: Vec<Real>,
@@ -44,15 +47,16 @@ pub struct QuadraticMap
 }
 
 impl QuadraticMap {
-    fn from_string(s: &str) -> Vec<f64> {
-        assert!(s.len() == 12);
+    fn from_string(s: &str) -> Result<Vec<f64>, FractalError> {
+        if s.chars().count() != 12 || !s.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(FractalError::InvalidQmapRule(format!("'{}' needs to consist of exactly 12 letters", s)))
+        }
         let mut a: Vec<f64> = Vec::new();
         for c in s.chars() {
-            assert!(c.is_ascii_alphabetic());
             let tmp = (c.to_ascii_lowercase() as u8 - b'a') as f64;
             a.push(0.1*tmp-1.2);
         }
-        a
+        Ok(a)
     }
 }
 
@@ -78,15 +82,18 @@ impl IteratedFunctionSystem for QuadraticMap
         SuggestedIterations::PerPixel(5)
     }
 
-    fn get_rng(&mut self) -> &mut RngType
-    {
-        &mut self.rng
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn partitions(&self) -> usize {
+        self.partitions
     }
 
-    fn get_sampler(&mut self) -> Box<dyn Samplable + Send> {
-        let rng = RngType::seed_from_u64(self.rng.gen::<u64>());
+    fn get_sampler(&self, seed: u64) -> Box<dyn Samplable + Send> {
+        let mut rng = RngType::seed_from_u64(seed);
 
-        let p = [self.rng.gen_range(-0.5, 0.5), self.rng.gen_range(-0.5, 0.5)];
+        let p = [rng.gen_range(-0.5, 0.5), rng.gen_range(-0.5, 0.5)];
 
         Box::new(QuadraticMapSampler {
             rng,
@@ -5,17 +5,18 @@ use rand::Rng;
 use crate::color::HSV;
 use crate::fractal::FractalBuilder;
 use crate::histogram::BoundsTypes;
-use super::QuadraticMap;
+use super::{QuadraticMap, DEFAULT_PARTITIONS};
+use crate::error::FractalError;
 
 use crate::numbers::Real;
 
 impl FractalBuilder
 {
-    pub fn quadratic_map(self) -> QuadraticMap {
+    pub fn quadratic_map(self) -> Result<QuadraticMap, FractalError> {
         let mut rng = self.seed_rng();
 
         let a = match self.qmaprule {
-            Some(s) => QuadraticMap::from_string(&s),
+            Some(s) => QuadraticMap::from_string(&s)?,
             None => (0..12).map(|_| rng.gen::<Real>() * 2.4 - 1.2).collect(),
         };
 
@@ -29,14 +30,15 @@ impl FractalBuilder
 
         info!("Will render {}", description);
 
-        QuadraticMap {
-            rng,
+        Ok(QuadraticMap {
+            seed: self.seed,
+            partitions: DEFAULT_PARTITIONS,
             description,
             a,
             color,
             bounds,
             gamma,
             vibrancy,
-        }
+        })
     }
 }
@@ -2,8 +2,7 @@ use crate::numbers::Real;
 
 use log::info;
 
-use rand::rngs::SmallRng;
-use rand::{Rng, FromEntropy};
+use rand::Rng;
 
 /// Estimates if the resulting fractal will be interesting
 ///
@@ -11,12 +10,13 @@ use rand::{Rng, FromEntropy};
 ///
 /// * `vals` - slice of a sample of points visited by the IFS
 /// * `bounds` - approximate bounds of the attractor
+/// * `rng` - random number generator to pick pairs of points
 ///
 /// # Remarks
 ///
 /// Uses the techniques from <http://sprott.physics.wisc.edu/pubs/paper210.pdf>
 /// and some ad hoc heuristics.
-pub fn probably_good(vals: &[[Real; 2]], bounds: (Real, Real, Real, Real)) -> bool {
+pub fn probably_good(vals: &[[Real; 2]], bounds: (Real, Real, Real, Real), rng: &mut impl Rng) -> bool {
     let width = bounds.1 - bounds.0;
     let height = bounds.3 - bounds.2;
 
@@ -32,7 +32,7 @@ pub fn probably_good(vals: &[[Real; 2]], bounds: (Real, Real, Real, Real)) -> bo
         return false
     }
 
-    let dim = correlation_dimension(vals, width+height);
+    let dim = correlation_dimension(vals, width+height, rng);
     if dim < 1.02 || dim.is_nan() {
         info!("bad correlation dimension ({})", dim);
         return false
@@ -50,20 +50,19 @@ pub fn probably_good(vals: &[[Real; 2]], bounds: (Real, Real, Real, Real)) -> bo
 ///
 /// * `vals` - slice of a sample of points visited by the IFS
 /// * `span` - total size of the attractor
+/// * `rng` - random number generator to pick pairs of points
 ///
 /// # Remarks
 ///
 /// The correlation dimension is an estimate for the fractal dimension.
 /// Fractals with a dimension larger than one are subjectively more pleasing.
 /// See also <http://sprott.physics.wisc.edu/pubs/paper210.pdf>
-fn correlation_dimension(vals: &[[Real; 2]], span: Real) -> Real {
+fn correlation_dimension(vals: &[[Real; 2]], span: Real, rng: &mut impl Rng) -> Real {
     let mut n1: Real = 0.;
     let mut n2: Real = 0.;
     let r1: Real = span/100.;
     let r2: Real = 10.*r1;
 
-    let mut rng = SmallRng::from_entropy();
-
     for (n, i) in vals.iter().enumerate().skip(20) {
         let j = vals[rng.gen_range(0, n)];
         let r = ((i[0] - j[0]).powi(2) + (i[1] - j[1]).powi(2)).sqrt();
@@ -2,7 +2,9 @@ use rand::Rng;
 
 use std::fmt;
 
-#[derive(Debug, Clone)]
+use serde::{self, Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Symmetry {
     Rotational(usize),
     Vertical,
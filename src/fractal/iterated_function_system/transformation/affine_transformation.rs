@@ -42,6 +42,12 @@ impl AffineTransformation {
         out
     }
 
+    /// the coefficients `[a, b, c, d, e, f]` of `x' = a x + b y + c`
+    /// and `y' = d x + e y + f`
+    pub fn parameters(&self) -> &[Real; 6] {
+        &self.parameters
+    }
+
     pub fn identity() -> AffineTransformation {
         AffineTransformation {
             parameters: [1., 0., 0.,
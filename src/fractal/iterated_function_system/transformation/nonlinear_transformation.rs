@@ -5,6 +5,7 @@ use serde::{self, Serialize, Deserialize};
 
 use crate::fractal::Variation;
 use crate::numbers::Real;
+use super::AffineTransformation;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NonlinearTransformation {
@@ -49,9 +50,11 @@ impl NonlinearTransformation {
     //     self.variation.name()
     // }
 
-    pub fn transform(&self, r: [Real; 2]) -> [Real; 2] {
+    /// apply the variations to the point `r`, which the transformation
+    /// of the coefficients `affine` produced
+    pub fn transform(&self, r: [Real; 2], affine: &AffineTransformation, rng: &mut impl Rng) -> [Real; 2] {
         let rs: Vec<[Real; 2]> = self.variations.iter()
-                                                .map(|v| v.apply(r))
+                                                .map(|v| v.apply(r, affine, rng))
                                                 .collect();
 
         let mut x = 0.;
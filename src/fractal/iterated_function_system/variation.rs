@@ -6,11 +6,22 @@ use rand::seq::SliceRandom;
 use serde::{self, Serialize, Deserialize};
 
 use crate::numbers::Real;
+use super::AffineTransformation;
 use std::f64::consts::PI as PI64;
 
 const PI: Real = PI64 as Real;
 
-const VARIATION_NUMBERS: [u8; 22] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 16, 18, 19, 20, 23, 24, 25];
+/// added to divisors like in flam3, the coefficients of the affine
+/// transformation are zero for Mobius transformations
+const EPS: Real = 1e-10;
+
+/// the implemented variations, numbered like in flam3
+const VARIATION_NUMBERS: [u8; 38] = [
+     0,  1,  2,  3,  4,  5,  6,  7,  8,  9,
+    10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
+    20, 21, 22, 23, 24, 25, 26, 27, 28, 29,
+    30, 32, 33, 38, 39, 40, 55, 77,
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Variation {
@@ -29,22 +40,36 @@ pub enum Variation {
     Ex,
     Julia(Real),
     Bent,
-    // Waves,
+    Waves,
     Fisheye,
-    // Popcorn,
+    Popcorn,
     Exponential,
     Power,
     Cosine,
-    // Rings,
-    // Fan,
+    Rings,
+    Fan,
     Blob(Real, Real, Real),
     Pdj(Real, Real, Real, Real),
     Fan2(Real, Real),
-    // Rings2(Real),
-    // Eyefish,
-    // Bubble,
-    // Cylinder,
-    // Perspective(Real, Real),
+    Rings2(Real),
+    Eyefish,
+    Bubble,
+    Cylinder,
+    /// angle and distance of the viewer
+    Perspective(Real, Real),
+    /// power and distance
+    JuliaN(Real, Real),
+    /// power and distance
+    JuliaScope(Real, Real),
+    /// power, sides, corners and circle
+    Ngon(Real, Real, Real, Real),
+    Curl(Real, Real),
+    /// width and height of the rectangles
+    Rectangles(Real, Real),
+    /// shift
+    Bipolar(Real),
+    /// angle, hole, count and swirl
+    Wedge(Real, Real, Real, Real),
 }
 
 impl Variation {
@@ -80,10 +105,14 @@ impl Variation {
             "Ex" | "12" => Some(Variation::Ex),
             "Julia" | "13" => Some(Variation::Julia(rng.gen_range(0.,1.))),
             "Bent" | "14" => Some(Variation::Bent),
+            "Waves" | "15" => Some(Variation::Waves),
             "Fisheye" | "16" => Some(Variation::Fisheye),
+            "Popcorn" | "17" => Some(Variation::Popcorn),
             "Exponential" | "18" => Some(Variation::Exponential),
             "Power" | "19" => Some(Variation::Power),
             "Cosine" | "20" => Some(Variation::Cosine),
+            "Rings" | "21" => Some(Variation::Rings),
+            "Fan" | "22" => Some(Variation::Fan),
             "Blob" | "23" => Some(Variation::Blob(
                 rng.gen_range(0.,1.),
                 rng.gen_range(0.,1.),
@@ -99,6 +128,43 @@ impl Variation {
                 rng.gen_range(0.,1.),
                 rng.gen_range(0.,1.),
             )),
+            "Rings2" | "26" => Some(Variation::Rings2(rng.gen_range(0.,1.))),
+            "Eyefish" | "27" => Some(Variation::Eyefish),
+            "Bubble" | "28" => Some(Variation::Bubble),
+            "Cylinder" | "29" => Some(Variation::Cylinder),
+            "Perspective" | "30" => Some(Variation::Perspective(
+                rng.gen_range(0.,PI/2.),
+                rng.gen_range(1.,3.),
+            )),
+            "JuliaN" | "32" => Some(Variation::JuliaN(
+                rng.gen_range(2,7) as Real,
+                rng.gen_range(0.5,2.),
+            )),
+            "JuliaScope" | "33" => Some(Variation::JuliaScope(
+                rng.gen_range(2,7) as Real,
+                rng.gen_range(0.5,2.),
+            )),
+            "Ngon" | "38" => Some(Variation::Ngon(
+                rng.gen_range(0.,4.),
+                rng.gen_range(3,10) as Real,
+                rng.gen_range(0.,2.),
+                rng.gen_range(0.,2.),
+            )),
+            "Curl" | "39" => Some(Variation::Curl(
+                rng.gen_range(0.,1.),
+                rng.gen_range(0.,1.),
+            )),
+            "Rectangles" | "40" => Some(Variation::Rectangles(
+                rng.gen_range(0.1,1.),
+                rng.gen_range(0.1,1.),
+            )),
+            "Bipolar" | "55" => Some(Variation::Bipolar(rng.gen_range(-1.,1.))),
+            "Wedge" | "77" => Some(Variation::Wedge(
+                rng.gen_range(0.,PI/2.),
+                rng.gen_range(0.,0.5),
+                rng.gen_range(1,7) as Real,
+                rng.gen_range(0.,0.5),
+            )),
             _ => None
         }
     }
@@ -120,13 +186,29 @@ impl Variation {
             Variation::Ex => "Ex",
             Variation::Julia(_) => "Julia",
             Variation::Bent => "Bent",
+            Variation::Waves => "Waves",
             Variation::Fisheye => "Fisheye",
+            Variation::Popcorn => "Popcorn",
             Variation::Exponential => "Exponential",
             Variation::Power => "Power",
             Variation::Cosine => "Cosine",
+            Variation::Rings => "Rings",
+            Variation::Fan => "Fan",
             Variation::Blob(_, _, _) => "Blob",
             Variation::Pdj(_, _, _, _) => "Pdj",
             Variation::Fan2(_, _) => "Fan2",
+            Variation::Rings2(_) => "Rings2",
+            Variation::Eyefish => "Eyefish",
+            Variation::Bubble => "Bubble",
+            Variation::Cylinder => "Cylinder",
+            Variation::Perspective(_, _) => "Perspective",
+            Variation::JuliaN(_, _) => "JuliaN",
+            Variation::JuliaScope(_, _) => "JuliaScope",
+            Variation::Ngon(_, _, _, _) => "Ngon",
+            Variation::Curl(_, _) => "Curl",
+            Variation::Rectangles(_, _) => "Rectangles",
+            Variation::Bipolar(_) => "Bipolar",
+            Variation::Wedge(_, _, _, _) => "Wedge",
         }.to_owned()
     }
 
@@ -137,9 +219,15 @@ impl Variation {
                    .collect()
     }
 
-    pub fn apply(&self, r: [Real; 2]) -> [Real; 2] {
+    /// apply the variation to the point `r`, which the transformation of
+    /// the coefficients `affine` produced
+    ///
+    /// Some variations, like Popcorn, depend on these coefficients, others,
+    /// like JuliaN, choose one of several branches at random.
+    pub fn apply(&self, r: [Real; 2], affine: &AffineTransformation, rng: &mut impl Rng) -> [Real; 2] {
         let x = r[0];
         let y = r[1];
+        let [_, b, c, _, e, f] = *affine.parameters();
 
         match *self {
             Variation::Linear => r,
@@ -218,11 +306,17 @@ impl Variation {
                     [2.*x, y/2.]
                 }
             }
+            Variation::Waves => {
+                [x + b * (y / (c*c + EPS)).sin(), y + e * (x / (f*f + EPS)).sin()]
+            }
             Variation::Fisheye => {
                 let r = (x*x + y*y).sqrt();
                 let ir = 2. / (r + 1.);
                 [ir * y, ir * x]
             }
+            Variation::Popcorn => {
+                [x + c * (3.*y).tan().sin(), y + f * (3.*x).tan().sin()]
+            }
             Variation::Exponential => {
                 let f = (x - 1.).exp();
                 [f * (PI * y).cos(), f * (PI * y).sin()]
@@ -236,6 +330,23 @@ impl Variation {
             Variation::Cosine => {
                 [(PI*x).cos() * y.cosh(), -(PI*x).sin() * y.sinh()]
             }
+            Variation::Rings => {
+                let r = (x*x + y*y).sqrt();
+                let theta = x.atan2(y);
+                let c2 = c*c + EPS;
+                let f = (r + c2) % (2.*c2) - c2 + r*(1. - c2);
+                [f * theta.cos(), f * theta.sin()]
+            }
+            Variation::Fan => {
+                let r = (x*x + y*y).sqrt();
+                let theta = x.atan2(y);
+                let t = PI * (c*c + EPS);
+                if (theta + f) % t > t / 2. {
+                    [r * (theta - t/2.).cos(), r * (theta - t/2.).sin()]
+                } else {
+                    [r * (theta + t/2.).cos(), r * (theta + t/2.).sin()]
+                }
+            }
             Variation::Blob(p1, p2, p3) => {
                 let r = (x*x + y*y).sqrt();
                 let theta = (x/y).atan();
@@ -255,6 +366,84 @@ impl Variation {
                     [r * (theta + p1/2.).sin(), r * (theta + p1/2.).cos()]
                 }
             }
+            Variation::Rings2(value) => {
+                let r = (x*x + y*y).sqrt();
+                let theta = x.atan2(y);
+                let p = value*value + EPS;
+                let t = r - 2.*p * ((r + p) / (2.*p)).trunc() + r*(1. - p);
+                [t * theta.sin(), t * theta.cos()]
+            }
+            Variation::Eyefish => {
+                let r = (x*x + y*y).sqrt();
+                let f = 2. / (r + 1.);
+                [f * x, f * y]
+            }
+            Variation::Bubble => {
+                let f = 4. / (x*x + y*y + 4.);
+                [f * x, f * y]
+            }
+            Variation::Cylinder => [x.sin(), y],
+            Variation::Perspective(angle, distance) => {
+                let f = distance / (distance - y * angle.sin());
+                [f * x, f * y * angle.cos()]
+            }
+            Variation::JuliaN(power, distance) => {
+                let r2 = x*x + y*y;
+                let phi = y.atan2(x);
+                let branch = (power.abs() * rng.gen::<Real>()).trunc();
+                let t = (phi + 2.*PI*branch) / power;
+                let f = r2.powf(distance / power / 2.);
+                [f * t.cos(), f * t.sin()]
+            }
+            Variation::JuliaScope(power, distance) => {
+                let r2 = x*x + y*y;
+                let phi = y.atan2(x);
+                let branch = (power.abs() * rng.gen::<Real>()).trunc();
+                // odd branches are mirrored
+                let sign = if branch % 2. == 0. { 1. } else { -1. };
+                let t = (sign * phi + 2.*PI*branch) / power;
+                let f = r2.powf(distance / power / 2.);
+                [f * t.cos(), f * t.sin()]
+            }
+            Variation::Ngon(power, sides, corners, circle) => {
+                let r2 = x*x + y*y;
+                let phi = y.atan2(x);
+                let p2 = 2.*PI / sides;
+                let t3 = phi - p2 * (phi / p2).floor();
+                let t4 = if t3 > p2 / 2. { t3 - p2 } else { t3 };
+                let k = (corners * (1. / (t4.cos() + EPS) - 1.) + circle) / (r2.powf(power / 2.) + EPS);
+                [k * x, k * y]
+            }
+            Variation::Curl(c1, c2) => {
+                let t1 = 1. + c1*x + c2*(x*x - y*y);
+                let t2 = c1*y + 2.*c2*x*y;
+                let f = 1. / (t1*t1 + t2*t2);
+                [f * (x*t1 + y*t2), f * (y*t1 - x*t2)]
+            }
+            Variation::Rectangles(width, height) => {
+                // a side of length zero leaves its coordinate alone
+                let fold = |v: Real, side: Real| if side == 0. { v } else { (2. * (v / side).floor() + 1.) * side - v };
+                [fold(x, width), fold(y, height)]
+            }
+            Variation::Bipolar(shift) => {
+                let r2 = x*x + y*y;
+                let t = r2 + 1.;
+                let mut v = 0.5 * (2.*y).atan2(r2 - 1.) - PI/2. * shift;
+                if v > PI/2. {
+                    v = -PI/2. + (v + PI/2.) % PI;
+                } else if v < -PI/2. {
+                    v = PI/2. - (PI/2. - v) % PI;
+                }
+                [0.5 / PI * ((t + 2.*x) / (t - 2.*x)).ln(), 2. / PI * v]
+            }
+            Variation::Wedge(angle, hole, count, swirl) => {
+                let r = (x*x + y*y).sqrt();
+                let a = y.atan2(x) + swirl * r;
+                let sector = ((count * a + PI) / (2.*PI)).floor();
+                let a = a * (1. - angle * count / (2.*PI)) + sector * angle;
+                let r = r + hole;
+                [r * a.cos(), r * a.sin()]
+            }
         }
     }
 }
@@ -270,3 +459,94 @@ impl Distribution<Variation> for Standard {
         Variation::from_number(*VARIATION_NUMBERS.choose(rng).unwrap() as usize, &mut rng).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use crate::fractal::RngType;
+
+    /// the reference values are calculated with the formulas of flam3
+    fn assert_apply(variation: Variation, expected: [[Real; 2]; 2]) {
+        let affine = AffineTransformation::new(0.5, 0.3, 0.7, -0.2, 0.4, 0.6);
+        let mut rng = RngType::seed_from_u64(1);
+        for (r, e) in [[0.3, -0.4], [-1.2, 0.5]].iter().zip(expected.iter()) {
+            let out = variation.apply(*r, &affine, &mut rng);
+            assert!((out[0] - e[0]).abs() < 1e-9 && (out[1] - e[1]).abs() < 1e-9,
+                    "{} of {:?} is {:?}, not {:?}", variation, r, out, e);
+        }
+    }
+
+    #[test]
+    fn test_numbers() {
+        let names = Variation::list();
+        assert_eq!(names.len(), VARIATION_NUMBERS.len());
+        assert!(!names.contains(&"n/a".to_owned()));
+
+        let mut rng = RngType::seed_from_u64(1);
+        assert_eq!(Variation::from_number(77, &mut rng).unwrap().name(), "Wedge");
+        assert_eq!(Variation::from_number(33, &mut rng).unwrap().name(), "JuliaScope");
+        assert!(Variation::from_number(31, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_affine_variations() {
+        assert_apply(Variation::Waves, [[0.08140956499342572, -0.10392925878384557], [-0.9443035291167894, 0.5762271847866112]]);
+        assert_apply(Variation::Popcorn, [[-0.07741295780972007, 0.17128322916540162], [-0.5004471990285119, 0.21579088173036703]]);
+        assert_apply(Variation::Rings, [[0.18000000019999987, -0.13500000014999988], [0.3780769229500001, -0.9073846150800002]]);
+        assert_apply(Variation::Fan, [[-0.49602435807308526, -0.06292722938588446], [1.1941585048730783, -0.5137951588320915]]);
+        assert_apply(Variation::Rings2(0.8), [[0.40799999996999997, -0.5439999999599999], [-0.4504615381569227, 0.18769230756538446]]);
+        assert_apply(Variation::Eyefish, [[0.4, -0.5333333333333333], [-1.0434782608695652, 0.4347826086956522]]);
+        assert_apply(Variation::Bubble, [[0.2823529411764706, -0.3764705882352941], [-0.843585237258348, 0.351493848857645]]);
+        assert_apply(Variation::Cylinder, [[0.29552020666133955, -0.4], [-0.9320390859672263, 0.5]]);
+        assert_apply(Variation::Perspective(0.5, 2.), [[0.2737513247387063, -0.320319185180103], [-1.3634138555038404, 0.4985450934291211]]);
+        assert_apply(Variation::Ngon(1.5, 3., 1., 0.5), [[0.9899494931454642, -1.3199326575272856], [-0.614039113895717, 0.2558496307898821]]);
+        assert_apply(Variation::Curl(0.3, 0.2), [[0.32883642495784143, -0.32040472175379425], [-1.4102985427171801, 0.4249124500631592]]);
+        assert_apply(Variation::Rectangles(0.3, 0.7), [[0.6, -0.3], [-0.9, 0.2]]);
+        assert_apply(Variation::Bipolar(0.3), [[0.16647106587601268, 0.9602645014777477], [-0.45600313320222063, 0.007746246935341331]]);
+        assert_apply(Variation::Wedge(0.4, 0.2, 3., 0.1), [[0.5309700253098085, -0.4561478183905971], [-1.3731459517098357, 0.6037136699652323]]);
+        // a single branch is deterministic
+        assert_apply(Variation::JuliaN(1., 0.8), [[0.34460950649911043, -0.45947934199881396], [-1.1386560960656993, 0.4744400400273746]]);
+    }
+
+    /// every branch of the variation is chosen, and nothing else
+    fn assert_branches(variation: Variation, branches: [[Real; 2]; 3]) {
+        let affine = AffineTransformation::identity();
+        let mut rng = RngType::seed_from_u64(1);
+        let mut seen = [false; 3];
+        for _ in 0..100 {
+            let out = variation.apply([0.3, -0.4], &affine, &mut rng);
+            let branch = branches.iter()
+                .position(|b| (out[0] - b[0]).abs() < 1e-9 && (out[1] - b[1]).abs() < 1e-9)
+                .unwrap_or_else(|| panic!("{} of (0.3, -0.4) is {:?}, which is no branch", variation, out));
+            seen[branch] = true;
+        }
+        assert_eq!(seen, [true; 3], "{}", variation);
+    }
+
+    #[test]
+    fn test_random_branches() {
+        assert_branches(Variation::JuliaN(3., 0.8), [
+            [0.7918440541556883, -0.252862480178795],
+            [-0.17693669557906855, 0.8121883068238843],
+            [-0.6149073585766198, -0.5593258266450891],
+        ]);
+        // the odd branch is mirrored
+        assert_branches(Variation::JuliaScope(3., 0.8), [
+            [0.7918440541556883, -0.252862480178795],
+            [-0.6149073585766196, 0.5593258266450893],
+            [-0.6149073585766198, -0.5593258266450891],
+        ]);
+    }
+
+    #[test]
+    fn test_identity_coefficients() {
+        // Mobius transformations pass zero coefficients `c` and `f`
+        let affine = AffineTransformation::identity();
+        let mut rng = RngType::seed_from_u64(1);
+        for variation in &[Variation::Waves, Variation::Rings, Variation::Fan, Variation::Rings2(0.)] {
+            let out = variation.apply([0.3, -0.4], &affine, &mut rng);
+            assert!(out[0].is_finite() && out[1].is_finite(), "{} is {:?}", variation, out);
+        }
+    }
+}
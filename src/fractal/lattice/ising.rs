@@ -1,17 +1,19 @@
 use crate::numbers::Real;
 use super::{SquareLattice, Boundary};
-use super::LatticeFractal;
+use super::{LatticeFractal, LatticeTypes};
 
 use log::info;
 use serde::{self, Serialize, Deserialize};
 
 use rand::{Rng, SeedableRng};
-use crate::fractal::{FractalBuilder, RngType, default_rng};
+use crate::fractal::{FractalBuilder, RngType, default_rng, stream_seed};
+use crate::fractal::progress::{Progress, RenderControl};
+use crate::error::FractalError;
 
 impl FractalBuilder {
     pub fn ising(self) -> Ising {
         let (w, h) = self.dimensions.unwrap_or((128, 128));
-        Ising::new(w, h, Boundary::Helical, self.seed.unwrap_or(42))
+        Ising::new(w, h, Boundary::Helical, self.seed)
     }
 }
 
@@ -29,6 +31,8 @@ pub struct Ising {
     #[serde(skip)]
     sweep_id: u8,
 
+    #[serde(default)]
+    pub seed: u64,
     #[serde(skip)]
     #[serde(default = "default_rng")]
     rng: RngType,
@@ -44,7 +48,7 @@ impl Ising {
         // start ordered
         // let spins: Vec<i8> = (0..n).map(|_| 1).collect();
         let t = 2.269;
-        let description = format!("Ising model on a {}x{} square lattice at T = {}", w, h, t);
+        let description = Ising::describe((w, h), t);
 
         let cluster_scratch = vec![0; n];
         let sweep_id = 1;
@@ -72,11 +76,21 @@ impl Ising {
             exp_lookup,
             cluster_scratch,
             sweep_id,
-            rng,
+            seed,
+            rng: Ising::dynamics_rng(seed),
             description,
         }
     }
 
+    fn describe(dimensions: (u32, u32), t: Real) -> String {
+        format!("Ising model on a {}x{} square lattice at T = {}", dimensions.0, dimensions.1, t)
+    }
+
+    /// the dynamics use a stream independent of the initial configuration
+    fn dynamics_rng(seed: u64) -> RngType {
+        RngType::seed_from_u64(stream_seed(seed, 0))
+    }
+
     pub fn sweep(&mut self) -> (i32, usize) {
         let num_flip = self.wolff();
         let de = self.single_spinflip();
@@ -115,6 +129,10 @@ impl Ising {
 
         // use a bitmap to remember which spins are already part of the cluster
         // and reset the bitmap before we run out of range
+        // (it is not serialized, so it might be missing after loading from json)
+        if self.cluster_scratch.len() != self.n {
+            self.cluster_scratch = vec![0; self.n];
+        }
         self.sweep_id += 1;
         if self.sweep_id == 255 {
             self.cluster_scratch.fill(0);
@@ -162,18 +180,26 @@ impl Ising {
         ext_energy
     }
 
-    fn equilibrate(&mut self) {
+    fn equilibrate(&mut self, control: &RenderControl) -> Result<(), FractalError> {
         let mut largest_cluster = 0;
         let mut t_eq_try = 100;
+        let mut done = 0;
         while largest_cluster < self.n / 2 {
             for i in 0..t_eq_try {
+                control.check()?;
                 let (de, num_flip) = self.sweep();
                 largest_cluster = std::cmp::max(num_flip, largest_cluster);
                 info!("{}: de = {}, # {}", i, de, num_flip);
+                done += 1;
+                control.report(Progress::Sweeps { done });
             }
             t_eq_try *= 2;
         }
-        self.description = format!("{} (m = {:.2}, E = {:.2})", self.description(), self.magnetization(), self.energy())
+        self.description = format!("{} (m = {:.2}, E = {:.2})",
+                                   Ising::describe(self.lattice.dimensions(), self.t),
+                                   self.magnetization(),
+                                   self.energy());
+        Ok(())
     }
 }
 
@@ -184,12 +210,20 @@ impl LatticeFractal for Ising {
 
     fn render(&mut self, resolution: (u32, u32),
                          _scale: Option<f64>,
-                         _center: Option<(f64, f64)>)
-        -> (Vec<u8>, bool)
+                         _center: Option<(f64, f64)>,
+                         control: &RenderControl)
+        -> Result<(Vec<u8>, bool), FractalError>
     {
         assert_eq!(self.lattice.dimensions(), resolution);
-        self.equilibrate();
-        let buffer: Vec<u8> = self.spins.iter()
+
+        // equilibrate a copy, such that the stored initial configuration
+        // and the seed reproduce this image
+        let mut ising = self.clone();
+        ising.rng = Ising::dynamics_rng(self.seed);
+        ising.equilibrate(control)?;
+        self.description = ising.description;
+
+        let buffer: Vec<u8> = ising.spins.iter()
             .map(|&s| {
                 if s > 0 {
                     vec![0, 0, 0, 255]
@@ -200,10 +234,10 @@ impl LatticeFractal for Ising {
             .flatten()
             .collect();
 
-        (buffer, true)
+        Ok((buffer, true))
     }
 
-    fn get_serializable(&self) -> Ising {
-        self.clone()
+    fn get_serializable(&self) -> LatticeTypes {
+        LatticeTypes::Ising(self.clone())
     }
 }
@@ -4,17 +4,28 @@ mod ising;
 pub use self::square_lattice::{SquareLattice, Boundary};
 pub use self::ising::Ising;
 
+use serde::{self, Serialize, Deserialize};
+
+use crate::error::FractalError;
+use super::progress::RenderControl;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum LatticeTypes {
+    Ising(Ising),
+}
+
 pub trait LatticeFractal : Sync {
     fn description(&self) -> &str;
 
     fn render(&mut self, _resolution: (u32, u32),
                          _scale: Option<f64>,
-                         _center: Option<(f64, f64)>)
-        -> (Vec<u8>, bool)
+                         _center: Option<(f64, f64)>,
+                         _control: &RenderControl)
+        -> Result<(Vec<u8>, bool), FractalError>
     {
         // TODO
         unimplemented!()
     }
 
-    fn get_serializable(&self) -> Ising;
+    fn get_serializable(&self) -> LatticeTypes;
 }
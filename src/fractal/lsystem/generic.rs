@@ -1,5 +1,3 @@
-use serde_json;
-
 use log::info;
 
 use std::f64::consts::PI;
@@ -8,6 +6,7 @@ use itertools::Itertools;
 use serde::{self, Serialize, Deserialize};
 
 use crate::fractal::FractalBuilder;
+use crate::error::{FractalError, from_json};
 
 use super::LSystem;
 use super::turtle::{Turtle, Canvas};
@@ -33,17 +32,17 @@ impl Generic {
         rules: &str,
         angle: f64,
         iterations: Option<u32>
-    ) -> Generic {
+    ) -> Result<Generic, FractalError> {
         let iterations = iterations.unwrap_or(6);
 
         info!("Will render {}, n = {}", description, iterations);
 
-        Generic {
+        Ok(Generic {
             description: description.to_owned(),
             iterations,
-            rules: Lrules::from_string(start, rules),
+            rules: Lrules::from_string(start, rules)?,
             angle,
-        }
+        })
     }
 }
 
@@ -95,7 +94,7 @@ impl FractalBuilder
         };
         let rules = match self.rules {
             Some(m) => m,
-            None => Lrules::random(self.seed)
+            None => Lrules::random(Some(self.seed))
         };
         let angle = match self.angle {
             Some(f) => f,
@@ -123,7 +122,7 @@ impl FractalBuilder
         }
     }
 
-    pub fn lsys_from_json(json: &str) -> Result<Generic, serde_json::Error> {
-        serde_json::from_str(json)
+    pub fn lsys_from_json(json: &str) -> Result<Generic, FractalError> {
+        from_json(json, "L-system")
     }
 }
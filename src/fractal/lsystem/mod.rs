@@ -9,6 +9,9 @@ pub use self::rules::Lrules;
 
 use turtle;
 
+use crate::error::FractalError;
+use super::progress::RenderControl;
+
 pub trait LSystem : Sync {
     fn description(&self) -> &str;
     fn get_canvas(&self) -> turtle::Canvas;
@@ -16,9 +19,12 @@ pub trait LSystem : Sync {
 
     fn render(&mut self, resolution: (u32, u32),
                          _scale: Option<f64>,
-                         _center: Option<(f64, f64)>)
-        -> (Vec<u8>, bool)
+                         _center: Option<(f64, f64)>,
+                         control: &RenderControl)
+        -> Result<(Vec<u8>, bool), FractalError>
     {
-        (self.get_canvas().render(resolution), true)
+        let canvas = self.get_canvas();
+        control.check()?;
+        Ok((canvas.render(resolution), true))
     }
 }
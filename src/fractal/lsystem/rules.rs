@@ -11,11 +11,12 @@ use itertools::Itertools;
 
 use super::Alphabet;
 use crate::fractal::{RngType};
+use crate::error::FractalError;
 
 use serde::{self, Serialize, Deserialize};
 use serde::ser::Serializer;
 use serde::Deserializer;
-use log::error;
+use serde::de::Error;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Lrules {
@@ -36,9 +37,9 @@ where
 {
     let s = String::deserialize(deserializer)?;
     let mut split = s.split('&');
-    let start_string = split.next().unwrap();
-    let rule_string = split.next().unwrap();
-    Ok(Lrules::from_string(start_string, rule_string))
+    let start_string = split.next().unwrap_or("");
+    let rule_string = split.next().ok_or_else(|| D::Error::custom(format!("missing '&' in '{}'", s)))?;
+    Lrules::from_string(start_string, rule_string).map_err(D::Error::custom)
 }
 
 impl Lrules {
@@ -48,7 +49,7 @@ impl Lrules {
     /// e.g. R:+RF-LFL-FR+,L:-LF+RFR+FL-
     /// the rule for F is by default the identity F:F
     /// only upper case letters are valid symbols
-    pub fn from_string(start_string: &str, rule_string: &str) -> Lrules {
+    pub fn from_string(start_string: &str, rule_string: &str) -> Result<Lrules, FractalError> {
         let start = start_string.chars()
                                 .map(Alphabet::new)
                                 .collect();
@@ -64,27 +65,30 @@ impl Lrules {
 
         for rule in rule_string.split(',') {
             let mut it = rule.chars();
-            let key = it.by_ref().find(|&x| x != ' ' && x != '\n').unwrap();
+            let key = it.by_ref()
+                .find(|&x| x != ' ' && x != '\n')
+                .ok_or_else(|| FractalError::InvalidRule(format!("empty rule in '{}'", rule_string)))?;
 
             // jump over :
-            let delimiter = it.by_ref().find(|&x| x != ' ' && x != '\n').unwrap();
+            let delimiter = it.by_ref()
+                .find(|&x| x != ' ' && x != '\n')
+                .ok_or_else(|| FractalError::InvalidRule(format!("missing ':' in '{}'", rule)))?;
             if delimiter != ':' && delimiter != '→' {
-                error!(
-                    "Rule is not valid: not exactly one symbols as key in '{}', but: '{}'",
+                return Err(FractalError::InvalidRule(format!(
+                    "not exactly one symbol as key in '{}', but: '{}'",
                     rule,
                     delimiter
-                );
-                panic!();
+                )));
             }
 
             let rule = it.skip_while(|&x| x == ' ' || x == '\n').collect::<String>();
             rules.insert(Alphabet::new(key), Alphabet::parse(&rule));
         }
 
-        Lrules{
+        Ok(Lrules{
             start,
             rules,
-        }
+        })
     }
 
     pub fn random(seed: Option<u64>) -> Lrules {
@@ -171,6 +175,8 @@ impl fmt::Display for Lrules {
                 _ => ()
             };
         }
+        // the order of a HashMap is random, but serialized rules should not be
+        rule_strings.sort();
         write!(f, "{}", rule_strings.join(", "))
     }
 }
@@ -3,11 +3,12 @@ use std::f64::consts::PI;
 use crate::fractal::FractalBuilder;
 
 use super::Generic;
+use crate::error::FractalError;
 
 
 impl FractalBuilder
 {
-    pub fn bush(&self) -> Generic {
+    pub fn bush(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Bush",
             "F",
@@ -17,7 +18,7 @@ impl FractalBuilder
         )
     }
 
-    pub fn gosper_curve(&self) -> Generic {
+    pub fn gosper_curve(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Gosper curve",
             "A",
@@ -27,7 +28,7 @@ impl FractalBuilder
         )
     }
 
-    pub fn hilbert_curve(&self) -> Generic {
+    pub fn hilbert_curve(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Hilbert curve",
             "L",
@@ -37,7 +38,7 @@ impl FractalBuilder
         )
     }
 
-    pub fn koch_curve(&self) -> Generic {
+    pub fn koch_curve(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Koch curve",
             "F",
@@ -47,7 +48,7 @@ impl FractalBuilder
         )
     }
 
-    pub fn penrose_tiling(&self) -> Generic {
+    pub fn penrose_tiling(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Penrose tiling",
             "+WF--XF---YF--ZF",
@@ -61,7 +62,7 @@ impl FractalBuilder
         )
     }
 
-    pub fn sierpinski_arrowhead(&self) -> Generic {
+    pub fn sierpinski_arrowhead(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Sierpinski arrowhead",
             "A",
@@ -71,7 +72,7 @@ impl FractalBuilder
         )
     }
 
-    pub fn pentigree(&self) -> Generic {
+    pub fn pentigree(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Pentigree",
             "F-F-F-F-F",
@@ -81,7 +82,7 @@ impl FractalBuilder
         )
     }
 
-    pub fn tritile(&self) -> Generic {
+    pub fn tritile(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Tritile",
             "OFF",
@@ -91,7 +92,7 @@ impl FractalBuilder
         )
     }
 
-    pub fn ldragon(&self) -> Generic {
+    pub fn ldragon(&self) -> Result<Generic, FractalError> {
         Generic::from_rules(
             "Dragon curve",
             "FX",
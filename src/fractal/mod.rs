@@ -3,10 +3,19 @@ mod iterated_function_system;
 mod lsystem;
 mod lattice;
 mod quality;
+mod schema;
+mod progress;
+mod supersampling;
+pub mod plugin;
 
 // reexport configuration types
-pub use self::escape_time_fractal::style::Style;
+pub use self::escape_time_fractal::style::{Style, Coloring, Channel, Quantity, Op, Param};
 pub use self::escape_time_fractal::EscapeTypes;
+pub use self::escape_time_fractal::normalization::Normalization;
+pub use self::escape_time_fractal::root_finding::RootFinding;
+pub use self::escape_time_fractal::orbit_trap::{OrbitTrap, TrapKind};
+pub use self::escape_time_fractal::kernel::{mandelbrot_lanes, LANES};
+pub use self::escape_time_fractal::perturbation::{reference_orbit, iterate_delta, precision};
 pub use self::iterated_function_system::variation::Variation;
 pub use self::iterated_function_system::transformation::{Transformation, AffineTransformation};
 pub use self::iterated_function_system::symmetry::Symmetry;
@@ -14,15 +23,16 @@ pub use self::iterated_function_system::fractal_flame::FractalFlame;
 pub use self::iterated_function_system::IterationFractalType;
 pub use self::lsystem::{Alphabet, Lrules, LSystem};
 pub use self::lattice::{LatticeFractal, Ising, Boundary};
+pub use self::schema::upgrade_json;
+pub use self::progress::{Progress, RenderControl, CancellationToken};
+pub use self::supersampling::Supersampling;
 
 use rand_pcg::Pcg32;
 
-use serde_json;
+use log::info;
 
-use log::{info, warn};
-
-use std::io;
 use crate::{histogram::BoundsTypes, png_helper::save_png};
+use crate::error::FractalError;
 
 use crate::FractalType;
 use crate::numbers::{Coef, ComplexFunction};
@@ -36,16 +46,38 @@ fn default_rng() -> RngType {
     RngType::seed_from_u64(SmallRng::from_entropy().gen::<u64>())
 }
 
+/// derive the seed of an independent random stream from the seed of a fractal
+///
+/// Every random decision during rendering uses its own stream, such that the
+/// result does not depend on the order in which the streams are consumed.
+pub fn stream_seed(seed: u64, stream: u64) -> u64 {
+    RngType::new(seed, stream).gen()
+}
+
 enum FractalInstance {
     EscapeTime(Box<dyn escape_time_fractal::EscapeTimeFractal>),
     Ifs(Box<dyn iterated_function_system::IteratedFunctionSystem>),
     LSys(Box<dyn lsystem::LSystem>),
     Lattice(Box<dyn lattice::LatticeFractal>),
+    Plugin(Box<dyn plugin::PluginFractal>),
 }
 
 pub struct Fractal {
     fractal: FractalInstance,
     fractal_type: FractalType,
+    // fractals loaded from json are rendered as they are, without quality checks
+    loaded: bool,
+}
+
+/// a rendered fractal
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA values of the pixels, row by row
+    pub buffer: Vec<u8>,
+    /// whether the image passed the quality estimate
+    pub good: bool,
 }
 
 impl std::fmt::Debug for Fractal {
@@ -55,8 +87,10 @@ impl std::fmt::Debug for Fractal {
 }
 
 pub struct FractalBuilder {
+    // every random decision is derived from the seed
+    seed: u64,
+
     // for iterated function systems
-    seed: Option<u64>,
     variation: Option<Variation>,
     post_transform: Option<Transformation>,
     final_transform: Option<Variation>,
@@ -68,11 +102,26 @@ pub struct FractalBuilder {
     // for escape time
     a: Option<Coef>,
     f: Option<ComplexFunction>,
+    rpn: Option<String>,
+    infix: Option<String>,
     style: Option<Style>,
+    normalization: Option<Normalization>,
+    trap: Option<TrapKind>,
 
     // for mandelbrot
-    zoom: Option<u64>,
+    zoom: Option<f64>,
     center: Option<(f64, f64)>,
+    precise_center: Option<(String, String)>,
+
+    // for multibrot
+    exponent: Option<f64>,
+
+    // for quadratic julia sets
+    parameter: Option<(f64, f64)>,
+
+    // for newton
+    basins: Option<RGB>,
+    root_finding: Option<RootFinding>,
 
     // for L systems
     iterations: Option<u32>,
@@ -80,17 +129,27 @@ pub struct FractalBuilder {
     rules: Option<Lrules>,
     angle: Option<f64>,
 
+    // for lyapunov fractals
+    sequence: Option<String>,
+
     // for quadratic maps
     qmaprule: Option<String>,
 
+    // for buddhabrots
+    limits: Option<[u64; 3]>,
+    anti: Option<bool>,
+
     // for lattices
     dimensions: Option<(u32, u32)>,
+
+    // for plugins
+    params: Option<plugin::PluginParams>,
 }
 
 impl FractalBuilder {
     pub fn new() -> FractalBuilder {
         FractalBuilder {
-            seed: None,
+            seed: SmallRng::from_entropy().gen(),
             variation: None,
             post_transform: None,
             final_transform: None,
@@ -101,30 +160,46 @@ impl FractalBuilder {
 
             a: None,
             f: None,
+            rpn: None,
+            infix: None,
             style: None,
+            normalization: None,
+            trap: None,
 
             zoom: None,
             center: None,
+            precise_center: None,
+
+            exponent: None,
+
+            parameter: None,
+
+            basins: None,
+            root_finding: None,
 
             iterations: None,
             start: None,
             rules: None,
             angle: None,
 
+            sequence: None,
+
             qmaprule: None,
 
+            limits: None,
+            anti: None,
+
             dimensions: None,
+
+            params: None,
         }
     }
     pub fn seed_rng(&self) -> RngType {
-        match self.seed {
-            Some(x) => RngType::seed_from_u64(x),
-            None => RngType::seed_from_u64(SmallRng::from_entropy().gen::<u64>())
-        }
+        RngType::seed_from_u64(self.seed)
     }
 
     pub fn seed(mut self, seed: usize) -> FractalBuilder {
-        self.seed = Some(seed as u64);
+        self.seed = seed as u64;
         self
     }
 
@@ -168,12 +243,19 @@ impl FractalBuilder {
         self
     }
 
+    /// the function is parsed during `build`, which fails on invalid input
     pub fn rpn(mut self, rpn: &Option<String>) -> FractalBuilder {
-        self.f = rpn.clone().map(|x| ComplexFunction::rpn_from_string(&x));
+        self.rpn = rpn.clone();
         self
     }
 
-    pub fn zoom(mut self, zoom: &Option<u64>) -> FractalBuilder {
+    /// the function in infix notation, e.g., `z^3 - 1`, is parsed during `build`
+    pub fn infix(mut self, infix: &Option<String>) -> FractalBuilder {
+        self.infix = infix.clone();
+        self
+    }
+
+    pub fn zoom(mut self, zoom: &Option<f64>) -> FractalBuilder {
         self.zoom = *zoom;
         self
     }
@@ -183,16 +265,73 @@ impl FractalBuilder {
         self
     }
 
+    /// center as decimal strings, which keep their full precision for deep zooms
+    pub fn precise_center(mut self, center: &Option<(String, String)>) -> FractalBuilder {
+        self.precise_center = center.clone();
+        self
+    }
+
+    pub fn exponent(mut self, exponent: &Option<f64>) -> FractalBuilder {
+        self.exponent = *exponent;
+        self
+    }
+
+    /// color newton fractals by their roots, points which do not converge get this color
+    pub fn basins(mut self, non_converged: &Option<RGB>) -> FractalBuilder {
+        self.basins = non_converged.clone();
+        self
+    }
+
+    pub fn root_finding(mut self, root_finding: &Option<RootFinding>) -> FractalBuilder {
+        self.root_finding = *root_finding;
+        self
+    }
+
+    /// the parameter `c` of the quadratic julia set of `z^2 + c`
+    pub fn parameter(mut self, parameter: &Option<(f64, f64)>) -> FractalBuilder {
+        self.parameter = *parameter;
+        self
+    }
+
+    /// the sequence of `A` and `B` of a lyapunov fractal, validated during `build`
+    pub fn sequence(mut self, sequence: &Option<String>) -> FractalBuilder {
+        self.sequence = sequence.clone();
+        self
+    }
+
     pub fn qmaprule(mut self, qmaprule: &Option<String>) -> FractalBuilder {
         self.qmaprule = qmaprule.clone();
         self
     }
 
+    /// iteration limits of the red, green and blue channel of a buddhabrot
+    pub fn limits(mut self, limits: &Option<[u64; 3]>) -> FractalBuilder {
+        self.limits = *limits;
+        self
+    }
+
+    /// count the orbits of a buddhabrot, which do not escape
+    pub fn anti(mut self, anti: &Option<bool>) -> FractalBuilder {
+        self.anti = *anti;
+        self
+    }
+
     pub fn style(mut self, style: &Option<Style>) -> FractalBuilder {
         self.style = style.clone();
         self
     }
 
+    pub fn normalization(mut self, normalization: &Option<Normalization>) -> FractalBuilder {
+        self.normalization = *normalization;
+        self
+    }
+
+    /// color escape time fractals by the closest approach of orbits to a random trap of this kind
+    pub fn trap(mut self, trap: &Option<TrapKind>) -> FractalBuilder {
+        self.trap = *trap;
+        self
+    }
+
     pub fn start(mut self, start: &Option<Vec<Alphabet>>) -> FractalBuilder {
         self.start = start.clone();
         self
@@ -213,12 +352,40 @@ impl FractalBuilder {
         self
     }
 
-    pub fn build(self, fractal_type: &FractalType) -> Fractal {
+    pub fn params(mut self, params: &Option<plugin::PluginParams>) -> FractalBuilder {
+        self.params = params.clone();
+        self
+    }
+
+    pub fn build(mut self, fractal_type: &FractalType) -> Result<Fractal, FractalError> {
+        if let Some(rpn) = self.rpn.take() {
+            self.f = Some(ComplexFunction::rpn_from_string(&rpn)?);
+        }
+        if let Some(infix) = self.infix.take() {
+            self.f = Some(ComplexFunction::from_infix(&infix)?);
+        }
+
+        if let FractalType::LoadJson(ref json) = *fractal_type {
+            let (fractal_type, fractal) = schema::from_json_document(json)?;
+            return Ok(Fractal {
+                fractal,
+                fractal_type,
+                loaded: true,
+            })
+        }
 
         let instance = match *fractal_type {
             FractalType::Newton => FractalInstance::EscapeTime(Box::new(self.newton())),
             FractalType::Julia => FractalInstance::EscapeTime(Box::new(self.julia())),
-            FractalType::Mandelbrot => FractalInstance::EscapeTime(Box::new(self.mandelbrot())),
+            FractalType::QuadraticJulia => FractalInstance::EscapeTime(Box::new(self.quadratic_julia())),
+            FractalType::Mandelbrot => FractalInstance::EscapeTime(Box::new(self.mandelbrot()?)),
+            FractalType::BurningShip => FractalInstance::EscapeTime(Box::new(self.burning_ship())),
+            FractalType::Tricorn => FractalInstance::EscapeTime(Box::new(self.tricorn())),
+            FractalType::Multibrot => FractalInstance::EscapeTime(Box::new(self.multibrot()?)),
+            FractalType::Phoenix => FractalInstance::EscapeTime(Box::new(self.phoenix())),
+            FractalType::Nova => FractalInstance::EscapeTime(Box::new(self.nova())),
+            FractalType::Lyapunov => FractalInstance::EscapeTime(Box::new(self.lyapunov()?)),
+            FractalType::GenericMandelbrot => FractalInstance::EscapeTime(Box::new(self.generic_mandelbrot())),
             FractalType::HeighwayDragon => FractalInstance::Ifs(Box::new(self.heighway_dragon())),
             FractalType::BarnsleyFern => FractalInstance::Ifs(Box::new(self.barnsley_fern())),
             FractalType::SierpinskiGasket => FractalInstance::Ifs(Box::new(self.sierpinski_gasket())),
@@ -227,30 +394,36 @@ impl FractalBuilder {
             FractalType::AppolonianGasket => FractalInstance::Ifs(Box::new(self.appolonian_gasket())),
             FractalType::MobiusFlame => FractalInstance::Ifs(Box::new(self.mobius_flame())),
             FractalType::FractalFlame => FractalInstance::Ifs(Box::new(self.fractal_flame())),
-            FractalType::KochCurve => FractalInstance::LSys(Box::new(self.koch_curve())),
-            FractalType::SierpinskiArrowhead => FractalInstance::LSys(Box::new(self.sierpinski_arrowhead())),
-            FractalType::HilbertCurve => FractalInstance::LSys(Box::new(self.hilbert_curve())),
-            FractalType::GosperCurve => FractalInstance::LSys(Box::new(self.gosper_curve())),
-            FractalType::Bush => FractalInstance::LSys(Box::new(self.bush())),
-            FractalType::PenroseTiling => FractalInstance::LSys(Box::new(self.penrose_tiling())),
-            FractalType::Pentigree => FractalInstance::LSys(Box::new(self.pentigree())),
-            FractalType::Tritile => FractalInstance::LSys(Box::new(self.tritile())),
-            FractalType::LDragon => FractalInstance::LSys(Box::new(self.ldragon())),
+            FractalType::KochCurve => FractalInstance::LSys(Box::new(self.koch_curve()?)),
+            FractalType::SierpinskiArrowhead => FractalInstance::LSys(Box::new(self.sierpinski_arrowhead()?)),
+            FractalType::HilbertCurve => FractalInstance::LSys(Box::new(self.hilbert_curve()?)),
+            FractalType::GosperCurve => FractalInstance::LSys(Box::new(self.gosper_curve()?)),
+            FractalType::Bush => FractalInstance::LSys(Box::new(self.bush()?)),
+            FractalType::PenroseTiling => FractalInstance::LSys(Box::new(self.penrose_tiling()?)),
+            FractalType::Pentigree => FractalInstance::LSys(Box::new(self.pentigree()?)),
+            FractalType::Tritile => FractalInstance::LSys(Box::new(self.tritile()?)),
+            FractalType::LDragon => FractalInstance::LSys(Box::new(self.ldragon()?)),
             FractalType::RandomLSystem => FractalInstance::LSys(Box::new(self.generic())),
             FractalType::Ising => FractalInstance::Lattice(Box::new(self.ising())),
-            FractalType::QuadraticMap => FractalInstance::Ifs(Box::new(self.quadratic_map())),
+            FractalType::QuadraticMap => FractalInstance::Ifs(Box::new(self.quadratic_map()?)),
             FractalType::Lorenz => FractalInstance::Ifs(Box::new(self.lorenz())),
             FractalType::Rossler => FractalInstance::Ifs(Box::new(self.rossler())),
             FractalType::Thomas => FractalInstance::Ifs(Box::new(self.thomas())),
             FractalType::DoublePendulum => FractalInstance::Ifs(Box::new(self.double_pendulum())),
-            FractalType::Random => unreachable!(),
-            FractalType::LoadJson(ref json) => FractalInstance::guess_fractal_from_json(json),
+            FractalType::Buddhabrot => FractalInstance::Ifs(Box::new(self.buddhabrot())),
+            FractalType::JuliaIim => FractalInstance::Ifs(Box::new(self.julia_iim())),
+            FractalType::Plugin(ref name) => FractalInstance::Plugin(
+                plugin::find(name)?.build(&self.params.unwrap_or_default(), self.seed)?
+            ),
+            FractalType::Random => return Err(FractalError::Unsupported(fractal_type.clone())),
+            FractalType::LoadJson(_) => unreachable!("loaded above"),
         };
 
-        Fractal {
+        Ok(Fractal {
             fractal: instance,
             fractal_type: fractal_type.clone(),
-        }
+            loaded: false,
+        })
     }
 }
 
@@ -261,43 +434,59 @@ impl Default for FractalBuilder {
 }
 
 impl Fractal {
-    pub fn render(&mut self, resolution: (u32, u32), filename: &str, supersampling: bool) -> io::Result<bool> {
+    fn render_buffer(&mut self, resolution: (u32, u32), draft: bool, supersampling: Supersampling, control: &RenderControl)
+        -> Result<Image, FractalError>
+    {
         let (buffer, good) = match self.fractal {
-            FractalInstance::EscapeTime(ref mut f) => f.render(resolution, None, None),
-            FractalInstance::Ifs(ref mut f) => f.render(
-                resolution,
-                f.suggested_iterations(),
-                f.suggested_parallelism(),
-                supersampling
-            ),
-            FractalInstance::LSys(ref mut f) => f.render(resolution, None, None),
-            FractalInstance::Lattice(ref mut f) => f.render(resolution, None, None),
+            FractalInstance::EscapeTime(ref mut f) => f.render(resolution, None, None, supersampling, control)?,
+            FractalInstance::Ifs(ref mut f) => {
+                let iterations = if draft {
+                    f.suggested_iterations_draft()
+                } else {
+                    f.suggested_iterations()
+                };
+                f.render(
+                    resolution,
+                    iterations,
+                    f.partitions(),
+                    supersampling.is_enabled() && !draft,
+                    control
+                )?
+            },
+            FractalInstance::LSys(ref mut f) => f.render(resolution, None, None, control)?,
+            FractalInstance::Lattice(ref mut f) => f.render(resolution, None, None, control)?,
+            FractalInstance::Plugin(ref mut f) => f.render(resolution, control)?,
         };
 
-        let (x, y) = resolution;
-        save_png(filename, x, y, &buffer)?;
+        let (width, height) = resolution;
+        Ok(Image {
+            width,
+            height,
+            buffer,
+            good,
+        })
+    }
 
-        Ok(good)
+    /// render into memory, `control` can be used to monitor and abort the render
+    pub fn render_to_image(&mut self, resolution: (u32, u32), supersampling: Supersampling, control: &RenderControl)
+        -> Result<Image, FractalError>
+    {
+        self.render_buffer(resolution, false, supersampling, control)
     }
 
-    /// same as render, but faster and lower quality
-    pub fn render_draft(&mut self, resolution: (u32, u32), filename: &str) -> io::Result<bool> {
-        let (buffer, good) = match self.fractal {
-            FractalInstance::EscapeTime(ref mut f) => f.render(resolution, None, None),
-            FractalInstance::Ifs(ref mut f) => f.render(
-                resolution,
-                f.suggested_iterations_draft(),
-                f.suggested_parallelism(),
-                false
-            ),
-            FractalInstance::LSys(ref mut f) => f.render(resolution, None, None),
-            FractalInstance::Lattice(ref mut f) => f.render(resolution, None, None),
-        };
+    pub fn render(&mut self, resolution: (u32, u32), filename: &str, supersampling: Supersampling) -> Result<bool, FractalError> {
+        let image = self.render_to_image(resolution, supersampling, &RenderControl::new())?;
+        save_png(filename, image.width, image.height, &image.buffer)?;
+
+        Ok(image.good)
+    }
 
-        let (x, y) = resolution;
-        save_png(filename, x, y, &buffer)?;
+    /// same as render, but faster and lower quality
+    pub fn render_draft(&mut self, resolution: (u32, u32), filename: &str) -> Result<bool, FractalError> {
+        let image = self.render_buffer(resolution, true, Supersampling::None, &RenderControl::new())?;
+        save_png(filename, image.width, image.height, &image.buffer)?;
 
-        Ok(good)
+        Ok(image.good)
     }
 
     pub fn description(&self) -> &str {
@@ -306,35 +495,20 @@ impl Fractal {
             FractalInstance::Ifs(ref f) => f.description(),
             FractalInstance::LSys(ref f) => f.description(),
             FractalInstance::Lattice(ref f) => f.description(),
+            FractalInstance::Plugin(ref f) => f.description(),
         }
     }
 
+    /// serialize the fractal as a tagged and versioned json document
     pub fn json(&self) -> String {
-        match self.fractal {
-            FractalInstance::EscapeTime(ref f) => {
-                serde_json::to_string(&f.get_serializable())
-                    .unwrap_or_else(|_| panic!("Escape: {:#?}", &f.get_serializable())
-                )
-            },
-            FractalInstance::Ifs(ref f) => {
-                serde_json::to_string(&f.get_serializable())
-                    .unwrap_or_else(|_| panic!("IFS: {:#?}", &f.get_serializable())
-                )
-            },
-            FractalInstance::LSys(ref f) => {
-                serde_json::to_string(&f.get_serializable())
-                    .unwrap_or_else(|_| panic!("Lsys: {:#?}", &f.get_serializable())
-                )
-            },
-            FractalInstance::Lattice(ref f) => {
-                serde_json::to_string(&f.get_serializable())
-                    .unwrap_or_else(|_| panic!("Lattice: {:#?}", &f.get_serializable())
-                )
-            },
-        }
+        schema::to_json(&self.fractal_type, &self.fractal)
     }
 
     pub fn estimate_quality_before(&mut self) -> bool {
+        if self.loaded {
+            return true
+        }
+
         match self.fractal_type {
             FractalType::FractalFlame | FractalType::QuadraticMap => {
                 match self.fractal {
@@ -342,24 +516,30 @@ impl Fractal {
                     _ => unreachable!(),
                 }
             },
+            FractalType::Plugin(_) => {
+                match self.fractal {
+                    FractalInstance::Plugin(ref mut f) => f.estimate_quality_before(),
+                    _ => unreachable!(),
+                }
+            },
             _ => true,
         }
     }
 
-    pub fn combine(&self, other: &Fractal) -> Result<Fractal, ()> {
+    pub fn combine(&self, other: &Fractal) -> Result<Fractal, FractalError> {
         // both need to be IFS
         let (f1, f2) = match (&self.fractal, &other.fractal) {
             (&FractalInstance::Ifs(ref a), &FractalInstance::Ifs(ref b)) => (a, b),
-            _ => return Err(()),
+            _ => return Err(FractalError::NotCombinable),
         };
 
         let f1_config = match f1.get_serializable() {
             IterationFractalType::IFS(x) => x,
-            _ => return Err(())
+            _ => return Err(FractalError::NotCombinable)
         };
         let f2_config = match f2.get_serializable() {
             IterationFractalType::IFS(x) => x,
-            _ => return Err(())
+            _ => return Err(FractalError::NotCombinable)
         };
 
         fn count_trafo(c: &FractalFlame) -> usize {
@@ -406,66 +586,11 @@ impl Fractal {
 
         // TODO: add a symmetry from f1 or f2 (if applicable)
 
-
-        // FIXME ugly detour over json string
-        let json = serde_json::to_string(&f_config).unwrap();
-        Ok(FractalBuilder::new().build(&FractalType::LoadJson(json)))
-    }
-}
-
-impl FractalInstance {
-    // FIXME This has to be replaced by a better approach
-    fn guess_fractal_from_json(json: &str) -> FractalInstance {
-        let ifs = FractalBuilder::ifs_from_json(json);
-        let lsys = FractalBuilder::lsys_from_json(json);
-        let escape_type = FractalBuilder::escape_type_from_json(json);
-
-        if let Ok(ft) = ifs {
-             match ft {
-                IterationFractalType::IFS(x) => FractalInstance::Ifs(Box::new(x)),
-                IterationFractalType::QuadraticMap(x) => FractalInstance::Ifs(Box::new(x)),
-                IterationFractalType::OdeFractal(x) => FractalInstance::Ifs(Box::new(x)),
-                IterationFractalType::None => panic!("invalid json")
-            }
-        } else if let Ok(ft) = lsys {
-            FractalInstance::LSys(Box::new(ft))
-        } else if let Ok(ft) = escape_type {
-            match ft {
-                EscapeTypes::Mandelbrot(x) => FractalInstance::EscapeTime(Box::new(x)),
-                EscapeTypes::Newton(x) => FractalInstance::EscapeTime(Box::new(x)),
-                EscapeTypes::None => panic!("invalid json")
-            }
-        } else {
-            warn!("offending json: {}", json);
-            let jd = &mut serde_json::Deserializer::from_str(json);
-            let ifs: Result<FractalFlame, _> = serde_path_to_error::deserialize(jd);
-            match ifs {
-                Ok(_) => panic!("expected an error: invalid json"),
-                Err(err) => {
-                    let path = err.path().to_string();
-                    warn!("Not a IFS: {}", path)
-                }
-            }
-            let jd = &mut serde_json::Deserializer::from_str(json);
-            let lsys: Result<FractalFlame, _> = serde_path_to_error::deserialize(jd);
-            match lsys {
-                Ok(_) => panic!("expected an error: invalid json"),
-                Err(err) => {
-                    let path = err.path().to_string();
-                    warn!("Not an LSystem: {}", path)
-                }
-            }
-            let jd = &mut serde_json::Deserializer::from_str(json);
-            let escape: Result<FractalFlame, _> = serde_path_to_error::deserialize(jd);
-            match escape {
-                Ok(_) => panic!("expected an error: invalid json"),
-                Err(err) => {
-                    let path = err.path().to_string();
-                    warn!("Not an escape: {}", path)
-                }
-            }
-            panic!("invalid json");
-        }
+        Ok(Fractal {
+            fractal: FractalInstance::Ifs(Box::new(f_config)),
+            fractal_type: FractalType::FractalFlame,
+            loaded: false,
+        })
     }
 }
 
@@ -473,18 +598,17 @@ pub fn render_wrapper(
                         fractal: &mut Fractal,
                         filename: &str,
                         dim: &(u32, u32),
-                        supersampling: bool
+                        supersampling: Supersampling
                      )
-                     -> (bool, String, String)
+                     -> Result<(bool, String, String), FractalError>
 {
     // for some fractals, we can estimate if it will look good
     // so abort, if not before rendering
     if ! fractal.estimate_quality_before() {
-        return (false, "".to_string(), "".to_string())
+        return Ok((false, "".to_string(), "".to_string()))
     }
 
-    let finished = fractal.render(*dim, filename, supersampling)
-                          .expect("creation of fractal failed");
+    let finished = fractal.render(*dim, filename, supersampling)?;
 
     let description = fractal.description().to_owned();
     info!("{}", description);
@@ -495,16 +619,25 @@ pub fn render_wrapper(
     // quality of an image, maybe do an FFT and look for intermediate frequencies?
 
     let finished = match fractal.fractal_type {
+        _ if fractal.loaded => true,
           FractalType::FractalFlame
         | FractalType::MobiusFlame
         | FractalType::Newton
         | FractalType::Mandelbrot
         | FractalType::Julia
+        | FractalType::QuadraticJulia
+        | FractalType::BurningShip
+        | FractalType::Tricorn
+        | FractalType::Multibrot
+        | FractalType::Phoenix
+        | FractalType::Nova
+        | FractalType::GenericMandelbrot
+        | FractalType::Plugin(_)
           => finished,
         _ => true
     };
 
-    (finished, description, json)
+    Ok((finished, description, json))
 }
 
 pub fn render_draft(
@@ -512,21 +645,20 @@ pub fn render_draft(
                         filename: &str,
                         dim: &(u32, u32),
                    )
-                     -> String
+                     -> Result<String, FractalError>
 {
     // for some fractals, we can estimate if it will look good
     // so abort, if not before rendering
     if ! fractal.estimate_quality_before() {
-        return "".to_string()
+        return Ok("".to_string())
     }
 
-    fractal.render_draft(*dim, filename)
-           .expect("creation of fractal failed");
+    fractal.render_draft(*dim, filename)?;
 
-    fractal.json()
+    Ok(fractal.json())
 }
 
-use crate::color::{RGBA, HSV, color_variance};
+use crate::color::{RGB, RGBA, HSV, color_variance};
 
 pub fn estimate_quality_after(rgb: &[RGBA], _resolution: &(u32, u32)) -> bool {
     let hsv: Vec<HSV> = rgb.iter().map(|c| c.blend_black().to_hsv()).collect();
@@ -538,11 +670,12 @@ pub fn estimate_quality_after(rgb: &[RGBA], _resolution: &(u32, u32)) -> bool {
 
 #[test]
 fn test_json() {
-    let f = FractalBuilder::new().build(&FractalType::RandomLSystem);
+    let f = FractalBuilder::new().build(&FractalType::RandomLSystem).unwrap();
     println!("Lsystem: {:?}", f);
     let json = f.json();
     println!("json: {:?}", json);
-    let g: lsystem::Generic = serde_json::from_str(&json).unwrap();
+    let g = FractalBuilder::new().build(&FractalType::LoadJson(json)).unwrap();
     println!("Lsystem: {:?}", g);
     println!("Lsystem: {:?}", g.description());
+    assert_eq!(f.description(), g.description());
 }
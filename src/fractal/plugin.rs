@@ -0,0 +1,106 @@
+//! Fractal types provided by other crates.
+//!
+//! A crate implements `FractalPlugin` and calls `register` before any
+//! fractal is built. Registered types are addressed as
+//! `FractalType::Plugin(name)`, they can be loaded from json and are
+//! eligible for the random selection of `random_type`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use serde_json::Value;
+
+use crate::FractalType;
+use crate::error::FractalError;
+use super::progress::RenderControl;
+
+/// free form parameters of a plugin, e.g., from `--param key=value`
+pub type PluginParams = HashMap<String, String>;
+
+/// a fractal type, which can be registered at runtime
+pub trait FractalPlugin : Send + Sync {
+    /// the unique name, used on the command line and as type tag in json documents
+    fn name(&self) -> &str;
+
+    /// build a new fractal, every random decision should be derived from `seed`
+    fn build(&self, params: &PluginParams, seed: u64) -> Result<Box<dyn PluginFractal>, FractalError>;
+
+    /// restore a fractal from the value written by `PluginFractal::to_value`
+    fn load(&self, value: Value) -> Result<Box<dyn PluginFractal>, FractalError>;
+
+    /// whether the type may be chosen if the user does not ask for a specific one
+    fn random(&self) -> bool {
+        true
+    }
+}
+
+/// an instance of a fractal type provided by a plugin
+pub trait PluginFractal : Send + Sync {
+    fn description(&self) -> &str;
+
+    /// render into an RGBA buffer and tell whether the image looks good
+    fn render(&mut self, resolution: (u32, u32), control: &RenderControl)
+        -> Result<(Vec<u8>, bool), FractalError>;
+
+    /// the configuration, which is saved in the json document
+    fn to_value(&self) -> Result<Value, serde_json::Error>;
+
+    /// cheap check, whether rendering is worth it
+    fn estimate_quality_before(&mut self) -> bool {
+        true
+    }
+}
+
+lazy_static! {
+    static ref REGISTRY: RwLock<Vec<Arc<dyn FractalPlugin>>> = RwLock::new(Vec::new());
+}
+
+/// make a fractal type available under its name
+pub fn register<P>(plugin: P) -> Result<(), FractalError>
+    where P: FractalPlugin + 'static
+{
+    let mut registry = REGISTRY.write().expect("plugin registry is poisoned");
+    if registry.iter().any(|p| p.name() == plugin.name()) {
+        return Err(FractalError::DuplicatePlugin(plugin.name().to_string()))
+    }
+    registry.push(Arc::new(plugin));
+    Ok(())
+}
+
+/// names of all registered plugins, in the order of registration
+pub fn registered() -> Vec<String> {
+    REGISTRY.read()
+            .expect("plugin registry is poisoned")
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect()
+}
+
+pub(super) fn find(name: &str) -> Result<Arc<dyn FractalPlugin>, FractalError> {
+    REGISTRY.read()
+            .expect("plugin registry is poisoned")
+            .iter()
+            .find(|p| p.name() == name)
+            .cloned()
+            .ok_or_else(|| FractalError::UnknownPlugin(name.to_string()))
+}
+
+/// choose a type for `FractalType::Random`, including registered plugins
+pub fn random_type(seed: usize) -> FractalType {
+    let mut candidates = vec![
+        FractalType::Newton,
+        FractalType::FractalFlame,
+        FractalType::MobiusFlame,
+        FractalType::QuadraticMap,
+    ];
+    candidates.extend(
+        REGISTRY.read()
+                .expect("plugin registry is poisoned")
+                .iter()
+                .filter(|p| p.random())
+                .map(|p| FractalType::Plugin(p.name().to_string()))
+    );
+
+    candidates[seed % candidates.len()].clone()
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::FractalError;
+
+/// progress of a running render
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Progress {
+    /// samples fed into the histogram of an iterated function system
+    Samples { done: usize, total: usize },
+    /// rows of an escape time fractal
+    Rows { done: usize, total: usize },
+    /// sweeps of a lattice model, the total number is not known in advance
+    Sweeps { done: usize },
+}
+
+/// a token to abort a running render from another thread
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// request the render to stop as soon as possible
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// callbacks and cancellation for a render, which are shared with all worker threads
+#[derive(Clone, Default)]
+pub struct RenderControl {
+    progress: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+    cancellation: CancellationToken,
+}
+
+impl fmt::Debug for RenderControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RenderControl (cancelled: {})", self.cancellation.is_cancelled())
+    }
+}
+
+impl RenderControl {
+    pub fn new() -> RenderControl {
+        RenderControl::default()
+    }
+
+    /// `callback` will be called from the worker threads whenever a chunk of work is done
+    pub fn on_progress<F>(mut self, callback: F) -> RenderControl
+        where F: Fn(Progress) + Send + Sync + 'static
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn cancellation(mut self, token: &CancellationToken) -> RenderControl {
+        self.cancellation = token.clone();
+        self
+    }
+
+    pub fn report(&self, progress: Progress) {
+        if let Some(ref callback) = self.progress {
+            callback(progress)
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// convenience to abort with `?` at checkpoints
+    pub fn check(&self) -> Result<(), FractalError> {
+        if self.is_cancelled() {
+            Err(FractalError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! The json representation of a fractal.
+//!
+//! Every fractal is saved as a document tagged with its `type` and the
+//! `version` of the format, such that we know how to read it back.
+//! Files written before the introduction of the tagged format do not
+//! carry this information, they are recognized by trial and error.
+
+use serde::{self, Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use log::warn;
+
+use crate::FractalType;
+use crate::error::{FractalError, from_json};
+
+use super::{FractalBuilder, FractalInstance};
+use super::EscapeTypes;
+use super::IterationFractalType;
+use super::lattice::LatticeTypes;
+use super::iterated_function_system::ode::OdeTypes;
+
+/// version of the json format written by this version of the crate
+///
+/// Version 0 are the legacy files without a tag.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FractalDocument {
+    version: u32,
+    #[serde(rename = "type")]
+    fractal_type: FractalType,
+    fractal: Value,
+}
+
+/// deserialize `value`, which is located at `fractal` in the document
+fn from_value<T>(value: Value, family: &'static str) -> Result<T, FractalError>
+    where T: DeserializeOwned
+{
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        FractalError::Json {
+            family,
+            path: format!("fractal.{}", err.path()),
+            message: err.inner().to_string(),
+        }
+    })
+}
+
+impl FractalInstance {
+    /// the serializable configuration, without the wrapping enum
+    fn to_value(&self) -> Result<Value, serde_json::Error> {
+        match self {
+            FractalInstance::EscapeTime(f) => match f.get_serializable() {
+                EscapeTypes::Newton(x) => serde_json::to_value(x),
+                EscapeTypes::Julia(x) => serde_json::to_value(x),
+                EscapeTypes::Mandelbrot(x) => serde_json::to_value(x),
+                EscapeTypes::BurningShip(x) => serde_json::to_value(x),
+                EscapeTypes::Tricorn(x) => serde_json::to_value(x),
+                EscapeTypes::Multibrot(x) => serde_json::to_value(x),
+                EscapeTypes::Phoenix(x) => serde_json::to_value(x),
+                EscapeTypes::Nova(x) => serde_json::to_value(x),
+                EscapeTypes::Lyapunov(x) => serde_json::to_value(x),
+                EscapeTypes::GenericMandelbrot(x) => serde_json::to_value(x),
+                EscapeTypes::None => Ok(Value::Null),
+            },
+            FractalInstance::Ifs(f) => match f.get_serializable() {
+                IterationFractalType::IFS(x) => serde_json::to_value(x),
+                IterationFractalType::QuadraticMap(x) => serde_json::to_value(x),
+                IterationFractalType::OdeFractal(x) => serde_json::to_value(x),
+                IterationFractalType::Buddhabrot(x) => serde_json::to_value(x),
+                IterationFractalType::JuliaIim(x) => serde_json::to_value(x),
+                IterationFractalType::None => Ok(Value::Null),
+            },
+            FractalInstance::LSys(f) => serde_json::to_value(f.get_serializable()),
+            FractalInstance::Lattice(f) => match f.get_serializable() {
+                LatticeTypes::Ising(x) => serde_json::to_value(x),
+            },
+            FractalInstance::Plugin(f) => f.to_value(),
+        }
+    }
+
+    /// build the instance from the configuration of a tagged document
+    fn from_value(fractal_type: &FractalType, value: Value) -> Result<FractalInstance, FractalError> {
+        let instance = match fractal_type {
+            FractalType::Newton => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::newton::NewtonFractal>(value, "Newton fractal")?
+            )),
+              FractalType::Julia
+            | FractalType::QuadraticJulia => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::julia::JuliaFractal>(value, "Julia fractal")?
+            )),
+            FractalType::Mandelbrot => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::mandelbrot::MandelbrotFractal>(value, "Mandelbrot fractal")?
+            )),
+            FractalType::BurningShip => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::quadratic::BurningShipFractal>(value, "Burning Ship fractal")?
+            )),
+            FractalType::Tricorn => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::quadratic::TricornFractal>(value, "Tricorn fractal")?
+            )),
+            FractalType::Multibrot => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::multibrot::MultibrotFractal>(value, "Multibrot fractal")?
+            )),
+            FractalType::Phoenix => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::phoenix::PhoenixFractal>(value, "Phoenix fractal")?
+            )),
+            FractalType::Nova => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::nova::NovaFractal>(value, "Nova fractal")?
+            )),
+            FractalType::Lyapunov => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::lyapunov::LyapunovFractal>(value, "Lyapunov fractal")?
+            )),
+            FractalType::GenericMandelbrot => FractalInstance::EscapeTime(Box::new(
+                from_value::<super::escape_time_fractal::generic_mandelbrot::GenericMandelbrotFractal>(value, "generic Mandelbrot fractal")?
+            )),
+              FractalType::HeighwayDragon
+            | FractalType::BarnsleyFern
+            | FractalType::SierpinskiGasket
+            | FractalType::SierpinskiPentagon
+            | FractalType::PythagoreanTree
+            | FractalType::AppolonianGasket
+            | FractalType::MobiusFlame
+            | FractalType::FractalFlame => FractalInstance::Ifs(Box::new(
+                from_value::<super::FractalFlame>(value, "fractal flame")?
+            )),
+            FractalType::QuadraticMap => FractalInstance::Ifs(Box::new(
+                from_value::<super::iterated_function_system::quadratic_map::QuadraticMap>(value, "quadratic map")?
+            )),
+              FractalType::Lorenz
+            | FractalType::Rossler
+            | FractalType::Thomas
+            | FractalType::DoublePendulum => FractalInstance::Ifs(Box::new(
+                from_value::<super::iterated_function_system::ode::OdeFractal>(value, "ode fractal")?
+            )),
+            FractalType::Buddhabrot => FractalInstance::Ifs(Box::new(
+                from_value::<super::iterated_function_system::buddhabrot::Buddhabrot>(value, "Buddhabrot")?
+            )),
+            FractalType::JuliaIim => FractalInstance::Ifs(Box::new(
+                from_value::<super::iterated_function_system::julia_iim::JuliaIim>(value, "inverse iteration Julia set")?
+            )),
+              FractalType::KochCurve
+            | FractalType::SierpinskiArrowhead
+            | FractalType::HilbertCurve
+            | FractalType::GosperCurve
+            | FractalType::Bush
+            | FractalType::PenroseTiling
+            | FractalType::Pentigree
+            | FractalType::Tritile
+            | FractalType::LDragon
+            | FractalType::RandomLSystem => FractalInstance::LSys(Box::new(
+                from_value::<super::lsystem::Generic>(value, "L-system")?
+            )),
+            FractalType::Ising => FractalInstance::Lattice(Box::new(
+                from_value::<super::Ising>(value, "Ising model")?
+            )),
+            FractalType::Plugin(name) => FractalInstance::Plugin(
+                super::plugin::find(name)?.load(value)?
+            ),
+            FractalType::Random | FractalType::LoadJson(_)
+                => return Err(FractalError::Unsupported(fractal_type.clone())),
+        };
+
+        Ok(instance)
+    }
+}
+
+/// serialize a fractal as a tagged document of the current version
+pub(super) fn to_json(fractal_type: &FractalType, instance: &FractalInstance) -> String {
+    let fractal = instance.to_value()
+        .unwrap_or_else(|e| panic!("{}: can not serialize {}", fractal_type, e));
+
+    let document = FractalDocument {
+        version: VERSION,
+        fractal_type: fractal_type.clone(),
+        fractal,
+    };
+
+    serde_json::to_string(&document)
+        .unwrap_or_else(|e| panic!("{}: can not serialize {}", fractal_type, e))
+}
+
+/// read a fractal from json, either a tagged document or a legacy file
+pub(super) fn from_json_document(json: &str) -> Result<(FractalType, FractalInstance), FractalError> {
+    let value: Value = from_json(json, "json document")?;
+
+    if value.get("version").is_none() {
+        return guess_legacy(json)
+    }
+
+    let document: FractalDocument = serde_path_to_error::deserialize(value).map_err(|err| {
+        FractalError::Json {
+            family: "fractal document",
+            path: err.path().to_string(),
+            message: err.inner().to_string(),
+        }
+    })?;
+
+    if document.version > VERSION {
+        return Err(FractalError::UnsupportedVersion(document.version))
+    }
+
+    let instance = FractalInstance::from_value(&document.fractal_type, document.fractal)?;
+
+    Ok((document.fractal_type, instance))
+}
+
+/// convert a json file of any known version into a tagged document of the current version
+pub fn upgrade_json(json: &str) -> Result<String, FractalError> {
+    let (fractal_type, instance) = from_json_document(json)?;
+    Ok(to_json(&fractal_type, &instance))
+}
+
+/// legacy files are untagged, try every family until one matches
+fn guess_legacy(json: &str) -> Result<(FractalType, FractalInstance), FractalError> {
+    let ifs = FractalBuilder::ifs_from_json(json);
+    let lsys = FractalBuilder::lsys_from_json(json);
+    let escape_type = FractalBuilder::escape_type_from_json(json);
+    let ising = from_json::<super::Ising>(json, "Ising model");
+
+    let empty = |family| FractalError::Json {
+        family,
+        path: ".".to_string(),
+        message: "the fractal is empty".to_string()
+    };
+
+    match (ifs, lsys, escape_type, ising) {
+        (Ok(ft), _, _, _) => match ft {
+            IterationFractalType::IFS(x) => Ok((FractalType::FractalFlame, FractalInstance::Ifs(Box::new(x)))),
+            IterationFractalType::QuadraticMap(x) => Ok((FractalType::QuadraticMap, FractalInstance::Ifs(Box::new(x)))),
+            IterationFractalType::OdeFractal(x) => {
+                let fractal_type = match x.ode {
+                    OdeTypes::Lorenz(_) => FractalType::Lorenz,
+                    OdeTypes::Rossler(_) => FractalType::Rossler,
+                    OdeTypes::Thomas(_) => FractalType::Thomas,
+                    OdeTypes::DoublePendulum(_) => FractalType::DoublePendulum,
+                };
+                Ok((fractal_type, FractalInstance::Ifs(Box::new(x))))
+            },
+            IterationFractalType::Buddhabrot(x) => Ok((FractalType::Buddhabrot, FractalInstance::Ifs(Box::new(x)))),
+            IterationFractalType::JuliaIim(x) => Ok((FractalType::JuliaIim, FractalInstance::Ifs(Box::new(x)))),
+            IterationFractalType::None => Err(empty("iterated function system")),
+        },
+        (_, Ok(ft), _, _) => Ok((FractalType::RandomLSystem, FractalInstance::LSys(Box::new(ft)))),
+        (_, _, Ok(ft), _) => match ft {
+            EscapeTypes::Mandelbrot(x) => Ok((FractalType::Mandelbrot, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::Newton(x) => Ok((FractalType::Newton, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::Julia(x) => Ok((FractalType::Julia, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::BurningShip(x) => Ok((FractalType::BurningShip, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::Tricorn(x) => Ok((FractalType::Tricorn, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::Multibrot(x) => Ok((FractalType::Multibrot, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::Phoenix(x) => Ok((FractalType::Phoenix, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::Nova(x) => Ok((FractalType::Nova, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::Lyapunov(x) => Ok((FractalType::Lyapunov, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::GenericMandelbrot(x) => Ok((FractalType::GenericMandelbrot, FractalInstance::EscapeTime(Box::new(x)))),
+            EscapeTypes::None => Err(empty("escape time fractal")),
+        },
+        (_, _, _, Ok(x)) => Ok((FractalType::Ising, FractalInstance::Lattice(Box::new(x)))),
+        (Err(e1), Err(e2), Err(e3), Err(e4)) => {
+            warn!("offending json: {}", json);
+            let errors = vec![e1, e2, e3, e4];
+            for e in &errors {
+                warn!("{}", e);
+            }
+            Err(FractalError::UnknownJson(errors))
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::{Rng, SeedableRng};
+use serde::{self, Serialize, Deserialize};
+
+use crate::error::FractalError;
+use super::{RngType, stream_seed};
+
+/// how many samples are averaged into one pixel
+///
+/// Iterated function systems always sample at twice the resolution,
+/// if any supersampling is requested.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Supersampling {
+    None,
+    /// a regular grid of n x n samples in every pixel
+    Grid(u32),
+    /// n x n samples, each at a random position in its cell of the grid
+    Jittered(u32),
+    /// like `Jittered`, but only for pixels which differ from their neighbors
+    Adaptive(u32),
+}
+
+// `#[default]` on enum variants needs a newer compiler than the docker image provides
+#[allow(clippy::derivable_impls)]
+impl Default for Supersampling {
+    fn default() -> Self {
+        Supersampling::None
+    }
+}
+
+impl Supersampling {
+    pub fn is_enabled(&self) -> bool {
+        *self != Supersampling::None
+    }
+
+    /// positions of the samples relative to the center of pixel number `pixel`,
+    /// in units of pixels, jittered by a stream derived from `seed`
+    pub fn offsets(&self, seed: u64, pixel: usize) -> Vec<(f64, f64)> {
+        let (n, jitter) = match *self {
+            Supersampling::None => return vec![(0., 0.)],
+            Supersampling::Grid(n) => (n, false),
+            Supersampling::Jittered(n) | Supersampling::Adaptive(n) => (n, true),
+        };
+
+        // seeded by the fractal and the pixel, such that renders are
+        // reproducible, but every fractal has its own pattern
+        let mut rng = RngType::seed_from_u64(stream_seed(seed, pixel as u64));
+        let n = n.max(1);
+        let cell = 1. / n as f64;
+        let mut offsets = Vec::with_capacity((n*n) as usize);
+        for a in 0..n {
+            for b in 0..n {
+                let (u, v) = if jitter {
+                    (rng.gen::<f64>(), rng.gen::<f64>())
+                } else {
+                    (0.5, 0.5)
+                };
+                offsets.push(((a as f64 + u) * cell - 0.5, (b as f64 + v) * cell - 0.5));
+            }
+        }
+        offsets
+    }
+}
+
+impl FromStr for Supersampling {
+    type Err = FractalError;
+
+    /// parse `none`, `grid:n`, `jittered:n` or `adaptive:n`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FractalError::InvalidArgument(
+            format!("supersampling needs to be `none`, `grid:n`, `jittered:n` or `adaptive:n`, but is '{}'", s)
+        );
+
+        let mut parts = s.splitn(2, ':');
+        let mode = parts.next().unwrap_or("");
+        let n = match parts.next() {
+            Some(n) => n.parse::<u32>().map_err(|_| invalid())?,
+            None => 2,
+        };
+        if n == 0 {
+            return Err(invalid())
+        }
+
+        match mode {
+            "none" => Ok(Supersampling::None),
+            "grid" => Ok(Supersampling::Grid(n)),
+            "jittered" => Ok(Supersampling::Jittered(n)),
+            "adaptive" => Ok(Supersampling::Adaptive(n)),
+            _ => Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for Supersampling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Supersampling::None => write!(f, "none"),
+            Supersampling::Grid(n) => write!(f, "grid:{}", n),
+            Supersampling::Jittered(n) => write!(f, "jittered:{}", n),
+            Supersampling::Adaptive(n) => write!(f, "adaptive:{}", n),
+        }
+    }
+}
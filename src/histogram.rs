@@ -53,21 +53,37 @@ impl ColoredHistogram {
         first + second
     }
 
+    fn max_count(&self) -> f64 {
+        self.bins.par_iter()
+                 .map(|&(_, _, _, a)| a)
+                 .max()
+                 .unwrap() as f64
+    }
+
     /// normalize the four channels of the histogram to RGBA values, with a gamma correction
+    ///
+    /// The brightness grows with the logarithm of the number of samples.
     pub fn normalize(&self) -> Vec<RGBA> {
-        let max_a = self.bins.par_iter()
-                            .map(|&(_, _, _, a)| a)
-                            .max()
-                            .unwrap() as f64;
-        let max_a = max_a.ln();
+        let max_a = self.max_count().ln();
+        self.normalize_by(|a| (a as f64).ln() / max_a)
+    }
+
+    /// like `normalize`, but the brightness grows with a power of the number
+    /// of samples, which keeps sparsely visited regions dark
+    pub fn normalize_power(&self, exponent: f64) -> Vec<RGBA> {
+        let max_a = self.max_count();
+        self.normalize_by(|a| (a as f64 / max_a).powf(exponent))
+    }
 
-        // normalize
+    fn normalize_by<F>(&self, brightness: F) -> Vec<RGBA>
+        where F: Fn(u64) -> f64 + Sync
+    {
         self.bins.par_iter()
             .map(|&(r, g, b, a)| {
                 let r = self.apply_vibrancy_and_gamma(r, a);
                 let g = self.apply_vibrancy_and_gamma(g, a);
                 let b = self.apply_vibrancy_and_gamma(b, a);
-                let a = ((a as f64).ln() / max_a * 255.) as u8;
+                let a = (brightness(a) * 255.) as u8;
                 RGBA(r, g, b, a)
             }
         ).collect()
@@ -8,6 +8,9 @@ pub mod colormap;
 pub mod numbers;
 pub mod histogram;
 pub mod png_helper;
+pub mod error;
+pub mod batch;
+pub mod cli;
 
 use std::process::Command;
 
@@ -20,7 +23,15 @@ pub enum FractalType {
     Random,
     Newton,
     Julia,
+    QuadraticJulia,
     Mandelbrot,
+    BurningShip,
+    Tricorn,
+    Multibrot,
+    Phoenix,
+    Nova,
+    Lyapunov,
+    GenericMandelbrot,
     HeighwayDragon,
     BarnsleyFern,
     SierpinskiGasket,
@@ -46,6 +57,10 @@ pub enum FractalType {
     Rossler,
     Thomas,
     DoublePendulum,
+    Buddhabrot,
+    JuliaIim,
+    /// a type registered at runtime, see `fractal::plugin`
+    Plugin(String),
 }
 
 impl fmt::Display for FractalType {
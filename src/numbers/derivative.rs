@@ -0,0 +1,64 @@
+//! Symbolic differentiation of functions in reverse polish notation.
+//!
+//! The operations are arranged into an expression tree, which is
+//! differentiated by the usual rules and written back as reverse polish
+//! notation. Trivial terms like `0 * x` are simplified on the way, such
+//! that the derivative stays reasonably short.
+
+use super::Op;
+use super::expr::{Expr, constant, unary, add, sub, mul, div, pow};
+
+impl Expr {
+    pub(super) fn derivative(&self) -> Expr {
+        match self {
+            Expr::Variable => constant(1.),
+            Expr::Constant(_) => constant(0.),
+            Expr::Unary(c, u) => {
+                let u = (**u).clone();
+                let du = u.derivative();
+                match c {
+                    's' => mul(unary('c', u), du),
+                    'c' => mul(mul(constant(-1.), unary('s', u)), du),
+                    't' => div(du, mul(unary('c', u.clone()), unary('c', u))),
+                    'S' => mul(unary('C', u), du),
+                    'C' => mul(unary('S', u), du),
+                    'T' => div(du, mul(unary('C', u.clone()), unary('C', u))),
+                    'e' => mul(unary('e', u), du),
+                    'l' => div(du, u),
+                    'q' => div(du, mul(constant(2.), unary('q', u))),
+                    // Wirtinger derivatives, `conj(u)` does not depend on `z`
+                    'j' => constant(0.),
+                    'a' => mul(div(unary('j', u.clone()), mul(constant(2.), unary('a', u))), du),
+                    _ => panic!("unexpected unary operator: '{}'", c)
+                }
+            }
+            Expr::Binary(c, x, y) => {
+                let (x, y) = ((**x).clone(), (**y).clone());
+                let (dx, dy) = (x.derivative(), y.derivative());
+                match c {
+                    '+' => add(dx, dy),
+                    '-' => sub(dx, dy),
+                    '*' => add(mul(dx, y.clone()), mul(x, dy)),
+                    '/' => div(sub(mul(dx, y.clone()), mul(x, dy)), mul(y.clone(), y)),
+                    // (x^y)' = y x^(y-1) x'
+                    '^' if dy.is(0.) => mul(mul(y.clone(), pow(x, sub(y, constant(1.)))), dx),
+                    // (x^y)' = x^y ln(x) y'
+                    '^' if dx.is(0.) => mul(mul(pow(x.clone(), y), unary('l', x)), dy),
+                    // (x^y)' = x^y (y' ln(x) + y x' / x)
+                    '^' => mul(
+                        pow(x.clone(), y.clone()),
+                        add(mul(dy, unary('l', x.clone())), div(mul(y, dx), x))
+                    ),
+                    _ => panic!("unexpected binary operator: '{}'", c)
+                }
+            }
+        }
+    }
+}
+
+/// the derivative of the function `ops` with respect to its variable
+pub fn differentiate(ops: &[Op]) -> Vec<Op> {
+    let mut derivative = vec![];
+    Expr::from_rpn(ops).derivative().to_rpn(&mut derivative);
+    derivative
+}
@@ -0,0 +1,282 @@
+//! Expression trees of complex functions.
+//!
+//! Functions are stored in reverse polish notation, but are easier to
+//! transform and to print as a tree. The constructors simplify trivial
+//! terms like `0 * x` on the way.
+
+use std::fmt;
+
+use super::{Op, Cplx, Real};
+
+/// expression tree of a function
+///
+/// `Binary(op, x, y)` means `x op y`, where `x` is the operand which was on
+/// top of the stack, see `ComplexFunction::eval`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Expr {
+    Variable,
+    Constant(Cplx),
+    Unary(char, Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub(super) fn from_rpn(ops: &[Op]) -> Expr {
+        let mut stack: Vec<Expr> = vec![];
+        for op in ops {
+            let expr = match *op {
+                Op::Variable => Expr::Variable,
+                Op::Constant(c) => Expr::Constant(c),
+                // written as `1/x`, such that all notations agree
+                Op::Unary('r') => {
+                    let arg = stack.pop().expect("too few arguments for unary operation");
+                    binary('/', constant(1.), arg)
+                }
+                Op::Unary(c) => {
+                    let arg = stack.pop().expect("too few arguments for unary operation");
+                    Expr::Unary(c, Box::new(arg))
+                }
+                Op::Power(n) => {
+                    let arg = stack.pop().expect("too few arguments for power");
+                    binary('^', arg, constant(n as Real))
+                }
+                Op::Binary(c) => {
+                    let x = stack.pop().expect("missing first argument for binary operation");
+                    let y = stack.pop().expect("missing second argument for binary operation");
+                    Expr::Binary(c, Box::new(x), Box::new(y))
+                }
+            };
+            stack.push(expr);
+        }
+        assert!(stack.len() == 1);
+        stack.pop().unwrap()
+    }
+
+    /// the polynomial with the coefficients `poly`, lowest degree first
+    /// like in `eval_poly`
+    pub(super) fn from_polynom(poly: &[Cplx]) -> Expr {
+        poly.iter()
+            .enumerate()
+            .rev()
+            .map(|(n, &c)| match n {
+                0 => Expr::Constant(c),
+                1 => mul(Expr::Constant(c), Expr::Variable),
+                n => mul(Expr::Constant(c), pow(Expr::Variable, constant(n as Real))),
+            })
+            .fold(None, |sum, term| match sum {
+                None => Some(term),
+                Some(sum) => Some(add(sum, term)),
+            })
+            .unwrap_or_else(|| constant(0.))
+    }
+
+    pub(super) fn to_rpn(&self, ops: &mut Vec<Op>) {
+        match self {
+            Expr::Variable => ops.push(Op::Variable),
+            Expr::Constant(c) => ops.push(Op::Constant(*c)),
+            Expr::Unary(c, arg) => {
+                arg.to_rpn(ops);
+                ops.push(Op::Unary(*c));
+            }
+            Expr::Binary('^', x, y) if y.integer().is_some() => {
+                x.to_rpn(ops);
+                ops.push(Op::Power(y.integer().unwrap()));
+            }
+            Expr::Binary(c, x, y) => {
+                // the first operand has to end up on top of the stack
+                y.to_rpn(ops);
+                x.to_rpn(ops);
+                ops.push(Op::Binary(*c));
+            }
+        }
+    }
+
+    pub(super) fn is(&self, value: f64) -> bool {
+        *self == Expr::Constant(Cplx::new(value, 0.))
+    }
+
+    /// the value of a constant, which is a small integer
+    fn integer(&self) -> Option<i32> {
+        match self {
+            Expr::Constant(c) if c.im == 0. && c.re.fract() == 0. && c.re.abs() <= 1e6 => Some(c.re as i32),
+            _ => None
+        }
+    }
+
+    /// binding strength of the outermost operation in infix notation
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(op, _, _) => precedence(*op),
+            _ => 4
+        }
+    }
+
+    /// whether the infix notation starts with a minus sign
+    fn is_negative(&self) -> bool {
+        match self {
+            Expr::Constant(c) => (c.re < 0. && c.im == 0.) || (c.re == 0. && c.im < 0.),
+            Expr::Binary('*', x, _) | Expr::Binary('/', x, _) => x.is_negative(),
+            _ => false
+        }
+    }
+
+    /// the expression without its leading minus sign, if it has one
+    fn negated(&self) -> Option<Expr> {
+        match self {
+            Expr::Constant(c) if self.is_negative() => Some(Expr::Constant(-c)),
+            Expr::Binary('*', x, y) => x.negated().map(|x| mul(x, (**y).clone())),
+            Expr::Binary('/', x, y) => x.negated().map(|x| div(x, (**y).clone())),
+            _ => None
+        }
+    }
+
+    /// whether this expression needs parentheses as operand of `op`
+    fn needs_parentheses(&self, op: char, left: bool) -> bool {
+        if self.is_negative() {
+            return !left || op == '^'
+        }
+
+        let (inner, outer) = (self.precedence(), precedence(op));
+        match (left, op) {
+            // `^` is right associative
+            (true, '^') => inner <= outer,
+            (false, '^') => inner < outer,
+            (false, '-') | (false, '/') => inner <= outer,
+            _ => inner < outer
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter, op: char, left: bool) -> fmt::Result {
+        if self.needs_parentheses(op, left) {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+/// infix notation with as few parentheses as possible
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Variable => write!(f, "z"),
+            Expr::Constant(c) => fmt_constant(f, *c, true),
+            Expr::Unary(c, arg) => {
+                write!(f, "{}(", Op::Unary(*c))?;
+                match **arg {
+                    Expr::Constant(a) => fmt_constant(f, a, false)?,
+                    ref arg => write!(f, "{}", arg)?,
+                }
+                write!(f, ")")
+            }
+            Expr::Binary('*', x, y) if x.is(-1.) => {
+                write!(f, "-")?;
+                if y.precedence() < 2 || y.is_negative() {
+                    write!(f, "({})", y)
+                } else {
+                    write!(f, "{}", y)
+                }
+            }
+            Expr::Binary(op, x, y) => {
+                // `x + -y` is written as `x - y`
+                let (op, y) = match (*op, y.negated()) {
+                    ('+', Some(y)) => ('-', y),
+                    ('-', Some(y)) => ('+', y),
+                    (op, _) => (op, (**y).clone())
+                };
+
+                x.fmt_operand(f, op, true)?;
+                match op {
+                    '+' | '-' => write!(f, " {} ", op)?,
+                    _ => write!(f, "{}", op)?,
+                }
+                y.fmt_operand(f, op, false)
+            }
+        }
+    }
+}
+
+/// complex constants are enclosed in parentheses, if `enclosed` is set
+fn fmt_constant(f: &mut fmt::Formatter, c: Cplx, enclosed: bool) -> fmt::Result {
+    // avoid printing `-0`
+    let (re, im) = (c.re + 0., c.im + 0.);
+    if im == 0. {
+        write!(f, "{}", re)
+    } else if re == 0. {
+        write!(f, "{}i", im)
+    } else if enclosed {
+        write!(f, "({}{:+}i)", re, im)
+    } else {
+        write!(f, "{}{:+}i", re, im)
+    }
+}
+
+fn precedence(op: char) -> u8 {
+    match op {
+        '+' | '-' => 1,
+        '*' | '/' => 2,
+        _ => 3
+    }
+}
+
+pub(super) fn constant(value: f64) -> Expr {
+    Expr::Constant(Cplx::new(value, 0.))
+}
+
+pub(super) fn unary(c: char, u: Expr) -> Expr {
+    match u {
+        u if c == 'r' => div(constant(1.), u),
+        Expr::Constant(a) => Expr::Constant(super::ComplexFunction::eval_rpn(a, &[Op::Variable, Op::Unary(c)])),
+        u => Expr::Unary(c, Box::new(u))
+    }
+}
+
+fn binary(c: char, x: Expr, y: Expr) -> Expr {
+    Expr::Binary(c, Box::new(x), Box::new(y))
+}
+
+pub(super) fn add(x: Expr, y: Expr) -> Expr {
+    match (x, y) {
+        (Expr::Constant(a), Expr::Constant(b)) => Expr::Constant(a + b),
+        (x, y) if x.is(0.) => y,
+        (x, y) if y.is(0.) => x,
+        (x, y) => binary('+', x, y)
+    }
+}
+
+pub(super) fn sub(x: Expr, y: Expr) -> Expr {
+    match (x, y) {
+        (Expr::Constant(a), Expr::Constant(b)) => Expr::Constant(a - b),
+        (x, y) if y.is(0.) => x,
+        (x, y) if x.is(0.) => mul(constant(-1.), y),
+        (x, y) => binary('-', x, y)
+    }
+}
+
+pub(super) fn mul(x: Expr, y: Expr) -> Expr {
+    match (x, y) {
+        (Expr::Constant(a), Expr::Constant(b)) => Expr::Constant(a * b),
+        (x, y) if x.is(0.) || y.is(0.) => constant(0.),
+        (x, y) if x.is(1.) => y,
+        (x, y) if y.is(1.) => x,
+        (x, y) => binary('*', x, y)
+    }
+}
+
+pub(super) fn div(x: Expr, y: Expr) -> Expr {
+    match (x, y) {
+        (Expr::Constant(a), Expr::Constant(b)) => Expr::Constant(a / b),
+        (x, _) if x.is(0.) => constant(0.),
+        (x, y) if y.is(1.) => x,
+        (x, y) => binary('/', x, y)
+    }
+}
+
+pub(super) fn pow(x: Expr, y: Expr) -> Expr {
+    match (x, y) {
+        (Expr::Constant(a), Expr::Constant(b)) => Expr::Constant(a.powc(b)),
+        (_, y) if y.is(0.) => constant(1.),
+        (x, y) if y.is(1.) => x,
+        (x, y) => binary('^', x, y)
+    }
+}
@@ -0,0 +1,184 @@
+//! Parser for functions in infix notation, e.g., `z^3 - 2*sin(z) + (0.5+1i)`.
+//!
+//! The usual precedence rules apply, `^` binds strongest and is right
+//! associative, a leading minus binds weaker than `^`, such that `-z^2`
+//! means `-(z^2)`. Functions are written with parentheses, e.g., `ln(z)`.
+
+use std::str::FromStr;
+
+use super::{Op, Cplx, Real};
+use super::expr::{Expr, constant, unary, add, sub, mul, div, pow};
+use crate::error::FractalError;
+
+struct Parser<'a> {
+    source: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> FractalError {
+        FractalError::InvalidFormula(format!("{} at position {} in '{}'", message, self.pos, self.source))
+    }
+
+    /// the next character which is not whitespace
+    fn peek(&mut self) -> Option<char> {
+        while matches!(self.chars.get(self.pos), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+        self.chars.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), FractalError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    /// sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Expr, FractalError> {
+        let mut x = self.product()?;
+        loop {
+            x = match self.peek() {
+                Some('+') => { self.pos += 1; add(x, self.product()?) }
+                Some('-') => { self.pos += 1; sub(x, self.product()?) }
+                _ => return Ok(x)
+            }
+        }
+    }
+
+    /// product := signed (('*' | '/') signed)*
+    fn product(&mut self) -> Result<Expr, FractalError> {
+        let mut x = self.signed()?;
+        loop {
+            x = match self.peek() {
+                Some('*') => { self.pos += 1; mul(x, self.signed()?) }
+                Some('/') => { self.pos += 1; div(x, self.signed()?) }
+                _ => return Ok(x)
+            }
+        }
+    }
+
+    /// signed := ('-' | '+') signed | power
+    fn signed(&mut self) -> Result<Expr, FractalError> {
+        match self.peek() {
+            Some('-') => { self.pos += 1; Ok(mul(constant(-1.), self.signed()?)) }
+            Some('+') => { self.pos += 1; self.signed() }
+            _ => self.power()
+        }
+    }
+
+    /// power := atom ('^' signed)?
+    fn power(&mut self) -> Result<Expr, FractalError> {
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.pos += 1;
+            Ok(pow(base, self.signed()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// atom := number | identifier | function '(' sum ')' | '(' sum ')'
+    fn atom(&mut self) -> Result<Expr, FractalError> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let x = self.sum()?;
+                self.expect(')')?;
+                Ok(x)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() => self.identifier(),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end"))
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while matches!(self.chars.get(self.pos), Some(&c) if predicate(c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// a real number like `1.5e-3`, or an imaginary one like `2i`
+    fn number(&mut self) -> Result<Expr, FractalError> {
+        let start = self.pos;
+        let is = |p: usize, f: fn(char) -> bool| matches!(self.chars.get(p), Some(&c) if f(c));
+
+        let mut end = start;
+        while is(end, |c| c.is_ascii_digit() || c == '.') {
+            end += 1;
+        }
+        // an exponent needs digits, otherwise the `e` belongs to the next token
+        if is(end, |c| c == 'e' || c == 'E') {
+            let mut k = end + 1;
+            if is(k, |c| c == '+' || c == '-') {
+                k += 1;
+            }
+            if is(k, |c| c.is_ascii_digit()) {
+                end = k;
+                while is(end, |c| c.is_ascii_digit()) {
+                    end += 1;
+                }
+            }
+        }
+        let imaginary = is(end, |c| c == 'i') && !is(end + 1, char::is_alphanumeric);
+
+        let digits: String = self.chars[start..end].iter().collect();
+        let value = match digits.parse::<Real>() {
+            Ok(x) => x,
+            Err(_) => return Err(self.error(&format!("invalid number '{}'", digits)))
+        };
+
+        if imaginary {
+            self.pos = end + 1;
+            Ok(Expr::Constant(Cplx::new(0., value)))
+        } else {
+            self.pos = end;
+            Ok(constant(value))
+        }
+    }
+
+    /// the variable `z`, the imaginary unit `i` or a function like `sin`
+    fn identifier(&mut self) -> Result<Expr, FractalError> {
+        let start = self.pos;
+        let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+        match name.as_str() {
+            "z" | "x" => Ok(Expr::Variable),
+            "i" => Ok(Expr::Constant(Cplx::new(0., 1.))),
+            _ => match Op::from_str(&name) {
+                Ok(Op::Unary(c)) => {
+                    self.expect('(')?;
+                    let arg = self.sum()?;
+                    self.expect(')')?;
+                    Ok(unary(c, arg))
+                }
+                _ => {
+                    self.pos = start;
+                    Err(self.error(&format!("unknown identifier '{}'", name)))
+                }
+            }
+        }
+    }
+}
+
+/// parse a function in infix notation into its expression tree
+pub(super) fn parse(s: &str) -> Result<Expr, FractalError> {
+    let mut parser = Parser {
+        source: s,
+        chars: s.chars().collect(),
+        pos: 0,
+    };
+
+    let expr = parser.sum()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(c) => Err(parser.error(&format!("unexpected '{}'", c)))
+    }
+}
@@ -0,0 +1,484 @@
+use num::complex::Complex;
+
+use serde::{Serialize, Deserialize};
+
+use rand::Rng;
+use rand::distributions::{Standard, Normal, Distribution};
+use rand::seq::SliceRandom;
+
+use crate::fmt;
+use crate::error::FractalError;
+use std::str::FromStr;
+use std::num::ParseFloatError;
+
+mod expr;
+mod derivative;
+mod infix;
+
+// adjust precision here
+pub type Real = f64;
+pub type Cplx = Complex<Real>;
+
+fn round_cplx(x: Real, y: Real) -> Cplx {
+    Cplx::new((x*10.).round()/10., (y*10.).round()/10.)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Op {
+    Unary(char),
+    Binary(char),
+    /// integer power, faster and more exact than `^`
+    Power(i32),
+    Constant(Cplx),
+    Variable
+}
+
+/// names of the unary operators in reverse polish and infix notation
+const UNARY: [(char, &str); 12] = [
+    ('s', "sin"),
+    ('c', "cos"),
+    ('t', "tan"),
+    ('S', "sinh"),
+    ('C', "cosh"),
+    ('T', "tanh"),
+    ('e', "exp"),
+    ('l', "ln"),
+    ('q', "sqrt"),
+    ('r', "recip"),
+    ('j', "conj"),
+    ('a', "abs"),
+];
+
+/// Relative probabilities of the operations in random functions.
+///
+/// Every operator has its own weight, variables and constants share
+/// `operands`. An operator with weight zero never occurs.
+#[derive(Debug, Clone)]
+pub struct OpWeights {
+    pub operators: Vec<(Op, f64)>,
+    pub operands: f64,
+}
+
+impl Default for OpWeights {
+    /// the classic operators are common, the others add some variety
+    fn default() -> OpWeights {
+        let mut operators: Vec<(Op, f64)> = "sin cos tan + - * / ^".split(' ')
+            .map(|s| (Op::from_str(s).unwrap(), 1.))
+            .collect();
+        operators.extend(
+            "exp ln sqrt sinh cosh tanh recip ^2 ^3".split(' ')
+                .map(|s| (Op::from_str(s).unwrap(), 0.4))
+        );
+        // not holomorphic, see `ComplexFunction::differentiate`
+        operators.push((Op::Unary('j'), 0.1));
+        operators.push((Op::Unary('a'), 0.1));
+
+        OpWeights {
+            operators,
+            operands: 2.,
+        }
+    }
+}
+
+impl OpWeights {
+    /// change the weight of `op`, adding it if it is not yet known
+    pub fn set(mut self, op: Op, weight: f64) -> OpWeights {
+        match self.operators.iter_mut().find(|(o, _)| *o == op) {
+            Some(entry) => entry.1 = weight,
+            None => self.operators.push((op, weight)),
+        }
+        self
+    }
+
+    /// the weights need to be non-negative and at least one binary
+    /// operator needs a positive weight, which every random function starts with
+    pub fn validate(&self) -> Result<(), FractalError> {
+        let weights = self.operators.iter().map(|(_, w)| *w).chain(std::iter::once(self.operands));
+        for w in weights {
+            if !w.is_finite() || w < 0. {
+                return Err(FractalError::InvalidArgument(format!("operator weights need to be non-negative, but one is {}", w)))
+            }
+        }
+        if !self.operators.iter().any(|(op, w)| matches!(op, Op::Binary(_)) && *w > 0.) {
+            return Err(FractalError::InvalidArgument("at least one binary operator needs a positive weight".to_string()))
+        }
+        Ok(())
+    }
+
+    /// choose one of the operators accepted by `filter`, or an operand
+    fn choose<T: Rng>(&self, rng: &mut T, filter: fn(&Op) -> bool, operands: bool) -> Op {
+        let mut candidates: Vec<(Option<&Op>, f64)> = self.operators.iter()
+            .filter(|(op, _)| filter(op))
+            .map(|(op, w)| (Some(op), *w))
+            .collect();
+        if operands {
+            candidates.push((None, self.operands));
+        }
+
+        match candidates.choose_weighted(rng, |c| c.1) {
+            Ok((Some(op), _)) => (*op).clone(),
+            _ => Op::random_operand(rng)
+        }
+    }
+}
+
+impl Op {
+    pub fn random_operand<T: Rng>(rng: &mut T) -> Op {
+        if rng.gen_bool(0.5) {
+            Op::Variable
+        } else {
+            Op::Constant(round_cplx(rng.gen(), rng.gen()))
+        }
+    }
+
+    pub fn random_operator<T: Rng>(rng: &mut T, weights: &OpWeights) -> Op {
+        weights.choose(rng, |_| true, false)
+    }
+
+    pub fn random_binary_operator<T: Rng>(rng: &mut T, weights: &OpWeights) -> Op {
+        weights.choose(rng, |op| matches!(op, Op::Binary(_)), false)
+    }
+
+    pub fn random_unary_or_operand<T: Rng>(rng: &mut T, weights: &OpWeights) -> Op {
+        weights.choose(rng, |op| matches!(op, Op::Unary(_) | Op::Power(_)), true)
+    }
+
+    pub fn random<T: Rng>(rng: &mut T, weights: &OpWeights) -> Op {
+        weights.choose(rng, |_| true, true)
+    }
+}
+
+impl FromStr for Op {
+    type Err = std::num::ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(&(c, _)) = UNARY.iter().find(|(_, name)| *name == s) {
+            return Ok(Op::Unary(c))
+        }
+        // `log` is accepted as an alias, but always written as `ln`
+        if s == "log" {
+            return Ok(Op::Unary('l'))
+        }
+        if let Some(n) = s.strip_prefix('^').and_then(|n| n.parse::<i32>().ok()) {
+            return Ok(Op::Power(n))
+        }
+
+        let op = match s {
+            "z" | "x" => Op::Variable,
+            "+" => Op::Binary('+'),
+            "-" => Op::Binary('-'),
+            "*" => Op::Binary('*'),
+            "/" => Op::Binary('/'),
+            "^" => Op::Binary('^'),
+            _ => Op::Constant(parse_cplx(s)?),
+        };
+        Ok(op)
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Op::Variable => "z".to_string(),
+            Op::Unary(c) => match UNARY.iter().find(|(x, _)| x == c) {
+                Some((_, name)) => name.to_string(),
+                None => "???".to_string()
+            },
+            Op::Binary('+') => "+".to_string(),
+            Op::Binary('-') => "-".to_string(),
+            Op::Binary('*') => "*".to_string(),
+            Op::Binary('/') => "/".to_string(),
+            Op::Binary('^') => "^".to_string(),
+            Op::Power(n) => format!("^{}", n),
+            Op::Constant(x) => format!("{}", x),
+            _ => "???".to_string()
+        };
+        write!(f, "{}", s)
+    }
+}
+
+fn parse_cplx(s: &str) -> Result<Cplx, ParseFloatError> {
+    Ok(
+        if s.contains('i') {
+            if s.contains('-') {
+                let mut s = s.trim_matches('i').split('-');
+                Cplx::new(s.next().unwrap_or("").parse::<Real>()?, -s.next().unwrap_or("").parse::<Real>()?)
+            } else {
+                let mut s = s.trim_matches('i').split('+');
+                Cplx::new(s.next().unwrap_or("").parse::<Real>()?, s.next().unwrap_or("").parse::<Real>()?)
+            }
+        } else {
+            Cplx::new(s.parse::<Real>()?, 0.)
+        }
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ComplexFunction {
+    RPN(Vec<Op>),
+    Polynom(Vec<Cplx>)
+}
+
+impl ComplexFunction {
+    /// Parses a function in reverse polish notation, e.g., `z z * 1 +`.
+    ///
+    /// Fails if a token is unknown or if the operators do not find enough operands.
+    pub fn rpn_from_string(s: &str) -> Result<ComplexFunction, FractalError> {
+        let v = s.split_whitespace()
+            .map(|t| Op::from_str(t).map_err(|_| FractalError::InvalidRpn(format!("unknown token '{}' in '{}'", t, s))))
+            .collect::<Result<Vec<Op>, FractalError>>()?;
+
+        // simulate the stack, to ensure that `eval_rpn` will not fail
+        let mut depth = 0;
+        for op in &v {
+            match op {
+                Op::Unary(_) | Op::Power(_) if depth < 1 => return Err(FractalError::InvalidRpn(format!("too few arguments for '{}' in '{}'", op, s))),
+                Op::Binary(_) if depth < 2 => return Err(FractalError::InvalidRpn(format!("too few arguments for '{}' in '{}'", op, s))),
+                Op::Unary(_) | Op::Power(_) => (),
+                Op::Binary(_) => depth -= 1,
+                Op::Constant(_) | Op::Variable => depth += 1,
+            }
+        }
+        if depth != 1 {
+            return Err(FractalError::InvalidRpn(format!("'{}' leaves {} values on the stack", s, depth)))
+        }
+
+        Ok(ComplexFunction::RPN(v))
+    }
+
+    /// Parses a function in infix notation, e.g., `z^3 - 2*sin(z) + (0.5+1i)`.
+    ///
+    /// Supports `+ - * / ^`, parentheses, the variable `z`, real and
+    /// imaginary numbers like `2.5` or `1i` and the functions which are
+    /// known in reverse polish notation, like `sin(z)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use a_fractal_a_day::numbers::{Cplx, ComplexFunction};
+    /// let f = ComplexFunction::from_infix("z^2 - 2*z + (1+1i)").unwrap();
+    ///
+    /// assert!((Cplx::new(4., 1.) - f.eval(Cplx::new(3., 0.))).norm() < 1e-12);
+    /// assert_eq!(f.infix(), "z^2 - 2*z + (1+1i)");
+    /// ```
+    pub fn from_infix(s: &str) -> Result<ComplexFunction, FractalError> {
+        let mut ops = vec![];
+        infix::parse(s)?.to_rpn(&mut ops);
+        Ok(ComplexFunction::RPN(ops))
+    }
+
+    fn expr(&self) -> expr::Expr {
+        match self {
+            ComplexFunction::RPN(rpn) => expr::Expr::from_rpn(rpn),
+            ComplexFunction::Polynom(poly) => expr::Expr::from_polynom(poly),
+        }
+    }
+
+    /// The function in infix notation with as few parentheses as possible.
+    pub fn infix(&self) -> String {
+        self.expr().to_string()
+    }
+
+    /// The function `1/f` in reverse polish notation.
+    pub fn reciprocal(&self) -> ComplexFunction {
+        let mut ops = vec![];
+        expr::div(expr::constant(1.), self.expr()).to_rpn(&mut ops);
+        ComplexFunction::RPN(ops)
+    }
+
+    pub fn eval(&self, z: Cplx) -> Cplx {
+        match self {
+            ComplexFunction::RPN(rpn) => ComplexFunction::eval_rpn(z, rpn),
+            ComplexFunction::Polynom(poly) => ComplexFunction::eval_poly(z, poly)
+        }
+    }
+
+    fn eval_rpn(z: Cplx, ops: &[Op]) -> Cplx {
+        let mut stack: Vec<Cplx> = vec![];
+        for op in ops {
+            let result = match op {
+                Op::Unary(c) => {
+                    let arg = stack.pop().expect("too few arguments for unary operation");
+                    match c {
+                        's' => arg.sin(),
+                        'c' => arg.cos(),
+                        't' => arg.tan(),
+                        'S' => arg.sinh(),
+                        'C' => arg.cosh(),
+                        'T' => arg.tanh(),
+                        'e' => arg.exp(),
+                        'l' => arg.ln(),
+                        'q' => arg.sqrt(),
+                        'r' => arg.inv(),
+                        'j' => arg.conj(),
+                        'a' => Cplx::new(arg.norm(), 0.),
+                        _ => panic!("unexpected unary operator: '{}'", c)
+                    }
+                }
+                Op::Binary(c) => {
+                    let x = stack.pop().expect("missing first argument for binary operation");
+                    let y = stack.pop().expect("missing second argument for binary operation");
+                    match c {
+                        '+' => x + y,
+                        '-' => x - y,
+                        '*' => x * y,
+                        '/' => x / y,
+                        '^' => x.powc(y),
+                        _ => panic!("unexpected binary operator: '{}'", c)
+                    }
+                }
+                Op::Power(n) => {
+                    let arg = stack.pop().expect("too few arguments for power");
+                    arg.powi(*n)
+                }
+                Op::Variable => z,
+                Op::Constant(x) => *x
+            };
+            stack.push(result);
+        }
+        assert!(stack.len() == 1);
+        stack[0]
+    }
+
+    fn eval_poly(z: Cplx, poly: &[Cplx]) -> Cplx {
+        // TODO, maybe something fast like horner schema?
+        (0..poly.len()).map(|i| poly[i] * z.powf(i as Real)).sum()
+    }
+
+    pub fn random(rng: &mut impl Rng) -> ComplexFunction {
+        ComplexFunction::random_weighted(rng, &OpWeights::default())
+            .expect("the default weights are valid")
+    }
+
+    /// A random polynomial or a random function in reverse polish notation,
+    /// whose operators are drawn according to `weights`.
+    ///
+    /// Fails if the `weights` are not valid, see `OpWeights::validate`.
+    pub fn random_weighted(mut rng: &mut impl Rng, weights: &OpWeights) -> Result<ComplexFunction, FractalError> {
+        weights.validate()?;
+
+        let f = if rng.gen::<bool>() {
+            let normal = Normal::new(0.0, 3.0);
+            let v = normal.sample(&mut rng).abs();
+            let num_terms = v.ceil() as usize;
+            let coefficients = rng.sample_iter(&Standard)
+                .map(|(x, y)| round_cplx(x, y))
+                .take(num_terms)
+                .collect::<Vec<Cplx>>();
+            ComplexFunction::Polynom(coefficients)
+        } else {
+            let mut stack = vec![Op::random_binary_operator(rng, weights)];
+            let mut needed = 2;
+
+            while needed > 0 {
+                let next = if stack.len() > 7 {
+                    Op::random_operand(rng)
+                } else {
+                    Op::random(rng, weights)
+                };
+                match next {
+                    Op::Binary(_) => needed += 1,
+                    Op::Unary(_) | Op::Power(_) => (),
+                    Op::Constant(_) | Op::Variable => needed -= 1
+                }
+                stack.push(next);
+            }
+
+            ComplexFunction::RPN(stack.into_iter().rev().collect())
+        };
+        Ok(f)
+    }
+
+    /// The exact derivative as a new function.
+    ///
+    /// Polynomials differentiate their coefficients, functions in reverse
+    /// polish notation are differentiated symbolically. Calculate it once
+    /// if it is needed for many points.
+    ///
+    /// `conj` and `abs` are not holomorphic, for them this is the Wirtinger
+    /// derivative `∂/∂z`, assuming that their arguments are holomorphic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use a_fractal_a_day::numbers::{Cplx, ComplexFunction};
+    /// let f = ComplexFunction::rpn_from_string("z z sin *").unwrap();
+    /// let df = f.differentiate();
+    ///
+    /// let z = Cplx::new(0.3, 0.2);
+    /// assert!((z.sin() + z * z.cos() - df.eval(z)).norm() < 1e-12);
+    ///
+    /// let p = ComplexFunction::Polynom(vec![Cplx::new(1., 0.), Cplx::new(0., 0.), Cplx::new(1., 0.)]);
+    /// assert!((Cplx::new(6., 0.) - p.differentiate().eval(Cplx::new(3., 0.))).norm() < 1e-9);
+    /// ```
+    pub fn differentiate(&self) -> ComplexFunction {
+        match self {
+            ComplexFunction::RPN(rpn) => ComplexFunction::RPN(derivative::differentiate(rpn)),
+            ComplexFunction::Polynom(poly) => ComplexFunction::Polynom(
+                poly.iter()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(n, c)| c * n as Real)
+                    .collect()
+            )
+        }
+    }
+
+    /// Calculates the derivative of f at z.
+    ///
+    /// This differentiates the whole function on every call, evaluating
+    /// the result of `differentiate` at many points is much faster.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![allow(deprecated)]
+    /// use a_fractal_a_day::numbers::{Cplx, ComplexFunction};
+    /// let f = ComplexFunction::rpn_from_string("z z *").unwrap();
+    ///
+    /// assert_eq!(Cplx::new(9., 0.), f.eval(Cplx::new(3., 0.)));
+    /// assert!((Cplx::new(6., 0.) - f.derivative(&Cplx::new(3., 0.))).norm() < 1e-12);
+    /// ```
+    #[deprecated(note = "differentiate once with `differentiate` and evaluate the result")]
+    pub fn derivative(&self, z: &Cplx) -> Cplx {
+        self.differentiate().eval(*z)
+    }
+
+    pub fn human_readable(&self) -> String {
+        self.infix()
+    }
+}
+
+impl fmt::Display for ComplexFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.human_readable())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum Coef {
+    Real(Real),
+    Complex(Cplx)
+}
+
+impl Coef {
+    pub fn random<T: Rng>(rng: &mut T) -> Coef {
+        let a_re = ((rng.gen_range(1f64, 2.) * 10.).floor() / 10.) as Real;
+        let a_im = ((rng.gen_range(1f64, 2.) * 10.).floor() / 10.) as Real;
+        if rng.gen::<f64>() < 0.1 {
+            let tmp = Cplx::new(a_re, a_im);
+            Coef::Complex(tmp)
+        } else if rng.gen::<f64>() < 0.4 {
+            let tmp = a_re;
+            Coef::Real(tmp)
+        } else {
+            Coef::Real(1.)
+        }
+    }
+}
+
+impl fmt::Display for Coef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,39 @@
+//! Helpers shared by the integration tests, which render small images.
+
+// every test file uses only some of the helpers
+#![allow(dead_code)]
+
+use a_fractal_a_day::fractal::{Fractal, RenderControl, Supersampling};
+use a_fractal_a_day::numbers::Cplx;
+
+/// render `fractal` at `resolution` into RGBA values, row by row
+pub fn render(fractal: &mut Fractal, resolution: (u32, u32)) -> Vec<u8> {
+    fractal.render_to_image(resolution, Supersampling::None, &RenderControl::new())
+           .unwrap()
+           .buffer
+}
+
+/// number of different colors in an RGBA buffer
+pub fn distinct_colors(buffer: &[u8]) -> usize {
+    let mut pixels: Vec<&[u8]> = buffer.chunks(4).collect();
+    pixels.sort();
+    pixels.dedup();
+    pixels.len()
+}
+
+/// for every pixel of an RGBA buffer, whether it is black
+pub fn black_pixels(buffer: &[u8]) -> Vec<bool> {
+    buffer.chunks(4).map(|p| p[..3] == [0, 0, 0]).collect()
+}
+
+/// the points of the complex plane, which an escape-time fractal samples
+/// for the pixels of an image at `resolution`, row by row
+///
+/// The arithmetic is the same as in the renderer, such that the points agree to the last bit.
+pub fn plane(resolution: (u32, u32), zoom: f64, center: (f64, f64)) -> Vec<Cplx> {
+    let (w, h) = (resolution.0 as i64, resolution.1 as i64);
+    let scale = 1. / h as f64;
+    (0..w * h)
+        .map(|k| Cplx::new((k % w - w / 2) as f64 * scale, (k / w - h / 2) as f64 * scale) / zoom + Cplx::new(center.0, center.1))
+        .collect()
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+
+use a_fractal_a_day::batch::{Recipe, Verdict};
+use a_fractal_a_day::error::FractalError;
+
+#[test]
+fn test_batch_resume() {
+    let output = std::env::temp_dir().join("a_fractal_a_day_test_batch");
+    let _ = fs::remove_dir_all(&output);
+
+    let json = format!(r#"{{
+        "output": {:?},
+        "threads": 2,
+        "jobs": [
+            {{"type": "BarnsleyFern", "seeds": {{"start": 0, "end": 2}}, "resolution": [32, 18]}},
+            {{"type": "Ising", "seeds": {{"start": 5, "end": 6}}, "resolution": [16, 16], "name": "ising_{{seed}}"}}
+        ]
+    }}"#, output.to_string_lossy());
+    let recipe_file = std::env::temp_dir().join("a_fractal_a_day_test_batch.json");
+    fs::write(&recipe_file, json).unwrap();
+
+    let recipe = Recipe::from_file(&recipe_file.to_string_lossy()).unwrap();
+    let report = recipe.run().unwrap();
+    assert_eq!(report.renders.len(), 3);
+    assert_eq!(report.accepted(0), vec![0, 1]);
+    assert_eq!(report.accepted(1), vec![5]);
+    assert!(report.renders.iter().all(|r| r.verdict == Verdict::Accepted));
+    assert!(Path::new(&output).join("BarnsleyFern_1.png").exists());
+    assert!(Path::new(&output).join("ising_5.json").exists());
+
+    // everything is done, a second run must not render anything
+    fs::remove_file(Path::new(&output).join("ising_5.png")).unwrap();
+    let report = recipe.run().unwrap();
+    assert_eq!(report.renders.len(), 3);
+    assert!(!Path::new(&output).join("ising_5.png").exists());
+}
+
+#[test]
+fn test_batch_retry_failed() {
+    let output = std::env::temp_dir().join("a_fractal_a_day_test_batch_retry");
+    let _ = fs::remove_dir_all(&output);
+    fs::create_dir_all(&output).unwrap();
+
+    // an earlier run was interrupted while rendering seed 5 and rejected seed 6
+    let report = r#"{"renders": [
+        {"job": 0, "seed": 5, "name": "ising_5", "verdict": {"Failed": "the render was cancelled"}, "description": ""},
+        {"job": 0, "seed": 6, "name": "ising_6", "verdict": "Rejected", "description": ""}
+    ]}"#;
+    fs::write(output.join("report.json"), report).unwrap();
+
+    let json = format!(r#"{{
+        "output": {:?},
+        "jobs": [{{"type": "Ising", "seeds": {{"start": 5, "end": 7}}, "resolution": [16, 16], "name": "ising_{{seed}}"}}]
+    }}"#, output.to_string_lossy());
+    let recipe_file = std::env::temp_dir().join("a_fractal_a_day_test_batch_retry.json");
+    fs::write(&recipe_file, json).unwrap();
+
+    // only the failed seed is rendered again and replaces its failure
+    let report = Recipe::from_file(&recipe_file.to_string_lossy()).unwrap().run().unwrap();
+    assert_eq!(report.renders.len(), 2);
+    assert_eq!(report.accepted(0), vec![5]);
+    assert_eq!(report.rejected(0), vec![6]);
+    assert!(output.join("ising_5.png").exists());
+    assert!(!output.join("ising_6.png").exists());
+    let _ = fs::remove_dir_all(&output);
+}
+
+#[test]
+fn test_batch_names() {
+    let output = std::env::temp_dir().join("a_fractal_a_day_test_batch_names");
+    let recipe = |jobs: &str| {
+        let json = format!(r#"{{"output": {:?}, "jobs": [{}]}}"#, output.to_string_lossy(), jobs);
+        let recipe_file = std::env::temp_dir().join("a_fractal_a_day_test_batch_names.json");
+        fs::write(&recipe_file, json).unwrap();
+        Recipe::from_file(&recipe_file.to_string_lossy())
+    };
+
+    // the default names of overlapping seeds collide
+    let colliding = recipe(r#"
+        {"type": "Ising", "seeds": {"start": 0, "end": 3}},
+        {"type": "Ising", "seeds": {"start": 2, "end": 4}, "resolution": [16, 16]}
+    "#);
+    assert!(matches!(colliding, Err(FractalError::InvalidArgument(_))));
+
+    let distinct = recipe(r#"
+        {"type": "Ising", "seeds": {"start": 0, "end": 3}},
+        {"type": "Ising", "seeds": {"start": 2, "end": 4}, "name": "small_{type}_{seed}"}
+    "#);
+    assert!(distinct.is_ok());
+
+    // plugin names are used verbatim, without quotes
+    let plugin = recipe(r#"{"type": {"Plugin": "my plugin"}, "seeds": {"start": 0, "end": 1}}"#).unwrap();
+    let report = plugin.run().unwrap();
+    assert_eq!(report.renders[0].name, "my_plugin_0");
+    let _ = fs::remove_dir_all(&output);
+}
@@ -0,0 +1,90 @@
+use std::fs;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use a_fractal_a_day::cli;
+use a_fractal_a_day::error::FractalError;
+use a_fractal_a_day::fractal::RenderControl;
+use a_fractal_a_day::fractal::plugin::{self, FractalPlugin, PluginFractal, PluginParams};
+
+#[derive(Serialize, Deserialize)]
+struct Gray {
+    description: String,
+    shade: u8,
+}
+
+impl PluginFractal for Gray {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn render(&mut self, resolution: (u32, u32), control: &RenderControl)
+        -> Result<(Vec<u8>, bool), FractalError>
+    {
+        control.check()?;
+        let (x, y) = resolution;
+        let buffer = (0..x*y).flat_map(|_| vec![self.shade, self.shade, self.shade, 255]).collect();
+        Ok((buffer, true))
+    }
+
+    fn to_value(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+}
+
+struct GrayPlugin;
+
+impl FractalPlugin for GrayPlugin {
+    fn name(&self) -> &str {
+        "gray"
+    }
+
+    fn build(&self, params: &PluginParams, seed: u64) -> Result<Box<dyn PluginFractal>, FractalError> {
+        let shade = match params.get("shade") {
+            Some(s) => s.parse().map_err(|_| FractalError::InvalidArgument(s.clone()))?,
+            None => seed as u8,
+        };
+        Ok(Box::new(Gray {
+            description: format!("gray {}", shade),
+            shade,
+        }))
+    }
+
+    fn load(&self, value: Value) -> Result<Box<dyn PluginFractal>, FractalError> {
+        serde_json::from_value::<Gray>(value)
+            .map(|g| Box::new(g) as Box<dyn PluginFractal>)
+            .map_err(|e| FractalError::InvalidArgument(e.to_string()))
+    }
+}
+
+#[test]
+fn test_cli_plugin() {
+    // the outputs are written relative to the working directory
+    let output = std::env::temp_dir().join("a_fractal_a_day_test_cli");
+    let _ = fs::remove_dir_all(&output);
+    fs::create_dir_all(&output).unwrap();
+    std::env::set_current_dir(&output).unwrap();
+
+    let args = [
+        "a_fractal_a_day", "--plugin", "gray", "--param", "shade=7", "--seed", "3",
+        "--filename", "gray", "--width", "4", "--height", "2", "--no-optipng", "--quiet",
+    ];
+
+    // unknown before the plugin is registered
+    assert!(matches!(cli::run(args), Err(FractalError::UnknownPlugin(_))));
+
+    plugin::register(GrayPlugin).unwrap();
+    cli::run(args).unwrap();
+
+    let decoder = png::Decoder::new(fs::File::open(output.join("img/gray.png")).unwrap());
+    let (info, mut reader) = decoder.read_info().unwrap();
+    assert_eq!((info.width, info.height), (4, 2));
+    let mut buffer = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buffer).unwrap();
+    assert_eq!(buffer[..4], [7, 7, 7, 255]);
+    let json = fs::read_to_string(output.join("json/gray.json")).unwrap();
+    assert!(json.contains("gray 7"), "{}", json);
+
+    let _ = fs::remove_dir_all(&output);
+}
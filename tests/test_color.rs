@@ -65,3 +65,23 @@ fn test_pingpong() {
         rgb_approx_equal!(*ping, pong);
     }
 }
+#[test]
+fn test_linear_average() {
+    let colors = &[RGB(0.25, 0., 1.), RGB(0.36, 0.18, 0.09), RGB(1., 0.75, 0.)];
+    for c in colors {
+        rgb_approx_equal!(c.clone(), c.to_linear().to_srgb());
+    }
+    // black and white mix to a gray brighter than 0.5 in sRGB
+    let RGB(r, g, b) = RGB::average(&[RGB(0., 0., 0.), RGB(1., 1., 1.)]);
+    assert_approx_eq!(r, 0.7354, 1e-3);
+    assert_eq!(r, g);
+    assert_eq!(g, b);
+}
+
+#[test]
+fn test_parse_hex() {
+    assert_eq!("#ff8000".parse::<RGB>().unwrap(), RGB(1., 128. / 255., 0.));
+    assert_eq!("000000".parse::<RGB>().unwrap(), RGB(0., 0., 0.));
+    assert!("#ff80".parse::<RGB>().is_err());
+    assert!("#gg0000".parse::<RGB>().is_err());
+}
@@ -0,0 +1,60 @@
+use a_fractal_a_day::numbers::{Cplx, ComplexFunction};
+
+/// Wirtinger derivative `(∂/∂x - i ∂/∂y) / 2` by central differences,
+/// which is the usual derivative for holomorphic functions
+fn numeric(f: &ComplexFunction, z: Cplx) -> Cplx {
+    let h = 1e-6;
+    let i = Cplx::new(0., h);
+    let dx = (f.eval(z + h) - f.eval(z - h)) / (2. * h);
+    let dy = (f.eval(z + i) - f.eval(z - i)) / (2. * h);
+    (dx - Cplx::i() * dy) / 2.
+}
+
+#[test]
+fn test_symbolic_derivative() {
+    // mind that binary operators take the top of the stack as first operand
+    let functions = [
+        "z z *",
+        "z tan",
+        "1 z sin /",
+        "z cos z *",
+        "2 z ^",
+        "z 3 ^",
+        "z z ^",
+        "z ln z z * +",
+        "z 1 - z tan *",
+        "1+1i z z * -",
+        "z exp z *",
+        "z sinh z cosh +",
+        "z tanh",
+        "z sqrt",
+        "z log",
+        "z recip",
+        "z ^3",
+        "z 1 + ^-2",
+        "z conj",
+        "z z * abs",
+        "z z sin * conj z +",
+    ];
+    let points = [Cplx::new(0.3, 0.2), Cplx::new(-1.1, 0.7), Cplx::new(2., -0.5)];
+
+    for s in &functions {
+        let f = ComplexFunction::rpn_from_string(s).unwrap();
+        let df = f.differentiate();
+        for &z in &points {
+            let exact = df.eval(z);
+            let approx = numeric(&f, z);
+            assert!((exact - approx).norm() < 1e-5 * (1. + approx.norm()),
+                    "d/dz {} at {}: {} != {} (derivative: {})", s, z, exact, approx, df.human_readable());
+        }
+    }
+}
+
+#[test]
+fn test_derivative_simplification() {
+    let f = ComplexFunction::rpn_from_string("z 3 *").unwrap();
+    assert_eq!(f.differentiate().human_readable(), "3");
+
+    let f = ComplexFunction::rpn_from_string("2 z z + +").unwrap();
+    assert_eq!(f.differentiate().human_readable(), "2");
+}
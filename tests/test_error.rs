@@ -0,0 +1,118 @@
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::error::FractalError;
+use a_fractal_a_day::fractal::{FractalBuilder, Lrules, RootFinding, TrapKind, Style, RenderControl, Supersampling};
+use a_fractal_a_day::numbers::ComplexFunction;
+
+#[test]
+fn test_invalid_rpn() {
+    assert!(ComplexFunction::rpn_from_string("z z * 1 +").is_ok());
+    assert!(ComplexFunction::rpn_from_string("z foo +").is_err());
+    assert!(ComplexFunction::rpn_from_string("z +").is_err());
+    assert!(ComplexFunction::rpn_from_string("z z").is_err());
+}
+
+#[test]
+fn test_invalid_rpn_in_builder() {
+    let f = FractalBuilder::new().rpn(&Some("z sin *".to_string())).build(&FractalType::Newton);
+    assert!(matches!(f, Err(FractalError::InvalidRpn(_))));
+}
+
+#[test]
+fn test_invalid_formula() {
+    assert!(ComplexFunction::from_infix("z^2 + sin(z)").is_ok());
+    assert!(ComplexFunction::from_infix("z^2 +").is_err());
+    assert!(ComplexFunction::from_infix("foo(z)").is_err());
+    assert!(ComplexFunction::from_infix("(z + 1").is_err());
+    assert!(ComplexFunction::from_infix("2 z").is_err());
+
+    let f = FractalBuilder::new().infix(&Some("sin z".to_string())).build(&FractalType::Newton);
+    assert!(matches!(f, Err(FractalError::InvalidFormula(_))));
+}
+
+#[test]
+fn test_invalid_root_finding() {
+    assert!("householder:4".parse::<RootFinding>().is_ok());
+    assert!("householder:0".parse::<RootFinding>().is_err());
+    assert!("householder:7".parse::<RootFinding>().is_err());
+    assert!("householder".parse::<RootFinding>().is_err());
+    assert!("secant".parse::<RootFinding>().is_err());
+
+    // the order is also checked for fractals loaded from json
+    let f = FractalBuilder::new()
+        .root_finding(&Some(RootFinding::Householder(3)))
+        .build(&FractalType::Newton)
+        .unwrap();
+    let json = f.json().replace(r#"{"Householder":3}"#, r#"{"Householder":40}"#);
+    let mut f = FractalBuilder::new().build(&FractalType::LoadJson(json)).unwrap();
+    let image = f.render_to_image((8, 8), Supersampling::None, &RenderControl::new());
+    assert!(matches!(image, Err(FractalError::InvalidArgument(_))));
+}
+
+#[test]
+fn test_invalid_trap() {
+    assert!("cross".parse::<TrapKind>().is_ok());
+    assert!("square".parse::<TrapKind>().is_err());
+    assert!(Style::from_string("trap_angle").is_some());
+}
+
+#[test]
+fn test_invalid_style() {
+    assert!("spooky".parse::<Style>().is_ok());
+    assert!(matches!("sparkly".parse::<Style>(), Err(FractalError::InvalidArgument(_))));
+    assert!(r#"{"name": "x", "coloring": {"hsv": {}}}"#.parse::<Style>().is_err());
+}
+
+#[test]
+fn test_invalid_rule() {
+    assert!(Lrules::from_string("F", "F → F+F").is_ok());
+    assert!(Lrules::from_string("F", "FF+F").is_err());
+    assert!(Lrules::from_string("F", "F").is_err());
+}
+
+#[test]
+fn test_invalid_json() {
+    let f = FractalBuilder::new().build(&FractalType::LoadJson(r#"{"IFS": {"description": 3}}"#.to_string()));
+    match f {
+        Err(FractalError::UnknownJson(errors)) => {
+            assert_eq!(errors.len(), 4);
+            match &errors[0] {
+                FractalError::Json { path, .. } => assert_eq!(path, "IFS.description"),
+                e => panic!("unexpected error {}", e),
+            }
+        },
+        _ => panic!("invalid json must not be accepted"),
+    }
+}
+
+#[test]
+fn test_invalid_limits() {
+    let f = FractalBuilder::new()
+        .limits(&Some([20, 20, 20]))
+        .build(&FractalType::Buddhabrot)
+        .unwrap();
+    let json = f.json().replace(r#""limits":[20,20,20]"#, r#""limits":[0,0,0]"#);
+    assert!(FractalBuilder::new().build(&FractalType::LoadJson(json)).is_err());
+
+    // limits which never accept an orbit render a black image instead of hanging
+    let mut f = FractalBuilder::new()
+        .limits(&Some([1, 1, 1]))
+        .anti(&Some(false))
+        .build(&FractalType::Buddhabrot)
+        .unwrap();
+    let image = f.render_to_image((8, 8), Supersampling::None, &RenderControl::new()).unwrap();
+    assert!(image.buffer.chunks(4).all(|p| p[..3] == [0, 0, 0]));
+}
+
+#[test]
+fn test_invalid_sequence() {
+    let f = FractalBuilder::new()
+        .sequence(&Some("ab".to_string()))
+        .build(&FractalType::Lyapunov)
+        .unwrap();
+    for sequence in &["", "abc"] {
+        let json = f.json().replace(r#""sequence":"AB""#, &format!(r#""sequence":"{}""#, sequence));
+        let mut g = FractalBuilder::new().build(&FractalType::LoadJson(json)).unwrap();
+        let image = g.render_to_image((8, 8), Supersampling::None, &RenderControl::new());
+        assert!(matches!(image, Err(FractalError::InvalidArgument(_))), "{}", sequence);
+    }
+}
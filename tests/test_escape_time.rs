@@ -0,0 +1,105 @@
+mod common;
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::error::FractalError;
+use a_fractal_a_day::fractal::{Fractal, FractalBuilder};
+use a_fractal_a_day::numbers::{Cplx, Coef};
+
+use common::{render, distinct_colors, black_pixels, plane};
+
+/// iterate `step` from `z` until its norm squared reaches `threshold`,
+/// returns whether it stays bounded for `max_count` iterations
+fn bounded(mut z: Cplx, max_count: u64, threshold: f64, mut step: impl FnMut(Cplx) -> Cplx) -> bool {
+    for _ in 0..max_count {
+        z = step(z);
+        if z.norm_sqr() >= threshold {
+            return false
+        }
+    }
+    true
+}
+
+fn parameter(f: &Fractal, key: &str) -> Cplx {
+    let json: serde_json::Value = serde_json::from_str(&f.json()).unwrap();
+    let c = &json["fractal"][key];
+    Cplx::new(c[0].as_f64().unwrap(), c[1].as_f64().unwrap())
+}
+
+/// renders the view and compares its black pixels with the points, which `inside` considers part of the set
+fn assert_set(f: &mut Fractal, zoom: f64, center: (f64, f64), inside: impl Fn(Cplx) -> bool) {
+    let resolution = (48, 27);
+    let black = black_pixels(&render(f, resolution));
+    let inside: Vec<bool> = plane(resolution, zoom, center).into_iter().map(inside).collect();
+
+    assert!(inside.contains(&true) && inside.contains(&false), "{} is not at the boundary", f.description());
+    let mismatches = black.iter().zip(&inside).filter(|(a, b)| a != b).count();
+    assert!(mismatches <= inside.len() / 100, "{} of {} pixels differ in {}", mismatches, inside.len(), f.description());
+}
+
+#[test]
+fn test_escape_time_maps() {
+    let view = |zoom: f64, center: (f64, f64)| FractalBuilder::new().seed(1).zoom(&Some(zoom)).center(&Some(center));
+
+    let (zoom, center) = (0.4, (-0.5, -0.6));
+    let mut f = view(zoom, center).build(&FractalType::BurningShip).unwrap();
+    assert_set(&mut f, zoom, center, |c| bounded(c, 1000, 4., |z| {
+        let z = Cplx::new(z.re.abs(), z.im.abs());
+        z * z + c
+    }));
+
+    let (zoom, center) = (0.4, (-0.3, 0.));
+    let mut f = view(zoom, center).build(&FractalType::Tricorn).unwrap();
+    assert_set(&mut f, zoom, center, |c| bounded(c, 1000, 4., |z| z.conj() * z.conj() + c));
+
+    let (zoom, center) = (0.4, (0., 0.));
+    let mut f = view(zoom, center).exponent(&Some(3.)).build(&FractalType::Multibrot).unwrap();
+    assert!(f.description().contains("z^3"), "{}", f.description());
+    assert_set(&mut f, zoom, center, |c| bounded(c, 1000, 1e10, |z| z * z * z + c));
+
+    // the pixels are the starting points of the orbit, which remembers its previous value
+    let (zoom, center) = (0.35, (0., 0.));
+    let mut f = view(zoom, center).build(&FractalType::Phoenix).unwrap();
+    let (c, p) = (parameter(&f, "c"), parameter(&f, "p"));
+    assert_set(&mut f, zoom, center, |z0| {
+        let mut previous = Cplx::new(0., 0.);
+        bounded(z0, 1000, 4., |z| {
+            let next = z * z + c + p * previous;
+            previous = z;
+            next
+        })
+    });
+
+    // the orbits start at the root `z = 1`, points which never converge are part of the set
+    let (zoom, center) = (0.4, (-0.25, 0.));
+    let mut f = view(zoom, center).coefficient(Coef::Real(1.)).build(&FractalType::Nova).unwrap();
+    let relaxation = parameter(&f, "relaxation");
+    assert_eq!(relaxation, Cplx::new(1., 0.));
+    assert_set(&mut f, zoom, center, |c| {
+        let mut z = Cplx::new(1., 0.);
+        for _ in 0..500 {
+            let next = z - relaxation * (z * z * z - 1.) / (z * z * 3.) + c;
+            if (next - z).norm_sqr() <= 1e-10 {
+                return false
+            }
+            z = next;
+        }
+        true
+    });
+}
+
+#[test]
+fn test_escape_time_families() {
+    let families = [FractalType::BurningShip, FractalType::Tricorn, FractalType::Multibrot, FractalType::Phoenix, FractalType::Nova, FractalType::Lyapunov];
+    for fractal_type in &families {
+        for seed in 0..3 {
+            let mut f = FractalBuilder::new().seed(seed).build(fractal_type).unwrap();
+
+            // the random region should show some structure
+            let colors = distinct_colors(&render(&mut f, (48, 27)));
+            assert!(colors > 5, "{} with seed {}: only {} colors in {}", fractal_type, seed, colors, f.description());
+        }
+    }
+
+    let invalid = FractalBuilder::new().exponent(&Some(0.5)).build(&FractalType::Multibrot);
+    assert!(matches!(invalid, Err(FractalError::InvalidArgument(_))));
+}
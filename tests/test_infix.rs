@@ -0,0 +1,110 @@
+use rand::SeedableRng;
+
+use a_fractal_a_day::numbers::{Cplx, ComplexFunction, Op, OpWeights};
+use a_fractal_a_day::fractal::RngType;
+
+fn assert_same(f: &ComplexFunction, g: &ComplexFunction) {
+    for &z in &[Cplx::new(0.3, 0.2), Cplx::new(-1.1, 0.7), Cplx::new(2., -0.5)] {
+        let (a, b) = (f.eval(z), g.eval(z));
+        assert!((a - b).norm() < 1e-9 * (1. + a.norm()) || a.is_nan() && b.is_nan(),
+                "{} != {} at {}: {} != {}", f.infix(), g.infix(), z, a, b);
+    }
+}
+
+#[test]
+fn test_infix_parser() {
+    // every formula next to the same function in reverse polish notation
+    let formulas = [
+        ("z^3 - 2*sin(z) + (0.5+1i)", "0.5+1i 2 z sin * 3 z ^ - +"),
+        ("-z^2", "-1 2 z ^ *"),
+        ("z^-1", "-1 z ^"),
+        ("2^z^2", "2 z ^ 2 ^"),
+        ("1 - z - z", "z z 1 - -"),
+        ("1/z/2", "2 z 1 / /"),
+        ("(z + 1)*(z - 1)", "1 z - 1 z + *"),
+        ("2.5e-1*z + 1i", "0+1i 0.25 z * +"),
+        ("ln(cos(z)) / tan(x)", "z tan z cos ln /"),
+        ("exp(z) + sqrt(z)*abs(z)", "z abs z sqrt * z exp +"),
+        ("sinh(z) - cosh(z)/tanh(conj(z))", "z conj tanh z cosh / z sinh -"),
+        ("1/z + z^3", "z ^3 z recip +"),
+        ("log(z)^-2", "z ln ^-2"),
+    ];
+
+    for (infix, rpn) in &formulas {
+        let f = ComplexFunction::from_infix(infix).unwrap();
+        let g = ComplexFunction::rpn_from_string(rpn).unwrap();
+        assert_same(&f, &g);
+    }
+}
+
+#[test]
+fn test_infix_printer() {
+    let printed = [
+        ("z z * 1 +", "1 + z*z"),
+        ("z 1 -", "1 - z"),
+        ("1 z z - -", "z - z - 1"),
+        ("z z 1 - -", "1 - z - z"),
+        ("2 z z + /", "(z + z)/2"),
+        ("2 3 ^ z ^", "z^3^2"),
+        ("2 3 z ^ ^", "(z^3)^2"),
+        ("z -2 *", "-2*z"),
+        ("z -2 * 1 +", "1 - 2*z"),
+        ("-2 z ^", "z^(-2)"),
+        ("z -2 ^", "(-2)^z"),
+        ("1 z + sin", "sin(z + 1)"),
+        ("1+1i cos", "cos(1+1i)"),
+        ("z exp ^2", "exp(z)^2"),
+        ("1 z + recip", "1/(z + 1)"),
+        ("z ^-1 conj", "conj(z^(-1))"),
+    ];
+    for (rpn, infix) in &printed {
+        let f = ComplexFunction::rpn_from_string(rpn).unwrap();
+        assert_eq!(&f.infix(), infix);
+    }
+
+    let p = ComplexFunction::Polynom(vec![Cplx::new(-1., 0.), Cplx::new(0.5, 1.), Cplx::new(0., 0.), Cplx::new(1., 0.)]);
+    assert_eq!(p.human_readable(), "z^3 + (0.5+1i)*z - 1");
+}
+
+#[test]
+fn test_infix_roundtrip() {
+    let mut rng = RngType::seed_from_u64(42);
+    for _ in 0..200 {
+        let f = ComplexFunction::random(&mut rng);
+        let g = ComplexFunction::from_infix(&f.infix()).unwrap();
+        assert_same(&f, &g);
+
+        // parsing folds constants, but is stable afterwards
+        let h = ComplexFunction::from_infix(&g.infix()).unwrap();
+        assert_eq!(g.infix(), h.infix());
+    }
+}
+
+#[test]
+fn test_random_weights() {
+    let weights = OpWeights::default()
+        .set(Op::Unary('s'), 5.);
+    assert!(weights.operators.contains(&(Op::Unary('s'), 5.)));
+
+    // only operators with a weight are drawn
+    let weights = OpWeights {
+        operators: vec![(Op::Unary('e'), 1.), (Op::Binary('*'), 1.), (Op::Binary('-'), 0.)],
+        operands: 1.,
+    };
+    let mut rng = RngType::seed_from_u64(7);
+    for _ in 0..100 {
+        if let ComplexFunction::RPN(ops) = ComplexFunction::random_weighted(&mut rng, &weights).unwrap() {
+            assert!(ops.iter().all(|op| matches!(op, Op::Unary('e') | Op::Binary('*') | Op::Variable | Op::Constant(_))));
+        }
+    }
+
+    // every random function starts with a binary operator
+    let unary = OpWeights::default()
+        .set(Op::Binary('+'), 0.)
+        .set(Op::Binary('-'), 0.)
+        .set(Op::Binary('*'), 0.)
+        .set(Op::Binary('/'), 0.)
+        .set(Op::Binary('^'), 0.);
+    assert!(ComplexFunction::random_weighted(&mut rng, &unary).is_err());
+    assert!(ComplexFunction::random_weighted(&mut rng, &weights.clone().set(Op::Unary('e'), -1.)).is_err());
+}
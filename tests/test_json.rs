@@ -0,0 +1,108 @@
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::error::FractalError;
+use a_fractal_a_day::fractal::{FractalBuilder, upgrade_json};
+
+use serde_json::Value;
+
+fn all_types() -> Vec<FractalType> {
+    vec![
+        FractalType::Newton,
+        FractalType::Julia,
+        FractalType::QuadraticJulia,
+        FractalType::Mandelbrot,
+        FractalType::BurningShip,
+        FractalType::Tricorn,
+        FractalType::Multibrot,
+        FractalType::Phoenix,
+        FractalType::Nova,
+        FractalType::Lyapunov,
+        FractalType::GenericMandelbrot,
+        FractalType::HeighwayDragon,
+        FractalType::BarnsleyFern,
+        FractalType::SierpinskiGasket,
+        FractalType::SierpinskiPentagon,
+        FractalType::PythagoreanTree,
+        FractalType::AppolonianGasket,
+        FractalType::MobiusFlame,
+        FractalType::FractalFlame,
+        FractalType::KochCurve,
+        FractalType::SierpinskiArrowhead,
+        FractalType::HilbertCurve,
+        FractalType::GosperCurve,
+        FractalType::Bush,
+        FractalType::PenroseTiling,
+        FractalType::Pentigree,
+        FractalType::Tritile,
+        FractalType::LDragon,
+        FractalType::RandomLSystem,
+        FractalType::Ising,
+        FractalType::QuadraticMap,
+        FractalType::Lorenz,
+        FractalType::Rossler,
+        FractalType::Thomas,
+        FractalType::DoublePendulum,
+        FractalType::Buddhabrot,
+        FractalType::JuliaIim,
+    ]
+}
+
+/// assert that two json documents are equal, up to the last digits of
+/// floats, which serde_json does not parse exactly
+fn assert_json_eq(a: &Value, b: &Value, path: &str) {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => {
+            let (x, y) = (x.as_f64().unwrap(), y.as_f64().unwrap());
+            assert!((x - y).abs() <= 1e-12 * x.abs().max(y.abs()), "{}: {} != {}", path, x, y);
+        }
+        (Value::Array(x), Value::Array(y)) => {
+            assert_eq!(x.len(), y.len(), "{}", path);
+            for (i, (x, y)) in x.iter().zip(y).enumerate() {
+                assert_json_eq(x, y, &format!("{}[{}]", path, i));
+            }
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            let keys = |o: &serde_json::Map<String, Value>| o.keys().cloned().collect::<Vec<String>>();
+            assert_eq!(keys(x), keys(y), "{}", path);
+            for (k, v) in x {
+                assert_json_eq(v, &y[k], &format!("{}.{}", path, k));
+            }
+        }
+        _ => assert_eq!(a, b, "{}", path),
+    }
+}
+
+#[test]
+fn test_roundtrip_every_type() {
+    for fractal_type in all_types() {
+        let f = FractalBuilder::new()
+            .seed(42)
+            .dimensions(&Some((16, 16)))
+            .build(&fractal_type)
+            .unwrap();
+        let json = f.json();
+        let g = FractalBuilder::new().build(&FractalType::LoadJson(json.clone())).unwrap();
+
+        let original: Value = serde_json::from_str(&json).unwrap();
+        let reserialized: Value = serde_json::from_str(&g.json()).unwrap();
+        assert_json_eq(&original, &reserialized, &format!("{:?}", fractal_type));
+        assert_eq!(f.description(), g.description());
+    }
+}
+
+#[test]
+fn test_legacy_json() {
+    let legacy = r#"{"Mandelbrot":{"description":"Mandelbrot Fractal","max_count":100,"shift":[-0.5,0.0],"zoom":1.0}}"#;
+    let f = FractalBuilder::new().build(&FractalType::LoadJson(legacy.to_string())).unwrap();
+    assert_eq!(format!("{:?}", f), "Mandelbrot");
+
+    let upgraded = upgrade_json(legacy).unwrap();
+    assert!(upgraded.contains(r#""type":"Mandelbrot""#));
+    assert!(upgraded.contains(r#""version":1"#));
+}
+
+#[test]
+fn test_future_version() {
+    let json = r#"{"version":1000,"type":"Mandelbrot","fractal":{}}"#;
+    let f = FractalBuilder::new().build(&FractalType::LoadJson(json.to_string()));
+    assert!(matches!(f, Err(FractalError::UnsupportedVersion(1000))));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::fractal::{FractalBuilder, Style, mandelbrot_lanes};
+use a_fractal_a_day::numbers::Cplx;
+
+use common::{render, distinct_colors, black_pixels, plane};
+
+#[test]
+fn test_quadratic_julia() {
+    // points of the filled Julia set are black, all others white
+    let definition = r#"{"name": "filled", "coloring": {"hsv": {
+        "hue": {"quantity": {"constant": 0}, "ops": []},
+        "saturation": {"quantity": {"constant": 0}, "ops": []},
+        "value": {"quantity": "count", "ops": [{"div": 2000}, "fract", {"scale": 1e9}]}
+    }}}"#;
+    let filled: Style = definition.parse().unwrap();
+    let (w, h) = (48, 27);
+
+    for seed in 0..3 {
+        let mut f = FractalBuilder::new().seed(seed).build(&FractalType::QuadraticJulia).unwrap();
+        assert!(f.description().starts_with("Julia Fractal of z^2 + ("), "{}", f.description());
+
+        // the parameter belongs to the Mandelbrot set, such that the julia set is connected
+        let json: serde_json::Value = serde_json::from_str(&f.json()).unwrap();
+        let c = &json["fractal"]["f"]["Polynom"][0];
+        let c = Cplx::new(c[0].as_f64().unwrap(), c[1].as_f64().unwrap());
+        assert_eq!(mandelbrot_lanes(&[c], 1000, 4., false)[0].count, 1000., "seed {}: {} escapes", seed, c);
+
+        let colors = distinct_colors(&render(&mut f, (w, h)));
+        assert!(colors > 10, "seed {}: only {} colors in {}", seed, colors, f.description());
+
+        let zoom = json["fractal"]["zoom"].as_f64().unwrap();
+        let inside: Vec<bool> = plane((w, h), zoom, (0., 0.)).into_iter().map(|mut z| {
+            for _ in 0..2000 {
+                z = z * z + c;
+                if z.norm_sqr() >= 1e8 {
+                    return false
+                }
+            }
+            true
+        }).collect();
+
+        // the whole set is visible and surrounded by escaping points
+        assert!(inside.contains(&true), "seed {}: {} is empty", seed, f.description());
+        let border = (0..w * h).filter(|k| k % w == 0 || k % w == w - 1 || k / w == 0 || k / w == h - 1);
+        assert!(border.clone().all(|k| !inside[k as usize]), "seed {}: {} is cut off", seed, f.description());
+
+        let mut f = FractalBuilder::new().seed(seed).style(&Some(filled.clone())).build(&FractalType::QuadraticJulia).unwrap();
+        let black = black_pixels(&render(&mut f, (w, h)));
+        let mismatches = black.iter().zip(&inside).filter(|(a, b)| a != b).count();
+        assert!(mismatches <= inside.len() / 100, "seed {}: {} of {} pixels differ", seed, mismatches, inside.len());
+    }
+}
+
+#[test]
+fn test_julia_iim() {
+    // the inverse iteration only visits the julia set, which is a small part of the image
+    let mut f = FractalBuilder::new()
+        .seed(3)
+        .parameter(&Some((-0.123, 0.745)))
+        .build(&FractalType::JuliaIim)
+        .unwrap();
+    assert_eq!(f.description(), "Julia Set of z^2 + (-0.1230+0.7450i) by inverse iteration");
+    let size = 48;
+    let lit: Vec<bool> = black_pixels(&render(&mut f, (size as u32, size as u32))).into_iter().map(|b| !b).collect();
+    let count = lit.iter().filter(|&&l| l).count();
+    assert!(count > size && count < size * size / 2, "{} lit pixels", count);
+
+    // both preimages `±sqrt(z - c)` are followed, such that the image is
+    // symmetric under `z -> -z`, up to a pixel
+    let lit_near = |i: usize, j: usize| {
+        (i.saturating_sub(1)..(i + 2).min(size))
+            .any(|x| (j.saturating_sub(1)..(j + 2).min(size)).any(|y| lit[y * size + x]))
+    };
+    let symmetric = (0..size * size)
+        .filter(|&k| lit[k] && lit_near(size - 1 - k % size, size - 1 - k / size))
+        .count();
+    assert!(symmetric * 10 >= count * 9, "only {} of {} lit pixels are symmetric", symmetric, count);
+}
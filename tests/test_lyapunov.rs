@@ -0,0 +1,69 @@
+mod common;
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::error::FractalError;
+use a_fractal_a_day::fractal::FractalBuilder;
+
+use common::{render, distinct_colors, black_pixels, plane};
+
+#[test]
+fn test_lyapunov() {
+    let mut f = FractalBuilder::new()
+        .seed(5)
+        .sequence(&Some("aabab".to_string()))
+        .zoom(&Some(0.5))
+        .center(&Some((3., 3.)))
+        .build(&FractalType::Lyapunov)
+        .unwrap();
+    assert!(f.description().contains("AABAB"), "{}", f.description());
+    assert!(f.json().contains("\"sequence\":\"AABAB\""));
+
+    // the overview contains stable and chaotic regions, colored by distinct colormaps
+    let colors = distinct_colors(&render(&mut f, (48, 48)));
+    assert!(colors > 10, "only {} colors in {}", colors, f.description());
+
+    let invalid = FractalBuilder::new().sequence(&Some("ABC".to_string())).build(&FractalType::Lyapunov);
+    assert!(matches!(invalid, Err(FractalError::InvalidArgument(_))));
+}
+
+#[test]
+fn test_lyapunov_exponent() {
+    let (zoom, center) = (0.5, (3., 3.));
+    let f = FractalBuilder::new()
+        .seed(5)
+        .sequence(&Some("AB".to_string()))
+        .zoom(&Some(zoom))
+        .center(&Some(center))
+        .build(&FractalType::Lyapunov)
+        .unwrap();
+
+    // stable orbits are black, chaotic ones white
+    let mut json: serde_json::Value = serde_json::from_str(&f.json()).unwrap();
+    json["fractal"]["stable"] = serde_json::json!({"map": [[0, [0, 0, 0]], [1, [0, 0, 0]]], "name": "black"});
+    json["fractal"]["chaotic"] = serde_json::json!({"map": [[0, [1, 1, 1]], [1, [1, 1, 1]]], "name": "white"});
+    let warmup = json["fractal"]["warmup"].as_u64().unwrap();
+    let iterations = json["fractal"]["iterations"].as_u64().unwrap();
+
+    let mut f = FractalBuilder::new().build(&FractalType::LoadJson(json.to_string())).unwrap();
+    let resolution = (48, 48);
+    let black = black_pixels(&render(&mut f, resolution));
+
+    // the logistic map alternates between `r = a` and `r = b`
+    let stable: Vec<bool> = plane(resolution, zoom, center).into_iter().map(|p| {
+        let r = |n: u64| [p.re, p.im][(n % 2) as usize];
+        let mut x = 0.5;
+        for n in 0..warmup {
+            x = r(n) * x * (1. - x);
+        }
+        let mut lambda = 0.;
+        for n in warmup..warmup + iterations {
+            lambda += (r(n) * (1. - 2. * x)).abs().ln();
+            x = r(n) * x * (1. - x);
+        }
+        lambda <= 0.
+    }).collect();
+
+    assert!(stable.contains(&true) && stable.contains(&false));
+    let mismatches = black.iter().zip(&stable).filter(|(a, b)| a != b).count();
+    assert!(mismatches <= stable.len() / 100, "{} of {} pixels differ", mismatches, stable.len());
+}
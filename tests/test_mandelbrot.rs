@@ -0,0 +1,123 @@
+mod common;
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::fractal::{FractalBuilder, mandelbrot_lanes, LANES};
+use a_fractal_a_day::numbers::{Cplx, ComplexFunction};
+
+use common::{render, distinct_colors, black_pixels, plane};
+
+#[test]
+fn test_mandelbrot_lanes() {
+    // a block size which does not fit the lanes
+    let (w, h) = (41, 29);
+    assert!((w * h) % LANES != 0);
+    let starts: Vec<Cplx> = (0..w * h)
+        .map(|k| Cplx::new(-2.1 + 2.8 * (k % w) as f64 / w as f64, -1.2 + 2.4 * (k / w) as f64 / h as f64))
+        .collect();
+
+    let max_count = 500;
+    let scalar: Vec<f64> = starts.iter().map(|&c| {
+        let mut z = c;
+        let mut n = 0;
+        loop {
+            z = z * z + c;
+            n += 1;
+            if z.norm_sqr() >= 4. || n >= max_count {
+                return n as f64
+            }
+        }
+    }).collect();
+
+    let plain: Vec<f64> = mandelbrot_lanes(&starts, max_count, 4., false).iter().map(|c| c.count).collect();
+    assert_eq!(plain, scalar);
+
+    // skipping the interior must not change any count
+    let interior: Vec<f64> = mandelbrot_lanes(&starts, max_count, 4., true).iter().map(|c| c.count).collect();
+    assert_eq!(interior, scalar);
+    assert!(interior.contains(&(max_count as f64)));
+}
+
+#[test]
+fn test_mandelbrot_location() {
+    let resolution = (48, 27);
+    for seed in 0..5 {
+        let mut f = FractalBuilder::new().seed(seed).build(&FractalType::Mandelbrot).unwrap();
+        let g = FractalBuilder::new().seed(seed).build(&FractalType::Mandelbrot).unwrap();
+        assert_eq!(f.description(), g.description());
+
+        let json: serde_json::Value = serde_json::from_str(&f.json()).unwrap();
+        let zoom = json["fractal"]["zoom"].as_f64().unwrap();
+        let center = (json["fractal"]["shift"][0].as_f64().unwrap(), json["fractal"]["shift"][1].as_f64().unwrap());
+
+        // the search ends up at the boundary, where the iteration counts vary a lot
+        let counts: Vec<f64> = mandelbrot_lanes(&plane(resolution, zoom, center), 1000, 4., false)
+            .iter()
+            .map(|c| c.count)
+            .collect();
+        let fastest = counts.iter().cloned().fold(f64::INFINITY, f64::min);
+        let slowest = counts.iter().cloned().fold(0., f64::max);
+        assert!(slowest >= 4. * fastest, "seed {}: counts from {} to {} in {}", seed, fastest, slowest, f.description());
+
+        let colors = distinct_colors(&render(&mut f, resolution));
+        assert!(colors > 10, "seed {}: only {} colors in {}", seed, colors, f.description());
+    }
+}
+
+#[test]
+fn test_generic_mandelbrot() {
+    // `z^2` has its critical point at `0`, where the Mandelbrot set starts
+    let resolution = (48, 27);
+    let (zoom, center) = (0.4, (-0.75, 0.));
+    let mut f = FractalBuilder::new()
+        .seed(3)
+        .formula(ComplexFunction::from_infix("z^2").unwrap())
+        .zoom(&Some(zoom))
+        .center(&Some(center))
+        .build(&FractalType::GenericMandelbrot)
+        .unwrap();
+    assert!(f.description().contains("z^2 + c"), "{}", f.description());
+    assert!(f.json().contains("\"critical_point\":[0.0,0.0]"), "{}", f.json());
+
+    // points of the set are black
+    let black = black_pixels(&render(&mut f, resolution));
+    let inside: Vec<bool> = mandelbrot_lanes(&plane(resolution, zoom, center), 999, 1e8, false).iter().map(|c| c.count >= 999.).collect();
+    assert!(inside.contains(&true) && inside.contains(&false));
+    assert_eq!(black, inside);
+
+    // the critical points of `z^3 - 3z` are `1` and `-1`
+    let (zoom, center) = (0.25, (0., 0.));
+    let f = FractalBuilder::new()
+        .seed(3)
+        .formula(ComplexFunction::from_infix("z^3 - 3*z").unwrap())
+        .zoom(&Some(zoom))
+        .center(&Some(center))
+        .build(&FractalType::GenericMandelbrot)
+        .unwrap();
+    let mut loaded = FractalBuilder::new().build(&FractalType::LoadJson(f.json())).unwrap();
+    assert_eq!(f.description(), loaded.description());
+    let json: serde_json::Value = serde_json::from_str(&f.json()).unwrap();
+    let critical_point = Cplx::new(json["fractal"]["critical_point"][0].as_f64().unwrap(), json["fractal"]["critical_point"][1].as_f64().unwrap());
+    assert!((critical_point.norm() - 1.).abs() < 1e-9 && critical_point.im.abs() < 1e-9, "{}", critical_point);
+
+    // the orbits start at the critical point
+    let black = black_pixels(&render(&mut loaded, resolution));
+    let inside: Vec<bool> = plane(resolution, zoom, center).into_iter().map(|c| {
+        let mut z = critical_point;
+        for _ in 0..1000 {
+            z = z * z * z - z * 3. + c;
+            if z.norm_sqr() >= 1e8 {
+                return false
+            }
+        }
+        true
+    }).collect();
+    assert!(inside.contains(&true) && inside.contains(&false));
+    let mismatches = black.iter().zip(&inside).filter(|(a, b)| a != b).count();
+    assert!(mismatches <= inside.len() / 100, "{} of {} pixels differ", mismatches, inside.len());
+
+    for seed in 0..3 {
+        let mut f = FractalBuilder::new().seed(seed).build(&FractalType::GenericMandelbrot).unwrap();
+        let colors = distinct_colors(&render(&mut f, resolution));
+        assert!(colors > 5, "seed {}: only {} colors in {}", seed, colors, f.description());
+    }
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::fractal::{FractalBuilder, RootFinding};
+use a_fractal_a_day::numbers::{Cplx, Coef, ComplexFunction};
+use a_fractal_a_day::color::RGB;
+
+use common::{render, plane};
+
+#[test]
+fn test_newton_basins() {
+    let f = ComplexFunction::from_infix("z^3 - 1").unwrap();
+    let mut f = FractalBuilder::new()
+        .seed(3)
+        .formula(f)
+        .basins(&Some(RGB(1., 1., 1.)))
+        .build(&FractalType::Newton)
+        .unwrap();
+    let original = render(&mut f, (48, 32));
+
+    // the three roots of unity are distinct basins
+    let description = f.description().to_owned();
+    for root in &["1+0i", "-0.5+0.866i", "-0.5-0.866i"] {
+        assert!(description.contains(root), "{} misses {}", description, root);
+    }
+    assert_eq!(description.split("with roots ").nth(1).unwrap().split(", ").count(), 3);
+
+    let mut loaded = FractalBuilder::new().build(&FractalType::LoadJson(f.json())).unwrap();
+    assert_eq!(loaded.description(), description);
+    let reproduced = render(&mut loaded, (48, 32));
+    assert_eq!(original, reproduced);
+}
+
+#[test]
+fn test_basin_indices() {
+    let resolution = (48, 32);
+    let f = FractalBuilder::new()
+        .seed(3)
+        .formula(ComplexFunction::from_infix("z^3 - 1").unwrap())
+        .coefficient(Coef::Real(1.))
+        .root_finding(&Some("newton".parse::<RootFinding>().unwrap()))
+        .basins(&Some(RGB(1., 1., 1.)))
+        .build(&FractalType::Newton)
+        .unwrap();
+
+    // the colormap is red, green and blue around the colors of the first, second and third root
+    let mut json: serde_json::Value = serde_json::from_str(&f.json()).unwrap();
+    json["fractal"]["basins"]["colormap"] = serde_json::json!({
+        "map": [[0, [1, 0, 0]], [0.33, [1, 0, 0]], [0.34, [0, 1, 0]], [0.66, [0, 1, 0]], [0.67, [0, 0, 1]], [1, [0, 0, 1]]],
+        "name": "rgb"
+    });
+    let roots: Vec<Cplx> = json["fractal"]["roots"].as_array().unwrap().iter()
+        .map(|r| Cplx::new(r[0].as_f64().unwrap(), r[1].as_f64().unwrap()))
+        .collect();
+    assert_eq!(roots.len(), 3);
+
+    let mut f = FractalBuilder::new().build(&FractalType::LoadJson(json.to_string())).unwrap();
+    let buffer = render(&mut f, resolution);
+    let colored: Vec<usize> = buffer.chunks(4)
+        .map(|p| (0..3).max_by_key(|&i| p[i]).unwrap())
+        .collect();
+
+    // every pixel is colored by the root, which Newton's method converges to,
+    // the center pixel is the critical point `z = 0`, which never converges
+    let expected: Vec<Option<usize>> = plane(resolution, 1., (0., 0.)).into_iter().map(|mut z| {
+        for _ in 0..100 {
+            z = z - (z * z * z - 1.) / (z * z * 3.);
+        }
+        roots.iter().position(|&r| (z - r).norm() < 1e-6)
+    }).collect();
+
+    for (idx, root) in roots.iter().enumerate() {
+        assert!(expected.contains(&Some(idx)), "no basin of {}", root);
+    }
+    let mismatches = colored.iter().zip(&expected).filter(|(&a, &b)| matches!(b, Some(b) if a != b)).count();
+    assert!(mismatches <= expected.len() / 50, "{} of {} pixels in the wrong basin", mismatches, expected.len());
+}
+
+#[test]
+fn test_root_finding() {
+    let names = [
+        ("newton", "Newton"),
+        ("halley", "Halley"),
+        ("schroeder", "Schröder"),
+        ("householder:3", "Householder (order 3)"),
+    ];
+    for (method, name) in &names {
+        let method: RootFinding = method.parse().unwrap();
+        let mut f = FractalBuilder::new()
+            .seed(5)
+            .formula(ComplexFunction::from_infix("z^3 - 1").unwrap())
+            .coefficient(Coef::Real(1.))
+            .root_finding(&Some(method))
+            .basins(&Some(RGB(1., 1., 1.)))
+            .build(&FractalType::Newton)
+            .unwrap();
+        render(&mut f, (48, 32));
+
+        // every method finds the same three roots
+        let description = f.description().to_owned();
+        assert!(description.starts_with(&format!("{} Fractal of z^3 - 1 with roots", name)), "{}", description);
+        assert_eq!(description.split("with roots ").nth(1).unwrap().split(", ").count(), 3, "{}", description);
+
+        let loaded = FractalBuilder::new().build(&FractalType::LoadJson(f.json())).unwrap();
+        assert_eq!(loaded.description(), description);
+    }
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::f64::consts::FRAC_PI_4;
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::fractal::{FractalBuilder, Style, TrapKind, OrbitTrap};
+use a_fractal_a_day::numbers::Cplx;
+
+use common::{render, plane};
+
+#[test]
+fn test_trap_measure() {
+    let trap = OrbitTrap::Circle { center: Cplx::new(0., 0.), radius: 1. };
+    assert!((trap.measure(Cplx::new(0., 2.)).distance - 1.).abs() < 1e-12);
+    assert!((trap.measure(Cplx::new(0., 2.)).angle - 0.75).abs() < 1e-12);
+    let trap = OrbitTrap::Cross { center: Cplx::new(1., 1.), angle: 0. };
+    assert!((trap.measure(Cplx::new(1.5, 3.)).distance - 0.5).abs() < 1e-12);
+    let trap = OrbitTrap::Line { point: Cplx::new(0., 1.), angle: FRAC_PI_4 };
+    assert!((trap.measure(Cplx::new(1., 1.)).distance - FRAC_PI_4.sin()).abs() < 1e-12);
+    let trap = OrbitTrap::Point(Cplx::new(1., 0.));
+    assert!((trap.measure(Cplx::new(4., 4.)).distance - 5.).abs() < 1e-12);
+}
+
+#[test]
+fn test_trap_distance() {
+    // the brightness is the closest approach to the trap
+    let definition = r#"{"name": "closest", "coloring": {"hsv": {
+        "hue": {"quantity": {"constant": 0}, "ops": []},
+        "saturation": {"quantity": {"constant": 0}, "ops": []},
+        "value": {"quantity": "trap_distance", "ops": [{"min": 1}]}
+    }}}"#;
+    let (zoom, center) = (0.5, (-0.5, 0.));
+    let f = FractalBuilder::new()
+        .seed(7)
+        .zoom(&Some(zoom))
+        .center(&Some(center))
+        .style(&Some(definition.parse::<Style>().unwrap()))
+        .trap(&Some(TrapKind::Point))
+        .build(&FractalType::Mandelbrot)
+        .unwrap();
+
+    let mut json: serde_json::Value = serde_json::from_str(&f.json()).unwrap();
+    json["fractal"]["trap"] = serde_json::json!({"Point": [0.0, 0.0]});
+    let mut f = FractalBuilder::new().build(&FractalType::LoadJson(json.to_string())).unwrap();
+    let resolution = (48, 27);
+    let buffer = render(&mut f, resolution);
+
+    // every orbit is followed until it escapes far beyond the trap
+    let expected: Vec<u8> = plane(resolution, zoom, center).into_iter().map(|c| {
+        let mut z = c;
+        let mut closest = f64::INFINITY;
+        for _ in 0..1000 {
+            z = z * z + c;
+            closest = closest.min(z.norm());
+            if z.norm_sqr() >= 1e10 {
+                break
+            }
+        }
+        (closest.min(1.) * 255.) as u8
+    }).collect();
+
+    assert!(expected.iter().any(|&v| v < 64) && expected.contains(&255));
+    let mismatches = buffer.chunks(4)
+        .zip(&expected)
+        .filter(|(p, &v)| p[0] != p[1] || p[1] != p[2] || (p[0] as i32 - v as i32).abs() > 1)
+        .count();
+    assert!(mismatches <= expected.len() / 100, "{} of {} pixels differ", mismatches, expected.len());
+}
+
+#[test]
+fn test_orbit_trap() {
+    for kind in &["point", "line", "cross", "circle", "image"] {
+        let kind: TrapKind = kind.parse().unwrap();
+        for fractal_type in &[FractalType::Mandelbrot, FractalType::Julia, FractalType::BurningShip] {
+            let build = || FractalBuilder::new()
+                .seed(7)
+                .trap(&Some(kind))
+                .build(fractal_type)
+                .unwrap();
+            let mut f = build();
+            let original = render(&mut f, (32, 18));
+            let reproduced = render(&mut build(), (32, 18));
+            assert_eq!(original, reproduced);
+
+            // the trap is part of the json
+            let json = f.json();
+            assert!(json.contains("\"trap\""), "{}", json);
+            let mut loaded = FractalBuilder::new().build(&FractalType::LoadJson(json)).unwrap();
+            assert_eq!(loaded.description(), f.description());
+            render(&mut loaded, (32, 18));
+        }
+    }
+
+    // the trap style adds a trap, which does not change the region
+    let plain = FractalBuilder::new().seed(7).build(&FractalType::Tricorn).unwrap();
+    let trapped = FractalBuilder::new()
+        .seed(7)
+        .style(&Some(Style::trap_angle()))
+        .build(&FractalType::Tricorn)
+        .unwrap();
+    assert_eq!(plain.description(), trapped.description());
+    assert!(trapped.json().contains("\"trap_angle\""));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::fractal::{FractalBuilder, reference_orbit, iterate_delta, precision};
+use a_fractal_a_day::numbers::Cplx;
+
+use common::{render, distinct_colors, plane};
+
+#[test]
+fn test_perturbation_matches_direct() {
+    // at a moderate zoom `f64` still resolves the pixels, such that both have to agree
+    let center = ("-0.743643887037151", "0.131825904205330");
+    let c0 = Cplx::new(center.0.parse().unwrap(), center.1.parse().unwrap());
+    let (zoom, max_count) = (1e4, 1000);
+    let offsets = plane((32, 24), zoom, (0., 0.));
+
+    // the distance estimation escapes to a much larger threshold
+    for &threshold in &[4., 1e10] {
+        let orbit = reference_orbit(center, precision(zoom, 24), max_count, threshold).unwrap();
+
+        // the direct iteration starts at `z_1 = c`
+        let direct: Vec<u64> = offsets.iter().map(|&dc| {
+            let c = c0 + dc;
+            let mut z = c;
+            let mut n = 0;
+            loop {
+                z = z * z + c;
+                n += 1;
+                if z.norm_sqr() >= threshold || n >= max_count {
+                    return n
+                }
+            }
+        }).collect();
+        let perturbed: Vec<u64> = offsets.iter().map(|&dc| iterate_delta(&orbit, dc, max_count, threshold).0).collect();
+
+        assert!(direct.contains(&max_count) && direct.iter().any(|&n| n < 100));
+        let mismatches = direct.iter().zip(&perturbed).filter(|(a, b)| a != b).count();
+        assert!(mismatches <= direct.len() / 100, "threshold {}: {} of {} counts differ", threshold, mismatches, direct.len());
+    }
+}
+
+#[test]
+fn test_deep_zoom() {
+    let center = (
+        "-0.0000000000000000000000000000000000000000000000000000000001".to_string(),
+        "1.0000000000000000000000000000000000000000000000000000000001".to_string(),
+    );
+    let mut f = FractalBuilder::new()
+        .seed(42)
+        .zoom(&Some(1e50))
+        .precise_center(&Some(center))
+        .build(&FractalType::Mandelbrot)
+        .unwrap();
+    let buffer = render(&mut f, (32, 18));
+
+    // without perturbation all pixels would collapse onto the same value
+    let colors = distinct_colors(&buffer);
+    assert!(colors > 10, "only {} distinct colors", colors);
+}
@@ -0,0 +1,79 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::error::FractalError;
+use a_fractal_a_day::fractal::{FractalBuilder, RenderControl, Supersampling};
+use a_fractal_a_day::fractal::plugin::{self, FractalPlugin, PluginFractal, PluginParams};
+
+#[derive(Serialize, Deserialize)]
+struct Gradient {
+    description: String,
+    shade: u8,
+}
+
+impl PluginFractal for Gradient {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn render(&mut self, resolution: (u32, u32), control: &RenderControl)
+        -> Result<(Vec<u8>, bool), FractalError>
+    {
+        control.check()?;
+        let (x, y) = resolution;
+        let buffer = (0..x*y).flat_map(|_| vec![self.shade, self.shade, self.shade, 255]).collect();
+        Ok((buffer, true))
+    }
+
+    fn to_value(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+}
+
+struct GradientPlugin;
+
+impl FractalPlugin for GradientPlugin {
+    fn name(&self) -> &str {
+        "gradient"
+    }
+
+    fn build(&self, params: &PluginParams, seed: u64) -> Result<Box<dyn PluginFractal>, FractalError> {
+        let shade = match params.get("shade") {
+            Some(s) => s.parse().map_err(|_| FractalError::InvalidArgument(s.clone()))?,
+            None => seed as u8,
+        };
+        Ok(Box::new(Gradient {
+            description: format!("gray {}", shade),
+            shade,
+        }))
+    }
+
+    fn load(&self, value: Value) -> Result<Box<dyn PluginFractal>, FractalError> {
+        let gradient: Gradient = serde_json::from_value(value)
+            .map_err(|e| FractalError::InvalidArgument(e.to_string()))?;
+        Ok(Box::new(gradient))
+    }
+}
+
+#[test]
+fn test_plugin() {
+    plugin::register(GradientPlugin).unwrap();
+    assert!(matches!(plugin::register(GradientPlugin), Err(FractalError::DuplicatePlugin(_))));
+    assert!(plugin::registered().contains(&"gradient".to_string()));
+
+    let fractal_type = FractalType::Plugin("gradient".to_string());
+    let mut params = PluginParams::new();
+    params.insert("shade".to_string(), "7".to_string());
+    let mut f = FractalBuilder::new().params(&Some(params)).build(&fractal_type).unwrap();
+    assert_eq!(f.description(), "gray 7");
+
+    let image = f.render_to_image((4, 2), Supersampling::None, &RenderControl::new()).unwrap();
+    assert_eq!(image.buffer[..4], [7, 7, 7, 255]);
+
+    let g = FractalBuilder::new().build(&FractalType::LoadJson(f.json())).unwrap();
+    assert_eq!(g.description(), "gray 7");
+
+    let unknown = FractalBuilder::new().build(&FractalType::Plugin("unknown".to_string()));
+    assert!(matches!(unknown, Err(FractalError::UnknownPlugin(_))));
+}
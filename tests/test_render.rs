@@ -0,0 +1,210 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use a_fractal_a_day::FractalType;
+use a_fractal_a_day::error::FractalError;
+use a_fractal_a_day::fractal::{FractalBuilder, RenderControl, CancellationToken, Progress, Style, Coloring, Channel, Quantity, Op, Param, Supersampling, Normalization};
+use a_fractal_a_day::numbers::{Cplx, ComplexFunction};
+
+use common::{render, distinct_colors};
+
+#[test]
+fn test_render_to_image() {
+    let rows = Arc::new(AtomicUsize::new(0));
+    let counter = rows.clone();
+    let control = RenderControl::new().on_progress(move |p| {
+        if let Progress::Rows { total, .. } = p {
+            assert_eq!(total, 18);
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    let mut f = FractalBuilder::new().seed(42).build(&FractalType::Mandelbrot).unwrap();
+    let image = f.render_to_image((32, 18), Supersampling::None, &control).unwrap();
+
+    assert_eq!((image.width, image.height), (32, 18));
+    assert_eq!(image.buffer.len(), 32 * 18 * 4);
+    assert_eq!(rows.load(Ordering::Relaxed), 18);
+}
+
+#[test]
+fn test_cancel_render() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let control = RenderControl::new().cancellation(&token);
+
+    let mut f = FractalBuilder::new().seed(42).build(&FractalType::BarnsleyFern).unwrap();
+    let image = f.render_to_image((32, 32), Supersampling::None, &control);
+    assert!(matches!(image, Err(FractalError::Cancelled)));
+
+    let mut f = FractalBuilder::new().seed(42).build(&FractalType::Newton).unwrap();
+    let image = f.render_to_image((32, 32), Supersampling::None, &control);
+    assert!(matches!(image, Err(FractalError::Cancelled)));
+}
+
+#[test]
+fn test_reproducible_render() {
+    for fractal_type in &[FractalType::BarnsleyFern, FractalType::QuadraticMap, FractalType::Ising, FractalType::Buddhabrot, FractalType::JuliaIim] {
+        let mut f = FractalBuilder::new()
+            .seed(7)
+            .dimensions(&Some((32, 32)))
+            .build(fractal_type)
+            .unwrap();
+        let original = render(&mut f, (32, 32));
+
+        let mut loaded = FractalBuilder::new().build(&FractalType::LoadJson(f.json())).unwrap();
+        let reproduced = render(&mut loaded, (32, 32));
+
+        assert_eq!(original, reproduced, "{} is not reproducible", fractal_type);
+    }
+}
+
+#[test]
+fn test_supersampling() {
+    assert_eq!("adaptive:3".parse::<Supersampling>().unwrap(), Supersampling::Adaptive(3));
+    assert_eq!("grid".parse::<Supersampling>().unwrap(), Supersampling::Grid(2));
+    assert!("grid:0".parse::<Supersampling>().is_err());
+    assert!("blur:2".parse::<Supersampling>().is_err());
+
+    // the jitter is reproducible, but differs between fractals
+    let jittered = Supersampling::Jittered(2);
+    assert_eq!(jittered.offsets(42, 7), jittered.offsets(42, 7));
+    assert_ne!(jittered.offsets(42, 7), jittered.offsets(43, 7));
+    assert_ne!(jittered.offsets(42, 7), jittered.offsets(42, 8));
+
+    let mut f = FractalBuilder::new()
+        .seed(42)
+        .build(&FractalType::Mandelbrot)
+        .unwrap();
+    let plain = f.render_to_image((64, 36), Supersampling::None, &RenderControl::new()).unwrap();
+    let grid = f.render_to_image((64, 36), Supersampling::Grid(3), &RenderControl::new()).unwrap();
+    let adaptive = f.render_to_image((64, 36), Supersampling::Adaptive(3), &RenderControl::new()).unwrap();
+
+    let changed = |image: &[u8]| plain.buffer.chunks(4)
+                                       .zip(image.chunks(4))
+                                       .filter(|(a, b)| a != b)
+                                       .count();
+    assert_eq!(grid.buffer.len(), plain.buffer.len());
+    assert!(changed(&grid.buffer) > 0);
+    // only pixels at edges are refined
+    assert!(changed(&adaptive.buffer) > 0);
+    assert!(changed(&adaptive.buffer) < 64 * 36);
+}
+
+#[test]
+fn test_distance_estimation() {
+    let z2_minus_1 = ComplexFunction::Polynom(vec![Cplx::new(-1., 0.), Cplx::new(0., 0.), Cplx::new(1., 0.)]);
+    let fractals = vec![
+        FractalBuilder::new().seed(1).zoom(&Some(0.5)).center(&Some((-0.5, 0.))),
+        FractalBuilder::new().seed(1).formula(z2_minus_1),
+    ];
+
+    for (builder, fractal_type) in fractals.into_iter().zip(&[FractalType::Mandelbrot, FractalType::Julia]) {
+        let mut f = builder.style(&Some(Style::distance())).build(fractal_type).unwrap();
+        let buffer = render(&mut f, (64, 64));
+
+        // the boundary is dark, far away pixels are bright
+        let value: Vec<u8> = buffer.chunks(4).map(|p| *p.iter().take(3).max().unwrap()).collect();
+        assert!(value.iter().any(|&v| v < 64), "{} has no boundary", fractal_type);
+        assert!(value.iter().any(|&v| v > 192), "{} has no exterior", fractal_type);
+
+        let loaded = FractalBuilder::new().build(&FractalType::LoadJson(f.json())).unwrap();
+        assert!(loaded.json().contains("\"name\":\"distance\""));
+    }
+}
+
+#[test]
+fn test_style_definition() {
+    // built-in styles are stored with their definition, older names still load
+    for name in &["vibrant", "spooky", "strong", "pastell", "distance", "trap", "trap_angle"] {
+        let style = Style::from_string(name).unwrap();
+        let json = serde_json::to_string(&style).unwrap();
+        assert_eq!(serde_json::from_str::<Style>(&json).unwrap(), style, "{}", json);
+        assert_eq!(serde_json::from_str::<Style>(&format!("\"{}\"", name)).unwrap(), style);
+    }
+    assert!(serde_json::from_str::<Style>("\"sparkly\"").is_err());
+
+    let definition = r#"{"name": "bands", "coloring": {"colormap": {
+        "index": {"quantity": "count", "ops": [{"div": {"base": 20, "random_count": 10}}, "fract"]},
+        "colormap": {"map": [[0, [0, 0, 0]], [1, [1, 1, 1]]], "name": "gray"}
+    }}}"#;
+    let style: Style = definition.parse().unwrap();
+    assert_eq!(style.name(), "bands");
+    assert!(!style.needs_trap());
+    assert_eq!(style.coloring, Coloring::Colormap {
+        index: Channel::new(Quantity::Count, vec![Op::Div(Param { base: 20., random_color: 0., random_count: 10. }), Op::Fract]),
+        colormap: match style.coloring { Coloring::Colormap { ref colormap, .. } => colormap.clone(), _ => unreachable!() },
+    });
+
+    let mut f = FractalBuilder::new()
+        .seed(1)
+        .zoom(&Some(0.5))
+        .center(&Some((-0.5, 0.)))
+        .style(&Some(style))
+        .build(&FractalType::Mandelbrot)
+        .unwrap();
+    let buffer = render(&mut f, (48, 27));
+    assert!(buffer.chunks(4).all(|p| p[0] == p[1] && p[1] == p[2]), "the colormap is gray");
+    assert!(buffer.chunks(4).any(|p| p[0] > 128));
+
+    let loaded = FractalBuilder::new().build(&FractalType::LoadJson(f.json())).unwrap();
+    assert!(loaded.json().contains("\"name\":\"bands\""));
+}
+
+#[test]
+fn test_normalization() {
+    assert_eq!("equalize".parse::<Normalization>().unwrap(), Normalization::Equalize);
+    assert_eq!("percentile:5,95".parse::<Normalization>().unwrap(), Normalization::Percentile(5., 95.));
+    assert!("percentile:95,5".parse::<Normalization>().is_err());
+    assert!("cubic".parse::<Normalization>().is_err());
+
+    let distinct = |normalization: Option<Normalization>| {
+        let mut f = FractalBuilder::new()
+            .seed(3)
+            .zoom(&Some(0.5))
+            .center(&Some((-0.5, 0.)))
+            .normalization(&normalization)
+            .build(&FractalType::Mandelbrot)
+            .unwrap();
+        (distinct_colors(&render(&mut f, (64, 36))), f.json())
+    };
+
+    // most pixels escape within a few iterations, which the linear map squeezes into few colors
+    let (linear, _) = distinct(None);
+    let (equalized, json) = distinct(Some(Normalization::Equalize));
+    assert!(equalized > linear, "equalized {} <= linear {}", equalized, linear);
+    assert!(json.contains("\"normalization\":\"Equalize\""));
+}
+
+#[test]
+fn test_nebulabrot() {
+    let render = |limits: [u64; 3], anti: bool| {
+        let mut f = FractalBuilder::new()
+            .seed(3)
+            .limits(&Some(limits))
+            .anti(&Some(anti))
+            .build(&FractalType::Buddhabrot)
+            .unwrap();
+        let buffer = render(&mut f, (32, 24));
+        (f.description().to_owned(), buffer)
+    };
+
+    // short orbits count for every channel, the long ones only for blue
+    let (description, buffer) = render([20, 200, 2000], false);
+    assert_eq!(description, "Nebulabrot with 20, 200 and 2000 iterations");
+    let sum = |channel: usize| buffer.chunks(4).map(|p| p[channel] as u64).sum::<u64>();
+    assert!(sum(0) > 0);
+    assert!(sum(0) < sum(1) && sum(1) < sum(2), "{} {} {}", sum(0), sum(1), sum(2));
+
+    // the Buddhabrot is gray
+    let (description, buffer) = render([100, 100, 100], false);
+    assert_eq!(description, "Buddhabrot with 100 iterations");
+    assert!(buffer.chunks(4).all(|p| p[0] == p[1] && p[1] == p[2]));
+    assert!(buffer.chunks(4).any(|p| p[0] > 0));
+
+    let (description, buffer) = render([50, 50, 50], true);
+    assert_eq!(description, "Anti-Buddhabrot with 50 iterations");
+    assert!(buffer.chunks(4).any(|p| p[0] > 0));
+}